@@ -0,0 +1,96 @@
+//! Times the history based queries over a database filled with synthetic events, first with the
+//! current views and then with the previous correlated subquery ones
+//!
+//! Run with `cargo bench --bench history [events]`
+
+use std::time::Instant;
+
+use diesel::connection::SimpleConnection;
+use diesel::sqlite::SqliteConnection;
+use oclock_sqlite::connection::{self, MemoryDB, Storage};
+use oclock_sqlite::constants::SystemEventType;
+use oclock_sqlite::mappers;
+use oclock_sqlite::models::{NewEvent, NewTask};
+
+// The correlated subquery views are quadratic in the number of events, keep the default run short
+const DEFAULT_EVENTS: usize = 20_000;
+const TASKS: usize = 20;
+const START_TIMESTAMP: i32 = 1_500_000_000;
+
+/// Views computing the interval end with a correlated subquery, without the timestamp index
+const CORRELATED_SUBQUERY_VIEWS: &str =
+    include_str!("../migrations/2026-10-19-120000_history_window_view/down.sql");
+
+fn time_queries(conn: &mut SqliteConnection, case: &str, events: usize) {
+    println!("{}:", case);
+
+    let started = Instant::now();
+    let timesheet = mappers::timesheet::full_timesheet(conn).unwrap();
+    println!(
+        "  full_timesheet: {} rows in {:?}",
+        timesheet.len(),
+        started.elapsed()
+    );
+
+    let last_week = START_TIMESTAMP + (events as i32) * 1200 - 7 * 24 * 3600;
+    let started = Instant::now();
+    let history = mappers::history::task_history_since(conn, 2, last_week).unwrap();
+    println!(
+        "  task_history_since: {} rows in {:?}",
+        history.len(),
+        started.elapsed()
+    );
+
+    let started = Instant::now();
+    let task = mappers::events::current_task(conn).unwrap();
+    println!(
+        "  current_task: {:?} in {:?}",
+        task.map(|t| t.name),
+        started.elapsed()
+    );
+}
+
+fn main() {
+    let events = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_EVENTS);
+
+    let storage = MemoryDB::new().expect("Error creating database");
+    let mut conn = storage.establish_connection().unwrap();
+
+    let started = Instant::now();
+    connection::transaction(&mut conn, |conn| {
+        for task in 0..TASKS {
+            let new_task = NewTask {
+                name: format!("task-{}", task),
+            };
+            mappers::tasks::create_task(conn, &new_task)?;
+        }
+        for event in 0..events {
+            // Switch task every ~20 minutes, with an occasional pause
+            let new_event = NewEvent {
+                event_timestamp: START_TIMESTAMP + (event as i32) * 1200 + (event as i32 % 7),
+                task_id: match event % 11 {
+                    0 => None,
+                    n => Some((n % TASKS + 1) as i32),
+                },
+                system_event_name: match event % 11 {
+                    0 => Some(SystemEventType::Pause.to_string()),
+                    _ => None,
+                },
+                note: None,
+            };
+            mappers::events::push_event(conn, &new_event)?;
+        }
+        Ok(())
+    })
+    .expect("Error inserting events");
+    println!("inserted {} events in {:?}", events, started.elapsed());
+
+    time_queries(&mut conn, "window function views", events);
+
+    conn.batch_execute(CORRELATED_SUBQUERY_VIEWS)
+        .expect("Error restoring the correlated subquery views");
+    time_queries(&mut conn, "correlated subquery views", events);
+}
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+CREATE TABLE pomodoro_cycles (
+    id INTEGER PRIMARY KEY NOT NULL,
+    task_id INTEGER NOT NULL,
+    started_at INTEGER NOT NULL,
+    completed_at INTEGER NOT NULL,
+
+    FOREIGN KEY(task_id) REFERENCES tasks(id) ON UPDATE CASCADE
+);
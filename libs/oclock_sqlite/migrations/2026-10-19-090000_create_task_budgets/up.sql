@@ -0,0 +1,13 @@
+-- Your SQL goes here
+
+CREATE TABLE task_budgets (
+    id INTEGER PRIMARY KEY NOT NULL,
+    task_id INTEGER NOT NULL,
+    period VARCHAR NOT NULL,
+    amount INTEGER NOT NULL,
+    warning_percent INTEGER DEFAULT 80 NOT NULL,
+
+    FOREIGN KEY(task_id) REFERENCES tasks(id) ON UPDATE CASCADE,
+
+    UNIQUE (task_id, period)
+);
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+
+CREATE TABLE scheduled_switches (
+    id INTEGER PRIMARY KEY NOT NULL,
+    task_id INTEGER,
+    minute_of_day INTEGER NOT NULL,
+    weekdays INTEGER DEFAULT 127 NOT NULL,
+    duration INTEGER,
+
+    FOREIGN KEY(task_id) REFERENCES tasks(id) ON UPDATE CASCADE,
+
+    CHECK (minute_of_day >= 0 AND minute_of_day < 1440)
+);
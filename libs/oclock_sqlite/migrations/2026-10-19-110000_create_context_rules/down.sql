@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE context_switches;
+DROP TABLE context_rules;
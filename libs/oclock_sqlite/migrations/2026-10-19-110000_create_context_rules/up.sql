@@ -0,0 +1,20 @@
+-- Your SQL goes here
+
+CREATE TABLE context_rules (
+    id INTEGER PRIMARY KEY NOT NULL,
+    context_kind VARCHAR NOT NULL,
+    pattern VARCHAR NOT NULL,
+    task_id INTEGER NOT NULL,
+
+    FOREIGN KEY(task_id) REFERENCES tasks(id) ON UPDATE CASCADE
+);
+
+CREATE TABLE context_switches (
+    id INTEGER PRIMARY KEY NOT NULL,
+    event_id INTEGER NOT NULL,
+    context_rule_id INTEGER NOT NULL,
+    context_kind VARCHAR NOT NULL,
+    context_value VARCHAR NOT NULL,
+
+    FOREIGN KEY(event_id) REFERENCES events(id) ON UPDATE CASCADE ON DELETE CASCADE
+);
@@ -0,0 +1,64 @@
+-- Compute the interval end with a window function instead of a correlated subquery,
+-- events sharing the same timestamp are ordered by insertion
+DROP VIEW v_timesheet;
+DROP VIEW v_history;
+
+CREATE INDEX events_event_timestamp_idx ON events (event_timestamp, id);
+
+CREATE VIEW v_history AS
+SELECT
+    e.id              AS "id",
+    e.ts_start        AS "ts_start",
+    e.ts_end          AS "ts_end",
+    e.system_event    AS "system_event",
+    t.name            AS "task_name",
+    t.id              AS "task_id"
+FROM
+    (
+        SELECT
+            id,
+            task_id,
+            system_event_name AS system_event,
+            event_timestamp AS ts_start,
+            LEAD(event_timestamp) OVER (ORDER BY event_timestamp, id) AS ts_end
+        FROM
+            events
+    ) e
+    LEFT JOIN tasks t ON t.id = e.task_id
+ORDER BY
+    e.ts_start ASC,
+    e.id ASC
+;
+
+CREATE VIEW v_timesheet AS
+SELECT
+    min(vh.id) as id,
+    vh.day,
+    vh.task_id,
+    vh.task_name,
+    vh.system_event,
+    sum(vh.ts_end - vh.ts_start) AS amount
+FROM
+    (
+        SELECT
+            id,
+            task_id,
+            task_name,
+            system_event,
+            ts_start,
+            ts_end,
+            date(ts_start, 'unixepoch', 'localtime') AS day
+        FROM
+            v_history
+    ) vh
+WHERE
+    vh.ts_end IS NOT NULL
+GROUP BY
+    vh.day,
+    vh.task_id,
+    vh.task_name,
+    vh.system_event
+ORDER BY
+    vh.day,
+    vh.task_name
+;
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+
+CREATE TABLE task_rates (
+    id INTEGER PRIMARY KEY NOT NULL,
+    task_id INTEGER NOT NULL,
+    hourly_rate INTEGER NOT NULL,
+    currency VARCHAR NOT NULL,
+    effective_from VARCHAR NOT NULL,
+
+    FOREIGN KEY(task_id) REFERENCES tasks(id) ON UPDATE CASCADE,
+
+    UNIQUE (task_id, effective_from)
+);
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+
+CREATE TABLE work_targets (
+    id INTEGER PRIMARY KEY NOT NULL,
+    effective_from VARCHAR NOT NULL,
+    weekdays INTEGER NOT NULL,
+    daily_minutes INTEGER NOT NULL,
+
+    UNIQUE (effective_from)
+);
+
+CREATE TABLE holidays (
+    id INTEGER PRIMARY KEY NOT NULL,
+    day VARCHAR NOT NULL,
+    description VARCHAR NOT NULL,
+
+    UNIQUE (day)
+);
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+
+CREATE TABLE working_hours (
+    id INTEGER PRIMARY KEY NOT NULL,
+    weekdays INTEGER DEFAULT 31 NOT NULL,
+    start_minute INTEGER NOT NULL,
+    end_minute INTEGER NOT NULL,
+
+    CHECK (start_minute >= 0 AND start_minute < end_minute AND end_minute <= 1440)
+);
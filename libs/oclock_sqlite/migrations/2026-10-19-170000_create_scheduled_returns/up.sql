@@ -0,0 +1,12 @@
+-- Your SQL goes here
+
+CREATE TABLE scheduled_returns (
+    switch_id INTEGER PRIMARY KEY NOT NULL,
+    task_id INTEGER,
+    return_at INTEGER NOT NULL,
+    previous_task_id INTEGER,
+
+    FOREIGN KEY(switch_id) REFERENCES scheduled_switches(id) ON DELETE CASCADE,
+    FOREIGN KEY(task_id) REFERENCES tasks(id) ON UPDATE CASCADE,
+    FOREIGN KEY(previous_task_id) REFERENCES tasks(id) ON UPDATE CASCADE
+);
@@ -0,0 +1,276 @@
+use std::collections::HashSet;
+use std::ffi::{CStr, CString};
+use std::fs;
+use std::os::raw::c_int;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::thread;
+use std::time::Duration;
+
+use diesel::connection::SimpleConnection;
+use diesel::migration::MigrationSource;
+use diesel::prelude::*;
+use diesel::sql_types::Text;
+use diesel::sqlite::{Sqlite, SqliteConnection};
+use diesel_migrations::MigrationHarness;
+use libsqlite3_sys as ffi;
+
+use crate::connection::{self, BUSY_TIMEOUT_MS, MIGRATIONS};
+
+#[derive(QueryableByName)]
+struct TableName {
+    #[diesel(sql_type = Text)]
+    name: String,
+}
+
+/// Write a consistent snapshot of the database to the given file, replacing it when present
+///
+/// The pages are copied with the SQLite online backup API from a connection of its own, so the
+/// snapshot can be taken while other connections keep writing.
+pub fn backup_into(database_url: &str, path: &Path) -> Result<(), String> {
+    let staging = PathBuf::from(format!("{}.partial", path.display()));
+    if staging.exists() {
+        fs::remove_file(&staging)
+            .map_err(|e| format!("Error removing {}: '{}'", staging.display(), e))?;
+    }
+
+    copy_database(database_url, &staging)
+        .map_err(|e| format!("Error writing backup to {}: '{}'", staging.display(), e))?;
+
+    fs::rename(&staging, path)
+        .map_err(|e| format!("Error moving backup to {}: '{}'", path.display(), e))
+}
+
+/// Connection opened through the SQLite C API, diesel doesn't expose the handle of its own
+struct RawConnection(*mut ffi::sqlite3);
+
+impl RawConnection {
+    fn open(database_url: &str, flags: c_int) -> Result<RawConnection, String> {
+        let url = CString::new(database_url).map_err(|e| e.to_string())?;
+        let mut handle = ptr::null_mut();
+        let code = unsafe { ffi::sqlite3_open_v2(url.as_ptr(), &mut handle, flags, ptr::null()) };
+        // The handle is allocated even when opening fails and must be closed anyway
+        let connection = RawConnection(handle);
+        if code != ffi::SQLITE_OK {
+            return Err(connection.error_message());
+        }
+        unsafe { ffi::sqlite3_busy_timeout(connection.0, BUSY_TIMEOUT_MS as c_int) };
+        Ok(connection)
+    }
+
+    fn error_message(&self) -> String {
+        if self.0.is_null() {
+            return String::from("out of memory");
+        }
+        unsafe { CStr::from_ptr(ffi::sqlite3_errmsg(self.0)) }
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+impl Drop for RawConnection {
+    fn drop(&mut self) {
+        unsafe { ffi::sqlite3_close(self.0) };
+    }
+}
+
+fn copy_database(database_url: &str, staging: &Path) -> Result<(), String> {
+    let source = RawConnection::open(
+        database_url,
+        ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_URI,
+    )?;
+    let destination = RawConnection::open(
+        &staging.display().to_string(),
+        ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE,
+    )?;
+
+    let main = c"main";
+    let backup =
+        unsafe { ffi::sqlite3_backup_init(destination.0, main.as_ptr(), source.0, main.as_ptr()) };
+    if backup.is_null() {
+        return Err(destination.error_message());
+    }
+
+    // Copy all the pages in a single step, waiting for the locks held by writers
+    let mut waited_ms = 0;
+    let step = loop {
+        match unsafe { ffi::sqlite3_backup_step(backup, -1) } {
+            ffi::SQLITE_BUSY | ffi::SQLITE_LOCKED if waited_ms < BUSY_TIMEOUT_MS => {
+                thread::sleep(Duration::from_millis(100));
+                waited_ms += 100;
+            }
+            code => break code,
+        }
+    };
+    let finish = unsafe { ffi::sqlite3_backup_finish(backup) };
+
+    if step != ffi::SQLITE_DONE {
+        let message = unsafe { CStr::from_ptr(ffi::sqlite3_errstr(step)) };
+        return Err(message.to_string_lossy().into_owned());
+    }
+    if finish != ffi::SQLITE_OK {
+        return Err(destination.error_message());
+    }
+    Ok(())
+}
+
+/// Replace all the data with the content of the given backup
+///
+/// The backup is rejected when it was written by a version of oclock with unknown migrations,
+/// older backups are upgraded to the current schema before being copied.
+pub fn restore_from(conn: &mut SqliteConnection, path: &Path) -> Result<(), String> {
+    if !path.is_file() {
+        return Err(format!("Backup {} not found", path.display()));
+    }
+
+    let staging = std::env::temp_dir().join(format!("oclock-restore-{}.db", std::process::id()));
+    fs::copy(path, &staging)
+        .map_err(|e| format!("Error reading backup {}: '{}'", path.display(), e))?;
+
+    let out = upgrade_backup(&staging).and_then(|_| copy_tables(conn, &staging));
+
+    if let Err(err) = fs::remove_file(&staging) {
+        log::warn!("Error removing {} - {err}", staging.display());
+    }
+    out
+}
+
+fn upgrade_backup(staging: &Path) -> Result<(), String> {
+    let mut backup_conn = SqliteConnection::establish(&staging.display().to_string())
+        .map_err(|e| format!("Error opening backup: '{}'", e))?;
+
+    let applied = backup_conn
+        .applied_migrations()
+        .map_err(|e| format!("Error reading backup migrations: '{}'", e))?;
+    if applied.is_empty() {
+        return Err(String::from("Backup is not an oclock database"));
+    }
+
+    let known: HashSet<String> = MigrationSource::<Sqlite>::migrations(&MIGRATIONS)
+        .map_err(|e| format!("Error reading migrations: '{}'", e))?
+        .iter()
+        .map(|m| m.name().version().to_string())
+        .collect();
+    if let Some(unknown) = applied.iter().find(|v| !known.contains(&v.to_string())) {
+        return Err(format!(
+            "Backup was written by a newer version of oclock (unknown migration {})",
+            unknown
+        ));
+    }
+
+    backup_conn
+        .run_pending_migrations(MIGRATIONS)
+        .map(|_| ())
+        .map_err(|e| format!("Error upgrading backup: '{}'", e))
+}
+
+fn copy_tables(conn: &mut SqliteConnection, staging: &Path) -> Result<(), String> {
+    diesel::sql_query("ATTACH DATABASE ? AS restored")
+        .bind::<Text, _>(staging.display().to_string())
+        .execute(conn)
+        .map_err(|e| format!("Error attaching backup: '{}'", e))?;
+
+    let out = connection::transaction(conn, |conn| {
+        let tables: Vec<TableName> = diesel::sql_query(
+            "SELECT name FROM main.sqlite_master \
+             WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name <> '__diesel_schema_migrations'",
+        )
+        .load(conn)?;
+
+        // Rows referencing each other are copied in no particular order
+        conn.batch_execute("PRAGMA defer_foreign_keys = ON")?;
+        for table in &tables {
+            conn.batch_execute(&format!("DELETE FROM main.\"{}\"", table.name))?;
+        }
+        for table in &tables {
+            conn.batch_execute(&format!(
+                "INSERT INTO main.\"{0}\" SELECT * FROM restored.\"{0}\"",
+                table.name
+            ))?;
+        }
+        Ok(())
+    })
+    .map_err(|e| format!("Error restoring backup: '{}'", e));
+
+    if let Err(err) = conn.batch_execute("DETACH DATABASE restored") {
+        log::warn!("Error detaching backup - {err}");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::{MemoryDB, Storage};
+    use crate::mappers;
+    use crate::models::NewTask;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("oclock-{}-{}.db", name, std::process::id()))
+    }
+
+    fn push_task(storage: &MemoryDB, name: &str) {
+        let new_task = NewTask {
+            name: String::from(name),
+        };
+        mappers::tasks::create_task(&mut storage.establish_connection().unwrap(), &new_task)
+            .unwrap();
+    }
+
+    fn task_names(storage: &MemoryDB) -> Vec<String> {
+        mappers::tasks::list_tasks(&mut storage.establish_connection().unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_backup_and_restore() {
+        let path = temp_path("backup");
+        let storage = MemoryDB::new().unwrap();
+        push_task(&storage, "dev");
+        backup_into(storage.database_url(), &path).unwrap();
+
+        push_task(&storage, "meeting");
+        assert_eq!(task_names(&storage), vec!["dev", "meeting"]);
+
+        restore_from(&mut storage.establish_connection().unwrap(), &path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(task_names(&storage), vec!["dev"]);
+    }
+
+    #[test]
+    fn test_restore_rejects_unknown_migrations() {
+        let path = temp_path("newer");
+        let storage = MemoryDB::new().unwrap();
+        backup_into(storage.database_url(), &path).unwrap();
+
+        let mut backup_conn = SqliteConnection::establish(&path.display().to_string()).unwrap();
+        backup_conn
+            .batch_execute(
+                "INSERT INTO __diesel_schema_migrations (version) VALUES ('29991231000000')",
+            )
+            .unwrap();
+        drop(backup_conn);
+
+        let out = restore_from(&mut storage.establish_connection().unwrap(), &path);
+        fs::remove_file(&path).unwrap();
+        assert!(out.unwrap_err().contains("29991231000000"));
+    }
+
+    #[test]
+    fn test_restore_rejects_foreign_database() {
+        let path = temp_path("foreign");
+        let mut foreign_conn = SqliteConnection::establish(&path.display().to_string()).unwrap();
+        foreign_conn
+            .batch_execute("CREATE TABLE notes (id INTEGER)")
+            .unwrap();
+        drop(foreign_conn);
+
+        let storage = MemoryDB::new().unwrap();
+        let out = restore_from(&mut storage.establish_connection().unwrap(), &path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(out, Err(String::from("Backup is not an oclock database")));
+    }
+}
@@ -1,40 +1,234 @@
+use std::cell::{RefCell, RefMut};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use diesel::dsl::sql;
 use diesel::prelude::*;
+use diesel::result::Error;
 use diesel::sql_types::*;
 use diesel::sqlite::SqliteConnection;
+use diesel::connection::SimpleConnection;
 use diesel::Connection;
 use diesel_migrations::EmbeddedMigrations;
 use diesel_migrations::MigrationHarness;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
+/// Path selecting a database living in memory for the lifetime of the storage
+pub const IN_MEMORY: &str = ":memory:";
+
+/// Database holding the oclock data
+pub trait Storage {
+    fn establish_connection(&self) -> Result<StorageConnection<'_>, String>;
+
+    /// Location of the database, other connections opened on it see the same data
+    fn database_url(&self) -> &str;
+}
+
+impl<S: Storage + ?Sized> Storage for Rc<S> {
+    fn establish_connection(&self) -> Result<StorageConnection<'_>, String> {
+        (**self).establish_connection()
+    }
+
+    fn database_url(&self) -> &str {
+        (**self).database_url()
+    }
+}
+
+impl<S: Storage + ?Sized> Storage for Box<S> {
+    fn establish_connection(&self) -> Result<StorageConnection<'_>, String> {
+        (**self).establish_connection()
+    }
+
+    fn database_url(&self) -> &str {
+        (**self).database_url()
+    }
+}
+
+/// Connection handed out by a storage, kept borrowed until the operation completes
+pub type StorageConnection<'a> = RefMut<'a, SqliteConnection>;
+
+/// Milliseconds a statement waits for a lock held by another process before failing
+pub const BUSY_TIMEOUT_MS: u32 = 5000;
+
+/// Open the storage at the given path, `:memory:` selects an in-memory database
+pub fn open(database_path: &str) -> Result<Box<dyn Storage>, String> {
+    if database_path == IN_MEMORY {
+        Ok(Box::new(MemoryDB::new()?))
+    } else {
+        Ok(Box::new(DB::new(database_path.to_string())?))
+    }
+}
+
+/// Run the given operations atomically, taking the write lock upfront so that concurrent writers
+/// wait for the busy timeout instead of failing halfway
+pub fn transaction<T>(
+    connection: &mut SqliteConnection,
+    operations: impl FnOnce(&mut SqliteConnection) -> Result<T, Error>,
+) -> Result<T, Error> {
+    connection.immediate_transaction(operations)
+}
+
+fn connect(connection_string: &str) -> Result<SqliteConnection, String> {
+    let mut connection = SqliteConnection::establish(connection_string).map_err(|e| {
+        format!(
+            "Error connecting to database at {}: '{}'",
+            connection_string, e
+        )
+    })?;
+
+    // Integer is a dummy placeholder. Compiling fails when passing ().
+    sql::<Integer>("PRAGMA foreign_keys = ON")
+        .execute(&mut connection)
+        .map_err(|e| format!("Error enabling foreign keys: '{}'", e))?;
+
+    Ok(connection)
+}
+
+fn migrate(connection: &mut SqliteConnection) -> Result<(), String> {
+    connection
+        .run_pending_migrations(MIGRATIONS)
+        .map(|_| ())
+        .map_err(|e| format!("Error running migrations: '{}'", e))
+}
+
+fn borrow_connection(
+    connection: &RefCell<SqliteConnection>,
+) -> Result<StorageConnection<'_>, String> {
+    connection
+        .try_borrow_mut()
+        .map_err(|_| String::from("Database connection already in use"))
+}
+
+/// SQLite database file, accessed through a single long-lived connection
+///
+/// The file is opened in WAL mode so that the daemon and offline clients can read while the
+/// other one is writing, concurrent writers wait up to [BUSY_TIMEOUT_MS] for the lock.
 pub struct DB {
     connection_string: String,
+    connection: RefCell<SqliteConnection>,
 }
 
 impl DB {
-    pub fn new(connection_string: String) -> DB {
-        let result = DB {
-            connection_string: connection_string,
-        };
+    pub fn new(connection_string: String) -> Result<DB, String> {
+        let mut connection = connect(&connection_string)?;
+        connection
+            .batch_execute(&format!(
+                "PRAGMA busy_timeout = {}; PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;",
+                BUSY_TIMEOUT_MS
+            ))
+            .map_err(|e| {
+                format!(
+                    "Error configuring database at {}: '{}'",
+                    connection_string, e
+                )
+            })?;
+        migrate(&mut connection)?;
 
-        let mut connection = result.establish_connection();
-        connection.run_pending_migrations(MIGRATIONS).unwrap();
+        Ok(DB {
+            connection_string,
+            connection: RefCell::new(connection),
+        })
+    }
+}
 
-        result
+impl Storage for DB {
+    fn establish_connection(&self) -> Result<StorageConnection<'_>, String> {
+        borrow_connection(&self.connection)
     }
 
-    pub fn establish_connection(&self) -> SqliteConnection {
-        let mut connection = SqliteConnection::establish(&self.connection_string).expect(&format!(
-            "Error connecting to database at {}",
-            self.connection_string
-        ));
+    fn database_url(&self) -> &str {
+        &self.connection_string
+    }
+}
 
-        // Integer is a dummy placeholder. Compiling fails when passing ().
-        sql::<Integer>("PRAGMA foreign_keys = ON")
-            .execute(&mut connection)
-            .expect("Should be able to enable foreign_keys");
+static MEMORY_DB_COUNT: AtomicUsize = AtomicUsize::new(0);
 
-        connection
+/// In-memory SQLite database, its connection keeps the data alive until dropped
+///
+/// Each database gets its own name in the shared cache, so that backups can read it from another
+/// connection.
+pub struct MemoryDB {
+    connection_string: String,
+    connection: RefCell<SqliteConnection>,
+}
+
+impl MemoryDB {
+    pub fn new() -> Result<MemoryDB, String> {
+        let connection_string = format!(
+            "file:oclock-memory-{}-{}?mode=memory&cache=shared",
+            std::process::id(),
+            MEMORY_DB_COUNT.fetch_add(1, Ordering::Relaxed)
+        );
+        let mut connection = connect(&connection_string)?;
+        migrate(&mut connection)?;
+
+        Ok(MemoryDB {
+            connection_string,
+            connection: RefCell::new(connection),
+        })
+    }
+}
+
+impl Storage for MemoryDB {
+    fn establish_connection(&self) -> Result<StorageConnection<'_>, String> {
+        borrow_connection(&self.connection)
+    }
+
+    fn database_url(&self) -> &str {
+        &self.connection_string
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mappers;
+    use crate::models::NewTask;
+
+    #[test]
+    fn test_memory_db_keeps_data() {
+        let storage = open(IN_MEMORY).unwrap();
+
+        let new_task = NewTask {
+            name: String::from("dev"),
+        };
+        mappers::tasks::create_task(&mut storage.establish_connection().unwrap(), &new_task)
+            .unwrap();
+
+        let tasks =
+            mappers::tasks::list_tasks(&mut storage.establish_connection().unwrap()).unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].name, "dev");
+    }
+
+    #[test]
+    fn test_file_db_settings() {
+        let path = std::env::temp_dir().join(format!("oclock-db-{}.db", std::process::id()));
+        let storage = DB::new(path.display().to_string()).unwrap();
+
+        let mut connection = storage.establish_connection().unwrap();
+        let journal_mode: String = sql::<Text>("PRAGMA journal_mode")
+            .get_result(&mut *connection)
+            .unwrap();
+        let busy_timeout: i32 = sql::<Integer>("PRAGMA busy_timeout")
+            .get_result(&mut *connection)
+            .unwrap();
+        drop(connection);
+        drop(storage);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+
+        assert_eq!(journal_mode, "wal");
+        assert_eq!(busy_timeout, BUSY_TIMEOUT_MS as i32);
+    }
+
+    #[test]
+    fn test_memory_db_single_connection() {
+        let storage = MemoryDB::new().unwrap();
+
+        let _connection = storage.establish_connection().unwrap();
+        assert!(storage.establish_connection().is_err());
     }
 }
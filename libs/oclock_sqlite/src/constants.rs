@@ -1,10 +1,14 @@
 use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
 
 #[derive(Debug)]
 pub enum SystemEventType {
     Startup,
     Shutdown,
     Ping,
+    Pause,
 }
 
 impl fmt::Display for SystemEventType {
@@ -12,3 +16,61 @@ impl fmt::Display for SystemEventType {
         write!(f, "{:?}", self)
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BudgetPeriod {
+    Day,
+    Week,
+    Month,
+    Total,
+}
+
+impl fmt::Display for BudgetPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for BudgetPeriod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Day" => Ok(BudgetPeriod::Day),
+            "Week" => Ok(BudgetPeriod::Week),
+            "Month" => Ok(BudgetPeriod::Month),
+            "Total" => Ok(BudgetPeriod::Total),
+            other => Err(format!("Unknown budget period '{}'", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ContextKind {
+    GitBranch,
+    WorkingDirectory,
+    WindowTitle,
+    CalendarEntry,
+}
+
+impl fmt::Display for ContextKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FromStr for ContextKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "GitBranch" => Ok(ContextKind::GitBranch),
+            "WorkingDirectory" => Ok(ContextKind::WorkingDirectory),
+            "WindowTitle" => Ok(ContextKind::WindowTitle),
+            "CalendarEntry" => Ok(ContextKind::CalendarEntry),
+            other => Err(format!("Unknown context kind '{}'", other)),
+        }
+    }
+}
@@ -0,0 +1,591 @@
+use std::collections::{HashMap, HashSet};
+
+use diesel::connection::SimpleConnection;
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sql_types::Integer;
+use diesel::sqlite::SqliteConnection;
+use serde::{Deserialize, Serialize};
+
+use crate::connection;
+use crate::constants::SystemEventType;
+use crate::models::{
+    ContextRule, ContextSwitch, Event, Holiday, PomodoroCycle, ScheduledSwitch, Task, TaskBudget,
+    TaskRate, WorkTarget, WorkingHours,
+};
+use crate::schema::{
+    context_rules, context_switches, events, holidays, pomodoro_cycles, scheduled_returns,
+    scheduled_switches, task_budgets, task_rates, tasks, work_targets, working_hours,
+};
+
+/// Version of the dump format, increased whenever a table is added or changed so that older
+/// versions of oclock reject the dumps they would only partly import
+pub const DUMP_VERSION: u32 = 5;
+
+/// Rows inserted by a single statement, keeps the bound parameters below the SQLite limit
+const INSERT_CHUNK_SIZE: usize = 1000;
+
+/// Full content of the database, the ids are the ones of the exporting database
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Dump {
+    pub version: u32,
+    pub tasks: Vec<Task>,
+    pub events: Vec<Event>,
+    pub pomodoro_cycles: Vec<PomodoroCycle>,
+    pub task_budgets: Vec<TaskBudget>,
+    pub scheduled_switches: Vec<ScheduledSwitch>,
+    pub context_rules: Vec<ContextRule>,
+    pub context_switches: Vec<ContextSwitch>,
+    /// Missing in the dumps of version 1, written before rates were introduced
+    #[serde(default)]
+    pub task_rates: Vec<TaskRate>,
+    /// Missing in the dumps up to version 2, written before work targets were introduced
+    #[serde(default)]
+    pub work_targets: Vec<WorkTarget>,
+    #[serde(default)]
+    pub holidays: Vec<Holiday>,
+    /// Missing in the dumps up to version 3, written before working hours were introduced
+    #[serde(default)]
+    pub working_hours: Vec<WorkingHours>,
+}
+
+/// Number of rows written by an import for each kind of record
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct ImportSummary {
+    pub tasks: usize,
+    pub events: usize,
+    pub pomodoro_cycles: usize,
+    pub task_budgets: usize,
+    pub scheduled_switches: usize,
+    pub context_rules: usize,
+    pub context_switches: usize,
+    pub task_rates: usize,
+    pub work_targets: usize,
+    pub holidays: usize,
+    pub working_hours: usize,
+}
+
+/// Read all data, the transient ping event of a running daemon is left out
+pub fn read_dump(conn: &mut SqliteConnection) -> Result<Dump, Error> {
+    conn.transaction(|conn| {
+        Ok(Dump {
+            version: DUMP_VERSION,
+            tasks: tasks::table.order(tasks::id).load(conn)?,
+            events: events::table
+                .filter(
+                    events::system_event_name
+                        .ne(SystemEventType::Ping.to_string())
+                        .or(events::system_event_name.is_null()),
+                )
+                .order(events::id)
+                .load(conn)?,
+            pomodoro_cycles: pomodoro_cycles::table
+                .order(pomodoro_cycles::id)
+                .load(conn)?,
+            task_budgets: task_budgets::table.order(task_budgets::id).load(conn)?,
+            scheduled_switches: scheduled_switches::table
+                .order(scheduled_switches::id)
+                .load(conn)?,
+            context_rules: context_rules::table.order(context_rules::id).load(conn)?,
+            context_switches: context_switches::table
+                .order(context_switches::id)
+                .load(conn)?,
+            task_rates: task_rates::table.order(task_rates::id).load(conn)?,
+            work_targets: work_targets::table.order(work_targets::id).load(conn)?,
+            holidays: holidays::table.order(holidays::id).load(conn)?,
+            working_hours: working_hours::table.order(working_hours::id).load(conn)?,
+        })
+    })
+}
+
+/// Check the dump version and that all references point to records of the dump itself
+fn validate(dump: &Dump) -> Result<(), String> {
+    if dump.version == 0 || dump.version > DUMP_VERSION {
+        return Err(format!(
+            "Unsupported dump version {}, expected at most {}",
+            dump.version, DUMP_VERSION
+        ));
+    }
+
+    let task_ids: HashSet<i32> = dump.tasks.iter().map(|t| t.id).collect();
+    let event_ids: HashSet<i32> = dump.events.iter().map(|e| e.id).collect();
+
+    let referenced_tasks = dump
+        .events
+        .iter()
+        .filter_map(|e| e.task_id)
+        .chain(dump.pomodoro_cycles.iter().map(|c| c.task_id))
+        .chain(dump.task_budgets.iter().map(|b| b.task_id))
+        .chain(dump.scheduled_switches.iter().filter_map(|s| s.task_id))
+        .chain(dump.context_rules.iter().map(|r| r.task_id))
+        .chain(dump.task_rates.iter().map(|r| r.task_id));
+    for task_id in referenced_tasks {
+        if !task_ids.contains(&task_id) {
+            return Err(format!("Dump references missing task {}", task_id));
+        }
+    }
+
+    for switch in &dump.context_switches {
+        if !event_ids.contains(&switch.event_id) {
+            return Err(format!("Dump references missing event {}", switch.event_id));
+        }
+    }
+
+    Ok(())
+}
+
+fn last_insert_rowid(conn: &mut SqliteConnection) -> Result<i32, Error> {
+    diesel::select(sql::<Integer>("last_insert_rowid()")).get_result(conn)
+}
+
+/// Delete all data and insert the dump keeping its ids
+pub fn replace_all(conn: &mut SqliteConnection, dump: &Dump) -> Result<ImportSummary, String> {
+    validate(dump)?;
+
+    connection::transaction(conn, |conn| {
+        conn.batch_execute("PRAGMA defer_foreign_keys = ON")?;
+        // Pending returns are runtime state of the daemon, not part of the dumps
+        diesel::delete(scheduled_returns::table).execute(conn)?;
+        diesel::delete(working_hours::table).execute(conn)?;
+        diesel::delete(holidays::table).execute(conn)?;
+        diesel::delete(work_targets::table).execute(conn)?;
+        diesel::delete(task_rates::table).execute(conn)?;
+        diesel::delete(context_switches::table).execute(conn)?;
+        diesel::delete(context_rules::table).execute(conn)?;
+        diesel::delete(scheduled_switches::table).execute(conn)?;
+        diesel::delete(task_budgets::table).execute(conn)?;
+        diesel::delete(pomodoro_cycles::table).execute(conn)?;
+        diesel::delete(events::table).execute(conn)?;
+        diesel::delete(tasks::table).execute(conn)?;
+
+        for chunk in dump.tasks.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(tasks::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in dump.events.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(events::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in dump.pomodoro_cycles.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(pomodoro_cycles::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in dump.task_budgets.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(task_budgets::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in dump.scheduled_switches.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(scheduled_switches::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in dump.context_rules.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(context_rules::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in dump.context_switches.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(context_switches::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in dump.task_rates.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(task_rates::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in dump.work_targets.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(work_targets::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in dump.holidays.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(holidays::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in dump.working_hours.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(working_hours::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+
+        Ok(ImportSummary {
+            tasks: dump.tasks.len(),
+            events: dump.events.len(),
+            pomodoro_cycles: dump.pomodoro_cycles.len(),
+            task_budgets: dump.task_budgets.len(),
+            scheduled_switches: dump.scheduled_switches.len(),
+            context_rules: dump.context_rules.len(),
+            context_switches: dump.context_switches.len(),
+            task_rates: dump.task_rates.len(),
+            work_targets: dump.work_targets.len(),
+            holidays: dump.holidays.len(),
+            working_hours: dump.working_hours.len(),
+        })
+    })
+    .map_err(|e| format!("Error importing dump: '{}'", e))
+}
+
+/// Add the dump to the existing data assigning new ids
+///
+/// Tasks are matched by name ignoring case, records already present are skipped so that importing the same
+/// dump twice doesn't duplicate anything.
+pub fn merge(conn: &mut SqliteConnection, dump: &Dump) -> Result<ImportSummary, String> {
+    validate(dump)?;
+
+    connection::transaction(conn, |conn| {
+        let mut summary = ImportSummary::default();
+
+        let existing_tasks: Vec<Task> = tasks::table.load(conn)?;
+        let mut task_ids = HashMap::new();
+        for task in &dump.tasks {
+            let new_id = match existing_tasks
+                .iter()
+                .find(|t| t.name.eq_ignore_ascii_case(&task.name))
+            {
+                Some(existing) => existing.id,
+                None => {
+                    diesel::insert_into(tasks::table)
+                        .values((tasks::name.eq(&task.name), tasks::enabled.eq(task.enabled)))
+                        .execute(conn)?;
+                    summary.tasks += 1;
+                    last_insert_rowid(conn)?
+                }
+            };
+            task_ids.insert(task.id, new_id);
+        }
+
+        let mut existing_events: HashSet<(i32, Option<i32>, Option<String>)> = events::table
+            .load::<Event>(conn)?
+            .into_iter()
+            .map(|e| (e.event_timestamp, e.task_id, e.system_event_name))
+            .collect();
+        let mut event_ids = HashMap::new();
+        for event in &dump.events {
+            let key = (
+                event.event_timestamp,
+                event.task_id.map(|id| task_ids[&id]),
+                event.system_event_name.clone(),
+            );
+            if existing_events.contains(&key) {
+                continue;
+            }
+            diesel::insert_into(events::table)
+                .values((
+                    events::event_timestamp.eq(key.0),
+                    events::task_id.eq(key.1),
+                    events::system_event_name.eq(&key.2),
+                    events::note.eq(&event.note),
+                ))
+                .execute(conn)?;
+            event_ids.insert(event.id, last_insert_rowid(conn)?);
+            existing_events.insert(key);
+            summary.events += 1;
+        }
+
+        let mut existing_cycles: HashSet<(i32, i32, i32)> = pomodoro_cycles::table
+            .load::<PomodoroCycle>(conn)?
+            .into_iter()
+            .map(|c| (c.task_id, c.started_at, c.completed_at))
+            .collect();
+        for cycle in &dump.pomodoro_cycles {
+            let key = (
+                task_ids[&cycle.task_id],
+                cycle.started_at,
+                cycle.completed_at,
+            );
+            if existing_cycles.insert(key) {
+                diesel::insert_into(pomodoro_cycles::table)
+                    .values((
+                        pomodoro_cycles::task_id.eq(key.0),
+                        pomodoro_cycles::started_at.eq(key.1),
+                        pomodoro_cycles::completed_at.eq(key.2),
+                    ))
+                    .execute(conn)?;
+                summary.pomodoro_cycles += 1;
+            }
+        }
+
+        // Budgets already set on a task for the same period are kept
+        for budget in &dump.task_budgets {
+            summary.task_budgets += diesel::insert_or_ignore_into(task_budgets::table)
+                .values((
+                    task_budgets::task_id.eq(task_ids[&budget.task_id]),
+                    task_budgets::period.eq(&budget.period),
+                    task_budgets::amount.eq(budget.amount),
+                    task_budgets::warning_percent.eq(budget.warning_percent),
+                ))
+                .execute(conn)?;
+        }
+
+        let mut existing_switches: HashSet<(Option<i32>, i32, i32, Option<i32>)> =
+            scheduled_switches::table
+                .load::<ScheduledSwitch>(conn)?
+                .into_iter()
+                .map(|s| (s.task_id, s.minute_of_day, s.weekdays, s.duration))
+                .collect();
+        for switch in &dump.scheduled_switches {
+            let key = (
+                switch.task_id.map(|id| task_ids[&id]),
+                switch.minute_of_day,
+                switch.weekdays,
+                switch.duration,
+            );
+            if existing_switches.insert(key) {
+                diesel::insert_into(scheduled_switches::table)
+                    .values((
+                        scheduled_switches::task_id.eq(key.0),
+                        scheduled_switches::minute_of_day.eq(key.1),
+                        scheduled_switches::weekdays.eq(key.2),
+                        scheduled_switches::duration.eq(key.3),
+                    ))
+                    .execute(conn)?;
+                summary.scheduled_switches += 1;
+            }
+        }
+
+        let existing_rules: Vec<ContextRule> = context_rules::table.load(conn)?;
+        let mut rule_ids = HashMap::new();
+        for rule in &dump.context_rules {
+            let task_id = task_ids[&rule.task_id];
+            let existing = existing_rules.iter().find(|r| {
+                r.context_kind == rule.context_kind
+                    && r.pattern == rule.pattern
+                    && r.task_id == task_id
+            });
+            let new_id = match existing {
+                Some(existing) => existing.id,
+                None => {
+                    diesel::insert_into(context_rules::table)
+                        .values((
+                            context_rules::context_kind.eq(&rule.context_kind),
+                            context_rules::pattern.eq(&rule.pattern),
+                            context_rules::task_id.eq(task_id),
+                        ))
+                        .execute(conn)?;
+                    summary.context_rules += 1;
+                    last_insert_rowid(conn)?
+                }
+            };
+            rule_ids.insert(rule.id, new_id);
+        }
+
+        for switch in &dump.context_switches {
+            // Switches of skipped events are already present, the ones of deleted rules can't be mapped
+            let (Some(event_id), Some(rule_id)) = (
+                event_ids.get(&switch.event_id),
+                rule_ids.get(&switch.context_rule_id),
+            ) else {
+                continue;
+            };
+            diesel::insert_into(context_switches::table)
+                .values((
+                    context_switches::event_id.eq(event_id),
+                    context_switches::context_rule_id.eq(rule_id),
+                    context_switches::context_kind.eq(&switch.context_kind),
+                    context_switches::context_value.eq(&switch.context_value),
+                ))
+                .execute(conn)?;
+            summary.context_switches += 1;
+        }
+
+        // Rates already set on a task from the same day are kept
+        for rate in &dump.task_rates {
+            summary.task_rates += diesel::insert_or_ignore_into(task_rates::table)
+                .values((
+                    task_rates::task_id.eq(task_ids[&rate.task_id]),
+                    task_rates::hourly_rate.eq(rate.hourly_rate),
+                    task_rates::currency.eq(&rate.currency),
+                    task_rates::effective_from.eq(&rate.effective_from),
+                ))
+                .execute(conn)?;
+        }
+
+        // Targets and holidays already set for the same day are kept
+        for target in &dump.work_targets {
+            summary.work_targets += diesel::insert_or_ignore_into(work_targets::table)
+                .values((
+                    work_targets::effective_from.eq(&target.effective_from),
+                    work_targets::weekdays.eq(target.weekdays),
+                    work_targets::daily_minutes.eq(target.daily_minutes),
+                ))
+                .execute(conn)?;
+        }
+        for holiday in &dump.holidays {
+            summary.holidays += diesel::insert_or_ignore_into(holidays::table)
+                .values((
+                    holidays::day.eq(&holiday.day),
+                    holidays::description.eq(&holiday.description),
+                ))
+                .execute(conn)?;
+        }
+
+        let mut existing_hours: HashSet<(i32, i32, i32)> = working_hours::table
+            .load::<WorkingHours>(conn)?
+            .into_iter()
+            .map(|h| (h.weekdays, h.start_minute, h.end_minute))
+            .collect();
+        for hours in &dump.working_hours {
+            let key = (hours.weekdays, hours.start_minute, hours.end_minute);
+            if existing_hours.insert(key) {
+                diesel::insert_into(working_hours::table)
+                    .values((
+                        working_hours::weekdays.eq(key.0),
+                        working_hours::start_minute.eq(key.1),
+                        working_hours::end_minute.eq(key.2),
+                    ))
+                    .execute(conn)?;
+                summary.working_hours += 1;
+            }
+        }
+
+        Ok(summary)
+    })
+    .map_err(|e| format!("Error merging dump: '{}'", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::{MemoryDB, Storage};
+    use crate::constants::BudgetPeriod;
+    use crate::mappers;
+    use crate::models::{NewEvent, NewTask, NewTaskBudget, NewTaskRate};
+
+    fn sample_db(tasks: &[&str], events: &[(i32, Option<i32>)]) -> MemoryDB {
+        let storage = MemoryDB::new().unwrap();
+        let mut conn = storage.establish_connection().unwrap();
+        for name in tasks {
+            let new_task = NewTask {
+                name: name.to_string(),
+            };
+            mappers::tasks::create_task(&mut conn, &new_task).unwrap();
+        }
+        for (event_timestamp, task_id) in events {
+            let event = NewEvent {
+                event_timestamp: *event_timestamp,
+                task_id: *task_id,
+                system_event_name: None,
+                note: None,
+            };
+            mappers::events::push_event(&mut conn, &event).unwrap();
+        }
+        drop(conn);
+        storage
+    }
+
+    fn history(storage: &MemoryDB) -> Vec<(i32, Option<String>)> {
+        let mut conn = storage.establish_connection().unwrap();
+        events::table
+            .left_join(tasks::table)
+            .select((events::event_timestamp, tasks::name.nullable()))
+            .order(events::event_timestamp)
+            .load(&mut *conn)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_replace_keeps_ids() {
+        let source = sample_db(&["dev", "meeting"], &[(100, Some(2)), (200, Some(1))]);
+        let budget = NewTaskBudget {
+            task_id: 2,
+            period: BudgetPeriod::Day.to_string(),
+            amount: 3600,
+            warning_percent: 80,
+        };
+        mappers::budgets::set_budget(&mut source.establish_connection().unwrap(), &budget).unwrap();
+        let dump = read_dump(&mut source.establish_connection().unwrap()).unwrap();
+
+        let target = sample_db(&["other"], &[(50, Some(1))]);
+        let summary = replace_all(&mut target.establish_connection().unwrap(), &dump).unwrap();
+
+        assert_eq!(summary.tasks, 2);
+        assert_eq!(summary.events, 2);
+        assert_eq!(summary.task_budgets, 1);
+        assert_eq!(
+            history(&target),
+            vec![
+                (100, Some(String::from("meeting"))),
+                (200, Some(String::from("dev")))
+            ]
+        );
+        let budgets =
+            mappers::budgets::list_budgets(&mut target.establish_connection().unwrap()).unwrap();
+        assert_eq!(budgets[0].task_id, 2);
+    }
+
+    #[test]
+    fn test_merge_remaps_ids() {
+        let source = sample_db(&["meeting", "dev"], &[(100, Some(2)), (200, Some(1))]);
+        let dump = read_dump(&mut source.establish_connection().unwrap()).unwrap();
+
+        let target = sample_db(&["Dev"], &[(50, Some(1)), (100, Some(1))]);
+        let summary = merge(&mut target.establish_connection().unwrap(), &dump).unwrap();
+
+        assert_eq!(
+            summary,
+            ImportSummary {
+                tasks: 1,
+                events: 1,
+                ..ImportSummary::default()
+            }
+        );
+        assert_eq!(
+            history(&target),
+            vec![
+                (50, Some(String::from("Dev"))),
+                (100, Some(String::from("Dev"))),
+                (200, Some(String::from("meeting"))),
+            ]
+        );
+
+        let summary = merge(&mut target.establish_connection().unwrap(), &dump).unwrap();
+        assert_eq!(summary, ImportSummary::default());
+    }
+
+    #[test]
+    fn test_merge_rates() {
+        let source = sample_db(&["meeting", "dev"], &[]);
+        let rate = NewTaskRate {
+            task_id: 2,
+            hourly_rate: 8550,
+            currency: String::from("EUR"),
+            effective_from: String::from("2024-01-01"),
+        };
+        mappers::rates::set_rate(&mut source.establish_connection().unwrap(), &rate).unwrap();
+        let dump = read_dump(&mut source.establish_connection().unwrap()).unwrap();
+
+        let target = sample_db(&["dev"], &[]);
+        let summary = merge(&mut target.establish_connection().unwrap(), &dump).unwrap();
+        assert_eq!(summary.task_rates, 1);
+        let rates =
+            mappers::rates::list_rates(&mut target.establish_connection().unwrap()).unwrap();
+        assert_eq!((rates[0].task_id, rates[0].hourly_rate), (1, 8550));
+
+        let summary = merge(&mut target.establish_connection().unwrap(), &dump).unwrap();
+        assert_eq!(summary, ImportSummary::default());
+    }
+
+    #[test]
+    fn test_rejects_invalid_dump() {
+        let source = sample_db(&["dev"], &[(100, Some(1))]);
+        let mut dump = read_dump(&mut source.establish_connection().unwrap()).unwrap();
+        let target = MemoryDB::new().unwrap();
+
+        dump.version = DUMP_VERSION + 1;
+        assert!(merge(&mut target.establish_connection().unwrap(), &dump).is_err());
+
+        dump.version = DUMP_VERSION;
+        dump.tasks.clear();
+        assert_eq!(
+            replace_all(&mut target.establish_connection().unwrap(), &dump),
+            Err(String::from("Dump references missing task 1"))
+        );
+    }
+}
@@ -3,7 +3,9 @@ extern crate diesel;
 #[macro_use]
 extern crate diesel_migrations;
 
+pub mod backup;
 pub mod constants;
+pub mod dump;
 pub mod mappers;
 pub mod models;
 pub mod schema;
@@ -0,0 +1,35 @@
+use diesel;
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sqlite::SqliteConnection;
+
+use crate::models::{NewTaskBudget, TaskBudget};
+
+pub fn set_budget(conn: &mut SqliteConnection, budget: &NewTaskBudget) -> Result<usize, Error> {
+    use crate::schema::task_budgets;
+
+    diesel::replace_into(task_budgets::table)
+        .values(budget)
+        .execute(conn)
+}
+
+pub fn remove_budget(
+    conn: &mut SqliteConnection,
+    budget_task_id: i32,
+    budget_period: String,
+) -> Result<usize, Error> {
+    use crate::schema::task_budgets::dsl::*;
+
+    diesel::delete(
+        task_budgets
+            .filter(task_id.eq(budget_task_id))
+            .filter(period.eq(budget_period)),
+    )
+    .execute(conn)
+}
+
+pub fn list_budgets(conn: &mut SqliteConnection) -> Result<Vec<TaskBudget>, Error> {
+    use crate::schema::task_budgets::dsl::*;
+
+    task_budgets.order((task_id, id)).load(conn)
+}
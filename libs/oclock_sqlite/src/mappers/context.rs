@@ -0,0 +1,79 @@
+use diesel;
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sql_types::Integer;
+use diesel::sqlite::SqliteConnection;
+
+use crate::models::{ContextRule, ContextSwitchEntry, NewContextRule, NewEvent};
+
+pub fn create_rule(conn: &mut SqliteConnection, rule: &NewContextRule) -> Result<usize, Error> {
+    use crate::schema::context_rules;
+
+    diesel::insert_into(context_rules::table)
+        .values(rule)
+        .execute(conn)
+}
+
+pub fn remove_rule(conn: &mut SqliteConnection, rule_id: i32) -> Result<usize, Error> {
+    use crate::schema::context_rules::dsl::*;
+
+    diesel::delete(context_rules.filter(id.eq(rule_id))).execute(conn)
+}
+
+pub fn list_rules(conn: &mut SqliteConnection) -> Result<Vec<ContextRule>, Error> {
+    use crate::schema::context_rules::dsl::*;
+
+    context_rules.order(id).load(conn)
+}
+
+/// Push the task switch event recording the context rule that triggered it
+pub fn push_context_switch(
+    conn: &mut SqliteConnection,
+    event: &NewEvent,
+    rule: &ContextRule,
+    value: &str,
+) -> Result<usize, Error> {
+    use crate::schema::context_switches;
+    use crate::schema::events;
+
+    conn.transaction(|conn| {
+        diesel::insert_into(events::table)
+            .values(event)
+            .execute(conn)?;
+
+        let new_event_id: i32 =
+            diesel::select(sql::<Integer>("last_insert_rowid()")).get_result(conn)?;
+
+        diesel::insert_into(context_switches::table)
+            .values((
+                context_switches::event_id.eq(new_event_id),
+                context_switches::context_rule_id.eq(rule.id),
+                context_switches::context_kind.eq(&rule.context_kind),
+                context_switches::context_value.eq(value),
+            ))
+            .execute(conn)
+    })
+}
+
+pub fn list_context_switches(
+    conn: &mut SqliteConnection,
+) -> Result<Vec<ContextSwitchEntry>, Error> {
+    use crate::schema::context_switches;
+    use crate::schema::events;
+    use crate::schema::tasks;
+
+    context_switches::table
+        .inner_join(events::table.inner_join(tasks::table))
+        .select((
+            context_switches::id,
+            events::event_timestamp,
+            tasks::id,
+            tasks::name,
+            context_switches::context_rule_id,
+            context_switches::context_kind,
+            context_switches::context_value,
+        ))
+        .order(events::event_timestamp.desc())
+        .load(conn)
+}
@@ -9,6 +9,9 @@ use crate::models::{Event, NewEvent, Task};
 
 type Backend = ::diesel::sqlite::Sqlite;
 
+/// Summary of a range of events, see [`events_fingerprint`]
+pub type EventsFingerprint = (i64, Option<i32>, Option<i64>, Option<i64>);
+
 pub fn push_event(conn: &mut SqliteConnection, task: &NewEvent) -> Result<usize, Error> {
     use crate::schema::events;
 
@@ -23,6 +26,16 @@ pub fn get_last_event(conn: &mut SqliteConnection) -> Result<Event, Error> {
     events.order(event_timestamp.desc()).first(conn)
 }
 
+pub fn get_last_system_event(conn: &mut SqliteConnection) -> Result<Option<Event>, Error> {
+    use crate::schema::events::dsl::*;
+
+    events
+        .filter(system_event_name.ne(SystemEventType::Ping.to_string()))
+        .order(event_timestamp.desc())
+        .first(conn)
+        .optional()
+}
+
 pub fn remove_all_system_events(conn: &mut SqliteConnection, event_name: String) {
     use crate::schema::events::dsl::*;
 
@@ -80,3 +93,62 @@ pub fn current_task(conn: &mut SqliteConnection) -> Result<Option<Task>, Error>
         Err(e) => Err(e),
     }
 }
+
+pub fn list_events(conn: &mut SqliteConnection) -> Result<Vec<Event>, Error> {
+    use crate::schema::events::dsl::*;
+
+    events.order((event_timestamp, id)).load(conn)
+}
+
+pub fn delete_events(conn: &mut SqliteConnection, event_ids: &[i32]) -> Result<usize, Error> {
+    use crate::schema::events::dsl::*;
+
+    diesel::delete(events.filter(id.eq_any(event_ids))).execute(conn)
+}
+
+/// Turn the given events into pauses, detaching them from their task
+pub fn convert_to_pause(conn: &mut SqliteConnection, event_ids: &[i32]) -> Result<usize, Error> {
+    use crate::schema::events::dsl::*;
+
+    diesel::update(events.filter(id.eq_any(event_ids)))
+        .set((
+            task_id.eq(None::<i32>),
+            system_event_name.eq(SystemEventType::Pause.to_string()),
+        ))
+        .execute(conn)
+}
+
+/// Count, highest id and sums of the timestamps and task ids of the events before `until`, changing
+/// whenever one of them is added, removed or modified
+pub fn events_fingerprint(
+    conn: &mut SqliteConnection,
+    until: i32,
+) -> Result<EventsFingerprint, Error> {
+    use crate::schema::events::dsl::*;
+    use diesel::dsl;
+
+    events
+        .filter(event_timestamp.lt(until))
+        .select((
+            dsl::count_star(),
+            dsl::max(id),
+            dsl::sum(event_timestamp),
+            dsl::sum(task_id),
+        ))
+        .first(conn)
+}
+
+/// Ids and notes of the events with a note between `since` (inclusive) and `until` (exclusive)
+pub fn notes_between(
+    conn: &mut SqliteConnection,
+    since: i32,
+    until: i32,
+) -> Result<Vec<(i32, String)>, Error> {
+    use crate::schema::events::dsl::*;
+
+    events
+        .filter(event_timestamp.ge(since).and(event_timestamp.lt(until)))
+        .filter(note.is_not_null())
+        .select((id, note.assume_not_null()))
+        .load(conn)
+}
@@ -0,0 +1,40 @@
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sqlite::SqliteConnection;
+
+use crate::models::HistoryEntry;
+
+pub fn task_history_since(
+    conn: &mut SqliteConnection,
+    history_task_id: i32,
+    since: i32,
+) -> Result<Vec<HistoryEntry>, Error> {
+    use crate::schema::v_history::dsl::*;
+
+    v_history
+        .filter(task_id.eq(history_task_id))
+        .filter(ts_end.gt(since))
+        .order(ts_start)
+        .load(conn)
+}
+
+pub fn full_history(conn: &mut SqliteConnection) -> Result<Vec<HistoryEntry>, Error> {
+    use crate::schema::v_history::dsl::*;
+
+    v_history.order((ts_start, id)).load(conn)
+}
+
+/// Intervals overlapping `[since, until)`, including the running one
+pub fn history_between(
+    conn: &mut SqliteConnection,
+    since: i32,
+    until: i32,
+) -> Result<Vec<HistoryEntry>, Error> {
+    use crate::schema::v_history::dsl::*;
+
+    v_history
+        .filter(ts_start.lt(until))
+        .filter(ts_end.gt(since).or(ts_end.is_null()))
+        .order((ts_start, id))
+        .load(conn)
+}
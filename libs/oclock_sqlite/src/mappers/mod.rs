@@ -1,3 +1,11 @@
+pub mod budgets;
+pub mod context;
 pub mod events;
+pub mod history;
+pub mod pomodoro;
+pub mod rates;
+pub mod scheduled_switches;
 pub mod tasks;
 pub mod timesheet;
+pub mod work_targets;
+pub mod working_hours;
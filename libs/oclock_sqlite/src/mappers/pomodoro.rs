@@ -0,0 +1,14 @@
+use diesel;
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sqlite::SqliteConnection;
+
+use crate::models::NewPomodoroCycle;
+
+pub fn push_cycle(conn: &mut SqliteConnection, cycle: &NewPomodoroCycle) -> Result<usize, Error> {
+    use crate::schema::pomodoro_cycles;
+
+    diesel::insert_into(pomodoro_cycles::table)
+        .values(cycle)
+        .execute(conn)
+}
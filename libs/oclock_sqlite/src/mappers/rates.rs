@@ -0,0 +1,35 @@
+use diesel;
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sqlite::SqliteConnection;
+
+use crate::models::{NewTaskRate, TaskRate};
+
+pub fn set_rate(conn: &mut SqliteConnection, rate: &NewTaskRate) -> Result<usize, Error> {
+    use crate::schema::task_rates;
+
+    diesel::replace_into(task_rates::table)
+        .values(rate)
+        .execute(conn)
+}
+
+pub fn remove_rate(
+    conn: &mut SqliteConnection,
+    rate_task_id: i32,
+    rate_effective_from: String,
+) -> Result<usize, Error> {
+    use crate::schema::task_rates::dsl::*;
+
+    diesel::delete(
+        task_rates
+            .filter(task_id.eq(rate_task_id))
+            .filter(effective_from.eq(rate_effective_from)),
+    )
+    .execute(conn)
+}
+
+pub fn list_rates(conn: &mut SqliteConnection) -> Result<Vec<TaskRate>, Error> {
+    use crate::schema::task_rates::dsl::*;
+
+    task_rates.order((task_id, effective_from)).load(conn)
+}
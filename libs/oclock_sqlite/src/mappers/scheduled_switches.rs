@@ -0,0 +1,64 @@
+use diesel;
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sqlite::SqliteConnection;
+
+use crate::connection;
+use crate::models::{NewScheduledSwitch, ScheduledReturn, ScheduledSwitch};
+
+pub fn create_scheduled_switch(
+    conn: &mut SqliteConnection,
+    switch: &NewScheduledSwitch,
+) -> Result<usize, Error> {
+    use crate::schema::scheduled_switches;
+
+    diesel::insert_into(scheduled_switches::table)
+        .values(switch)
+        .execute(conn)
+}
+
+pub fn remove_scheduled_switch(
+    conn: &mut SqliteConnection,
+    switch_id: i32,
+) -> Result<usize, Error> {
+    use crate::schema::scheduled_switches::dsl::*;
+
+    diesel::delete(scheduled_switches.filter(id.eq(switch_id))).execute(conn)
+}
+
+pub fn list_scheduled_switches(conn: &mut SqliteConnection) -> Result<Vec<ScheduledSwitch>, Error> {
+    use crate::schema::scheduled_switches::dsl::*;
+
+    scheduled_switches.order((minute_of_day, id)).load(conn)
+}
+
+/// Pending return of the last timed scheduled switch, kept in the database to survive daemon restarts
+pub fn get_scheduled_return(conn: &mut SqliteConnection) -> Result<Option<ScheduledReturn>, Error> {
+    use crate::schema::scheduled_returns::dsl::*;
+
+    scheduled_returns
+        .order(return_at.desc())
+        .first(conn)
+        .optional()
+}
+
+/// Replace the pending return, a new timed switch overrides the previous one
+pub fn set_scheduled_return(
+    conn: &mut SqliteConnection,
+    scheduled_return: &ScheduledReturn,
+) -> Result<usize, Error> {
+    use crate::schema::scheduled_returns;
+
+    connection::transaction(conn, |conn| {
+        diesel::delete(scheduled_returns::table).execute(conn)?;
+        diesel::insert_into(scheduled_returns::table)
+            .values(scheduled_return)
+            .execute(conn)
+    })
+}
+
+pub fn clear_scheduled_return(conn: &mut SqliteConnection) -> Result<usize, Error> {
+    use crate::schema::scheduled_returns;
+
+    diesel::delete(scheduled_returns::table).execute(conn)
+}
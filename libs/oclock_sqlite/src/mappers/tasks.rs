@@ -1,6 +1,8 @@
 use diesel;
+use diesel::dsl::sql;
 use diesel::prelude::*;
 use diesel::result::Error;
+use diesel::sql_types::{Bool, Integer, Text};
 use diesel::sqlite::SqliteConnection;
 
 use crate::models::{NewTask, Task};
@@ -11,6 +13,31 @@ pub fn create_task(conn: &mut SqliteConnection, task: &NewTask) -> Result<usize,
     diesel::insert_into(tasks::table).values(task).execute(conn)
 }
 
+/// Id of the task with the given name ignoring case, creating the task when missing
+pub fn find_or_create_task(conn: &mut SqliteConnection, task_name: &str) -> Result<i32, Error> {
+    use crate::schema::tasks::dsl::*;
+
+    let existing = tasks
+        .filter(
+            sql::<Bool>("lower(name) = lower(")
+                .bind::<Text, _>(task_name)
+                .sql(")"),
+        )
+        .order(id)
+        .select(id)
+        .first::<i32>(conn)
+        .optional()?;
+    match existing {
+        Some(task_id) => Ok(task_id),
+        None => {
+            diesel::insert_into(tasks)
+                .values(name.eq(task_name))
+                .execute(conn)?;
+            diesel::select(sql::<Integer>("last_insert_rowid()")).get_result(conn)
+        }
+    }
+}
+
 pub fn list_tasks(conn: &mut SqliteConnection) -> Result<Vec<Task>, Error> {
     use crate::schema::tasks::dsl::*;
 
@@ -28,3 +55,27 @@ pub fn change_enabled(
         .set(enabled.eq(if new_enabled { 1 } else { 0 }))
         .execute(conn)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::{MemoryDB, Storage};
+
+    #[test]
+    fn test_find_or_create_task_ignores_case() {
+        let storage = MemoryDB::new().unwrap();
+        let mut conn = storage.establish_connection().unwrap();
+
+        let dev = find_or_create_task(&mut conn, "Dev Review").unwrap();
+        assert_eq!(find_or_create_task(&mut conn, "dev review").unwrap(), dev);
+        assert_eq!(find_or_create_task(&mut conn, "DEV REVIEW").unwrap(), dev);
+        assert_ne!(find_or_create_task(&mut conn, "dev").unwrap(), dev);
+
+        let names: Vec<String> = list_tasks(&mut conn)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec!["Dev Review", "dev"]);
+    }
+}
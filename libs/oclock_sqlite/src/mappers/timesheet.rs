@@ -11,7 +11,10 @@ pub fn full_timesheet(conn: &mut SqliteConnection) -> Result<Vec<TimesheetEntry>
     v_timesheet
         .filter(
             system_event
-                .eq(SystemEventType::Startup.to_string())
+                .eq_any([
+                    SystemEventType::Startup.to_string(),
+                    SystemEventType::Pause.to_string(),
+                ])
                 .or(system_event.is_null()),
         )
         .order(day)
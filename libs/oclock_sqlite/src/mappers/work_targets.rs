@@ -0,0 +1,52 @@
+use diesel;
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sqlite::SqliteConnection;
+
+use crate::models::{Holiday, NewHoliday, NewWorkTarget, WorkTarget};
+
+pub fn set_work_target(
+    conn: &mut SqliteConnection,
+    target: &NewWorkTarget,
+) -> Result<usize, Error> {
+    use crate::schema::work_targets;
+
+    diesel::replace_into(work_targets::table)
+        .values(target)
+        .execute(conn)
+}
+
+pub fn remove_work_target(
+    conn: &mut SqliteConnection,
+    target_effective_from: String,
+) -> Result<usize, Error> {
+    use crate::schema::work_targets::dsl::*;
+
+    diesel::delete(work_targets.filter(effective_from.eq(target_effective_from))).execute(conn)
+}
+
+pub fn list_work_targets(conn: &mut SqliteConnection) -> Result<Vec<WorkTarget>, Error> {
+    use crate::schema::work_targets::dsl::*;
+
+    work_targets.order(effective_from).load(conn)
+}
+
+pub fn set_holiday(conn: &mut SqliteConnection, holiday: &NewHoliday) -> Result<usize, Error> {
+    use crate::schema::holidays;
+
+    diesel::replace_into(holidays::table)
+        .values(holiday)
+        .execute(conn)
+}
+
+pub fn remove_holiday(conn: &mut SqliteConnection, holiday_day: String) -> Result<usize, Error> {
+    use crate::schema::holidays::dsl::*;
+
+    diesel::delete(holidays.filter(day.eq(holiday_day))).execute(conn)
+}
+
+pub fn list_holidays(conn: &mut SqliteConnection) -> Result<Vec<Holiday>, Error> {
+    use crate::schema::holidays::dsl::*;
+
+    holidays.order(day).load(conn)
+}
@@ -0,0 +1,29 @@
+use diesel;
+use diesel::prelude::*;
+use diesel::result::Error;
+use diesel::sqlite::SqliteConnection;
+
+use crate::models::{NewWorkingHours, WorkingHours};
+
+pub fn create_working_hours(
+    conn: &mut SqliteConnection,
+    hours: &NewWorkingHours,
+) -> Result<usize, Error> {
+    use crate::schema::working_hours;
+
+    diesel::insert_into(working_hours::table)
+        .values(hours)
+        .execute(conn)
+}
+
+pub fn remove_working_hours(conn: &mut SqliteConnection, hours_id: i32) -> Result<usize, Error> {
+    use crate::schema::working_hours::dsl::*;
+
+    diesel::delete(working_hours.filter(id.eq(hours_id))).execute(conn)
+}
+
+pub fn list_working_hours(conn: &mut SqliteConnection) -> Result<Vec<WorkingHours>, Error> {
+    use crate::schema::working_hours::dsl::*;
+
+    working_hours.order((start_minute, id)).load(conn)
+}
@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use super::schema::*;
 
@@ -8,7 +8,8 @@ pub struct NewTask {
     pub name: String,
 }
 
-#[derive(Queryable, Serialize)]
+#[derive(Debug, Queryable, Insertable, Serialize, Deserialize)]
+#[diesel(table_name=tasks)]
 pub struct Task {
     pub id: i32,
     pub enabled: i32,
@@ -21,14 +22,29 @@ pub struct NewEvent {
     pub event_timestamp: i32,
     pub task_id: Option<i32>,
     pub system_event_name: Option<String>,
+    pub note: Option<String>,
 }
 
-#[derive(Debug, Queryable)]
+#[derive(Debug, Queryable, Insertable, Serialize, Deserialize)]
+#[diesel(table_name=events)]
 pub struct Event {
     pub id: i32,
     pub event_timestamp: i32,
     pub task_id: Option<i32>,
     pub system_event_name: Option<String>,
+    /// Missing in the dumps up to version 4, written before switch notes were introduced
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Queryable, Serialize)]
+pub struct HistoryEntry {
+    pub id: i32,
+    pub ts_start: i32,
+    pub ts_end: Option<i32>,
+    pub system_event: Option<String>,
+    pub task_name: Option<String>,
+    pub task_id: Option<i32>,
 }
 
 #[derive(Debug, Queryable, Serialize)]
@@ -40,3 +56,174 @@ pub struct TimesheetEntry {
     pub system_event: Option<String>,
     pub amount: i32,
 }
+
+#[derive(Insertable)]
+#[diesel(table_name=pomodoro_cycles)]
+pub struct NewPomodoroCycle {
+    pub task_id: i32,
+    pub started_at: i32,
+    pub completed_at: i32,
+}
+
+#[derive(Debug, Queryable, Insertable, Serialize, Deserialize)]
+#[diesel(table_name=pomodoro_cycles)]
+pub struct PomodoroCycle {
+    pub id: i32,
+    pub task_id: i32,
+    pub started_at: i32,
+    pub completed_at: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name=task_budgets)]
+pub struct NewTaskBudget {
+    pub task_id: i32,
+    pub period: String,
+    pub amount: i32,
+    pub warning_percent: i32,
+}
+
+#[derive(Debug, Queryable, Insertable, Serialize, Deserialize)]
+#[diesel(table_name=task_budgets)]
+pub struct TaskBudget {
+    pub id: i32,
+    pub task_id: i32,
+    pub period: String,
+    pub amount: i32,
+    pub warning_percent: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name=task_rates)]
+pub struct NewTaskRate {
+    pub task_id: i32,
+    pub hourly_rate: i32,
+    pub currency: String,
+    pub effective_from: String,
+}
+
+#[derive(Debug, Queryable, Insertable, Serialize, Deserialize)]
+#[diesel(table_name=task_rates)]
+pub struct TaskRate {
+    pub id: i32,
+    pub task_id: i32,
+    pub hourly_rate: i32,
+    pub currency: String,
+    pub effective_from: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name=scheduled_switches)]
+pub struct NewScheduledSwitch {
+    pub task_id: Option<i32>,
+    pub minute_of_day: i32,
+    pub weekdays: i32,
+    pub duration: Option<i32>,
+}
+
+#[derive(Debug, Queryable, Insertable, Serialize, Deserialize)]
+#[diesel(table_name=scheduled_switches)]
+pub struct ScheduledSwitch {
+    pub id: i32,
+    pub task_id: Option<i32>,
+    pub minute_of_day: i32,
+    pub weekdays: i32,
+    pub duration: Option<i32>,
+}
+
+/// Return to the previous task once a timed scheduled switch is over
+#[derive(Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name=scheduled_returns)]
+pub struct ScheduledReturn {
+    pub switch_id: i32,
+    pub task_id: Option<i32>,
+    pub return_at: i32,
+    pub previous_task_id: Option<i32>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name=context_rules)]
+pub struct NewContextRule {
+    pub context_kind: String,
+    pub pattern: String,
+    pub task_id: i32,
+}
+
+#[derive(Debug, Queryable, Insertable, Serialize, Deserialize)]
+#[diesel(table_name=context_rules)]
+pub struct ContextRule {
+    pub id: i32,
+    pub context_kind: String,
+    pub pattern: String,
+    pub task_id: i32,
+}
+
+#[derive(Debug, Queryable, Insertable, Serialize, Deserialize)]
+#[diesel(table_name=context_switches)]
+pub struct ContextSwitch {
+    pub id: i32,
+    pub event_id: i32,
+    pub context_rule_id: i32,
+    pub context_kind: String,
+    pub context_value: String,
+}
+
+#[derive(Debug, Queryable, Serialize)]
+pub struct ContextSwitchEntry {
+    pub id: i32,
+    pub event_timestamp: i32,
+    pub task_id: i32,
+    pub task_name: String,
+    pub context_rule_id: i32,
+    pub context_kind: String,
+    pub context_value: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name=work_targets)]
+pub struct NewWorkTarget {
+    pub effective_from: String,
+    pub weekdays: i32,
+    pub daily_minutes: i32,
+}
+
+#[derive(Debug, Queryable, Insertable, Serialize, Deserialize)]
+#[diesel(table_name=work_targets)]
+pub struct WorkTarget {
+    pub id: i32,
+    pub effective_from: String,
+    pub weekdays: i32,
+    pub daily_minutes: i32,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name=holidays)]
+pub struct NewHoliday {
+    pub day: String,
+    pub description: String,
+}
+
+#[derive(Debug, Queryable, Insertable, Serialize, Deserialize)]
+#[diesel(table_name=holidays)]
+pub struct Holiday {
+    pub id: i32,
+    pub day: String,
+    pub description: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name=working_hours)]
+pub struct NewWorkingHours {
+    pub weekdays: i32,
+    pub start_minute: i32,
+    pub end_minute: i32,
+}
+
+#[derive(Debug, Queryable, Insertable, Serialize, Deserialize)]
+#[diesel(table_name=working_hours)]
+pub struct WorkingHours {
+    pub id: i32,
+    pub weekdays: i32,
+    pub start_minute: i32,
+    pub end_minute: i32,
+}
@@ -4,6 +4,7 @@ table! {
         event_timestamp -> Integer,
         task_id -> Nullable<Integer>,
         system_event_name -> Nullable<Text>,
+        note -> Nullable<Text>,
     }
 }
 
@@ -17,6 +18,17 @@ table! {
 
 joinable!(events -> tasks (task_id));
 
+table! {
+    v_history (id) {
+        id -> Integer,
+        ts_start -> Integer,
+        ts_end -> Nullable<Integer>,
+        system_event -> Nullable<Text>,
+        task_name -> Nullable<Text>,
+        task_id -> Nullable<Integer>,
+    }
+}
+
 table! {
     v_timesheet (id) {
         id -> Integer,
@@ -27,3 +39,111 @@ table! {
         amount -> Integer,
     }
 }
+
+table! {
+    pomodoro_cycles (id) {
+        id -> Integer,
+        task_id -> Integer,
+        started_at -> Integer,
+        completed_at -> Integer,
+    }
+}
+
+joinable!(pomodoro_cycles -> tasks (task_id));
+
+table! {
+    task_budgets (id) {
+        id -> Integer,
+        task_id -> Integer,
+        period -> Text,
+        amount -> Integer,
+        warning_percent -> Integer,
+    }
+}
+
+joinable!(task_budgets -> tasks (task_id));
+
+table! {
+    task_rates (id) {
+        id -> Integer,
+        task_id -> Integer,
+        hourly_rate -> Integer,
+        currency -> Text,
+        effective_from -> Text,
+    }
+}
+
+joinable!(task_rates -> tasks (task_id));
+
+table! {
+    scheduled_switches (id) {
+        id -> Integer,
+        task_id -> Nullable<Integer>,
+        minute_of_day -> Integer,
+        weekdays -> Integer,
+        duration -> Nullable<Integer>,
+    }
+}
+
+joinable!(scheduled_switches -> tasks (task_id));
+
+table! {
+    scheduled_returns (switch_id) {
+        switch_id -> Integer,
+        task_id -> Nullable<Integer>,
+        return_at -> Integer,
+        previous_task_id -> Nullable<Integer>,
+    }
+}
+
+joinable!(scheduled_returns -> scheduled_switches (switch_id));
+
+table! {
+    context_rules (id) {
+        id -> Integer,
+        context_kind -> Text,
+        pattern -> Text,
+        task_id -> Integer,
+    }
+}
+
+joinable!(context_rules -> tasks (task_id));
+
+table! {
+    context_switches (id) {
+        id -> Integer,
+        event_id -> Integer,
+        context_rule_id -> Integer,
+        context_kind -> Text,
+        context_value -> Text,
+    }
+}
+
+table! {
+    work_targets (id) {
+        id -> Integer,
+        effective_from -> Text,
+        weekdays -> Integer,
+        daily_minutes -> Integer,
+    }
+}
+
+table! {
+    holidays (id) {
+        id -> Integer,
+        day -> Text,
+        description -> Text,
+    }
+}
+
+table! {
+    working_hours (id) {
+        id -> Integer,
+        weekdays -> Integer,
+        start_minute -> Integer,
+        end_minute -> Integer,
+    }
+}
+
+joinable!(context_switches -> events (event_id));
+allow_tables_to_appear_in_same_query!(context_switches, events, tasks);
@@ -1,4 +1,4 @@
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 /// Simple time tracking software
@@ -23,6 +23,10 @@ pub enum OClockCommand {
 
 #[derive(Args, Debug)]
 pub struct ClientArgs {
+    /// Execute the command in-process on the local database, without connecting to the server;
+    /// this is also done when no server is running
+    #[arg(long)]
+    pub offline: bool,
     #[clap(subcommand)]
     pub command: OClockClientCommandArg,
 }
@@ -41,10 +45,13 @@ pub enum OClockClientCommandArg {
         #[clap(long, short)]
         task_id: u64,
     },
-    /// Switch to the task with the given id
+    /// Switch to the task with the given id, eventually with a note on what is being done
     SwitchTask {
         #[clap(long, short)]
         task_id: u64,
+        /// Used as the comment of the exported Jira worklogs
+        #[clap(long, short)]
+        note: Option<String>,
     },
     /// Read the current task
     CurrentTask,
@@ -60,10 +67,13 @@ pub enum OClockClientCommandArg {
         #[clap(long, short)]
         task_id: u64,
     },
-    /// Switch to the task with the given id (json version)
+    /// Switch to the task with the given id, eventually with a note on what is being done (json version)
     JsonSwitchTask {
         #[clap(long, short)]
         task_id: u64,
+        /// Used as the comment of the exported Jira worklogs
+        #[clap(long, short)]
+        note: Option<String>,
     },
     /// Switch to the task with the given id at the given time, eventually returning to the current task (json version)
     JsonRetroSwitchTask {
@@ -76,8 +86,426 @@ pub enum OClockClientCommandArg {
     },
     /// Read the current state (json version)
     JsonState,
-    /// Produce the full timesheet
-    Timesheet,
+    /// Produce the full timesheet, eventually rounding the durations
+    Timesheet {
+        #[clap(flatten)]
+        rounding: RoundingArgs,
+    },
+    /// Switch to the task with the given id alternating work and break phases (json version)
+    StartPomodoro {
+        #[clap(long, short)]
+        task_id: u64,
+        #[clap(long, default_value_t = 25)]
+        work_minutes: u32,
+        #[clap(long, default_value_t = 5)]
+        break_minutes: u32,
+        #[clap(long, default_value_t = 4)]
+        cycles: u32,
+        #[clap(long)]
+        break_task_id: Option<u64>,
+    },
+    /// Stop the running pomodoro keeping the current task (json version)
+    StopPomodoro,
+    /// Set the time budget of the task with the given id for the given period (json version)
+    SetTaskBudget {
+        #[clap(long, short)]
+        task_id: u64,
+        #[clap(long, short, value_enum)]
+        period: BudgetPeriodArg,
+        #[clap(long, short)]
+        amount_minutes: u32,
+        #[clap(long, default_value_t = 80)]
+        warning_percent: u32,
+    },
+    /// Remove the time budget of the task with the given id for the given period (json version)
+    RemoveTaskBudget {
+        #[clap(long, short)]
+        task_id: u64,
+        #[clap(long, short, value_enum)]
+        period: BudgetPeriodArg,
+    },
+    /// Set the hourly rate of the task with the given id, effective from the given day (YYYY-MM-DD) or always when missing (json version)
+    SetTaskRate {
+        #[clap(long, short)]
+        task_id: u64,
+        /// Amount per hour, like `85` or `85.50`
+        #[clap(long, short = 'r')]
+        hourly_rate: String,
+        #[clap(long, short)]
+        currency: String,
+        #[clap(long, short)]
+        effective_from: Option<String>,
+    },
+    /// Remove the hourly rate of the task with the given id effective from the given day, or the one always effective when missing (json version)
+    RemoveTaskRate {
+        #[clap(long, short)]
+        task_id: u64,
+        #[clap(long, short)]
+        effective_from: Option<String>,
+    },
+    /// List all task rates (json version)
+    ListTaskRates,
+    /// Produce the timesheet of the days between `from` and `to` (YYYY-MM-DD, inclusive) with the billable amount of each task per day and the totals per currency, eventually rounding the durations
+    BillableTimesheet {
+        #[clap(long, short)]
+        from: String,
+        #[clap(long, short)]
+        to: String,
+        #[clap(flatten)]
+        rounding: RoundingArgs,
+    },
+    /// Write a self-contained HTML report of the days between `from` and `to` (YYYY-MM-DD, inclusive) with tasks, rates, totals and a daily breakdown, eventually using the given template file
+    HtmlReport {
+        #[clap(long, short)]
+        path: PathBuf,
+        #[clap(long, short)]
+        from: String,
+        #[clap(long, short)]
+        to: String,
+        #[clap(long)]
+        title: Option<String>,
+        /// Client name and address, shown in the header
+        #[clap(long, short)]
+        client: Option<String>,
+        /// HTML file with `{{title}}`, `{{client}}`, `{{from}}`, `{{to}}`, `{{tasks}}`, `{{totals}}` and `{{daily}}` placeholders
+        #[clap(long)]
+        template: Option<PathBuf>,
+        #[clap(flatten)]
+        rounding: RoundingArgs,
+    },
+    /// Write an SVG chart of the time tracked in the days between `from` and `to` (YYYY-MM-DD, inclusive) to the given file
+    ExportChart {
+        #[clap(long, short)]
+        path: PathBuf,
+        #[clap(long, short, value_enum)]
+        chart: ChartKindArg,
+        #[clap(long, short)]
+        from: String,
+        #[clap(long, short)]
+        to: String,
+    },
+    /// Write the Jira worklogs of the days between `from` and `to` (YYYY-MM-DD, inclusive) to the given JSON file, a worklog per issue per day with the issue key extracted from the task names, eventually rounding the durations
+    ExportJiraWorklogs {
+        #[clap(long, short)]
+        path: PathBuf,
+        #[clap(long, short)]
+        from: String,
+        #[clap(long, short)]
+        to: String,
+        /// Regex matching the issue key in the task names, its first group when it has one, Jira keys (e.g. `PROJ-42`) when missing
+        #[clap(long, short)]
+        issue_pattern: Option<String>,
+        #[clap(flatten)]
+        rounding: RoundingArgs,
+    },
+    /// Compute statistics of the time tracked in the days between `from` and `to` (YYYY-MM-DD, inclusive): totals, focus streaks, context switches and time of day distribution (json version)
+    Stats {
+        #[clap(long, short)]
+        from: String,
+        #[clap(long, short)]
+        to: String,
+    },
+    /// Set the minutes of work due each of the given weekdays (Monday to Friday when empty) from the given day (YYYY-MM-DD) on (json version)
+    SetWorkTarget {
+        #[clap(long, short)]
+        daily_minutes: u32,
+        /// Comma separated list of weekdays, Monday to Friday when missing
+        #[clap(long, short, value_enum, value_delimiter = ',')]
+        weekdays: Vec<WeekdayArg>,
+        #[clap(long, short)]
+        effective_from: String,
+    },
+    /// Remove the work target effective from the given day (json version)
+    RemoveWorkTarget {
+        #[clap(long, short)]
+        effective_from: String,
+    },
+    /// Mark the given day (YYYY-MM-DD) as a holiday, without any work due (json version)
+    AddHoliday {
+        #[clap(long, short)]
+        day: String,
+        #[clap(long)]
+        description: Option<String>,
+    },
+    /// Remove the holiday on the given day (json version)
+    RemoveHoliday {
+        #[clap(long, short)]
+        day: String,
+    },
+    /// List all work targets and holidays (json version)
+    ListWorkTargets,
+    /// Compare the tracked time with the work targets per day and per week between `from` and `to` (YYYY-MM-DD, inclusive), by default from the first target to today, along with the running balance (json version)
+    OvertimeReport {
+        #[clap(long, short)]
+        from: Option<String>,
+        #[clap(long, short)]
+        to: Option<String>,
+    },
+    /// Add working hours between the given local times (HH:MM) on the given weekdays (Monday to Friday when empty) (json version)
+    AddWorkingHours {
+        /// Comma separated list of weekdays, Monday to Friday when missing
+        #[clap(long, short, value_enum, value_delimiter = ',')]
+        weekdays: Vec<WeekdayArg>,
+        /// Local time in the HH:MM format
+        #[clap(long, short)]
+        start: String,
+        /// Local time in the HH:MM format
+        #[clap(long, short)]
+        end: String,
+    },
+    /// Remove the working hours with the given id (json version)
+    RemoveWorkingHours {
+        #[clap(long, short = 'i')]
+        hours_id: u64,
+    },
+    /// List all working hours (json version)
+    ListWorkingHours,
+    /// List the parts of the working hours between `from` and `to` (YYYY-MM-DD, inclusive) without any active task, eventually ignoring the ones shorter than the given minutes (json version)
+    UntrackedGaps {
+        #[clap(long, short)]
+        from: String,
+        #[clap(long, short)]
+        to: String,
+        #[clap(long, short)]
+        min_minutes: Option<u32>,
+    },
+    /// Switch to the task with the given id (or stop tracking when missing) at the given local time, eventually returning to the previous task after the given duration (json version)
+    AddScheduledSwitch {
+        #[clap(long, short)]
+        task_id: Option<u64>,
+        /// Local time in the HH:MM format
+        #[clap(long, short)]
+        at: String,
+        /// Comma separated list of weekdays, every day when missing
+        #[clap(long, short, value_enum, value_delimiter = ',')]
+        weekdays: Vec<WeekdayArg>,
+        #[clap(long, short)]
+        duration_minutes: Option<u32>,
+    },
+    /// Remove the scheduled switch with the given id (json version)
+    RemoveScheduledSwitch {
+        #[clap(long, short)]
+        switch_id: u64,
+    },
+    /// List all scheduled switches (json version)
+    ListScheduledSwitches,
+    /// Notify a change of the user context, eventually switching task according to the context rules (json version)
+    Context {
+        #[clap(long, short, value_enum)]
+        kind: ContextKindArg,
+        #[clap(long, short)]
+        value: String,
+    },
+    /// Switch to the task with the given id when a context value matches the given regex (json version)
+    AddContextRule {
+        #[clap(long, short, value_enum)]
+        kind: ContextKindArg,
+        #[clap(long, short)]
+        pattern: String,
+        #[clap(long, short)]
+        task_id: u64,
+    },
+    /// Remove the context rule with the given id (json version)
+    RemoveContextRule {
+        #[clap(long, short)]
+        rule_id: u64,
+    },
+    /// List all context rules (json version)
+    ListContextRules,
+    /// List the task switches triggered by context rules (json version)
+    ListContextSwitches,
+    /// Write a snapshot of the database to the given file
+    Backup {
+        #[clap(long, short)]
+        path: PathBuf,
+    },
+    /// Replace all data with the content of the given backup
+    Restore {
+        #[clap(long, short)]
+        path: PathBuf,
+    },
+    /// Write all data to the given file in a versioned json format
+    Export {
+        #[clap(long, short)]
+        path: PathBuf,
+    },
+    /// Load data exported in json format, either replacing or merging with the existing data
+    Import {
+        #[clap(long, short)]
+        path: PathBuf,
+        #[clap(long, short, value_enum, default_value_t = ImportModeArg::Merge)]
+        mode: ImportModeArg,
+    },
+    /// Check the event log for inconsistencies, eventually fixing them (json version)
+    Fsck {
+        #[clap(long, short)]
+        fix: bool,
+    },
+    /// Load `timew export` json, a timewarrior data file or data directory, skipping the intervals overlapping tracked time (json version)
+    ImportTimewarrior {
+        #[clap(long, short)]
+        path: PathBuf,
+    },
+    /// Write the tracked intervals to the given file in the `timew export` json format
+    ExportTimewarrior {
+        #[clap(long, short)]
+        path: PathBuf,
+    },
+    /// Load the detailed csv report of Toggl Track or Clockify, skipping the intervals overlapping tracked time, eventually only showing what would be imported (json version); the date format (e.g. `%d/%m/%Y`) is required when dates can be read with the day or the month first
+    ImportCsv {
+        #[clap(long, short)]
+        path: PathBuf,
+        #[clap(long)]
+        dry_run: bool,
+        #[clap(long)]
+        date_format: Option<String>,
+    },
+    /// Write the tracked intervals of the days between `from` and `to` (YYYY-MM-DD, inclusive) to the given iCalendar file, eventually merging the consecutive ones of the same task within each day
+    ExportCalendar {
+        #[clap(long, short)]
+        path: PathBuf,
+        #[clap(long, short)]
+        from: String,
+        #[clap(long, short)]
+        to: String,
+        #[clap(long)]
+        merge_daily: bool,
+    },
+    #[cfg(feature = "api")]
+    /// Create intervals for the past events and occurrences of the given iCalendar file and scheduled switches for the endless daily and weekly ones, mapping them to tasks with the first matching rule, eventually splitting the tracked intervals they fall within (json version)
+    ImportCalendar {
+        #[clap(long, short)]
+        path: PathBuf,
+        /// Rule in the `FIELD:PATTERN=TASK_ID` format, like `summary:(?i)standup=3` or `category:^Meeting$=4`
+        #[clap(long = "rule", short, value_parser = parse_calendar_rule, required = true)]
+        rules: Vec<CalendarRuleArg>,
+        /// Switch to the events within a tracked interval and back to its task once they are over
+        #[clap(long)]
+        split_tracked: bool,
+    },
+    /// Write the tracked intervals to the given file as org-mode clocks or ledger timeclock entries
+    ExportClock {
+        #[clap(long, short)]
+        path: PathBuf,
+        #[clap(long, short, value_enum)]
+        format: ClockFormatArg,
+    },
+    /// Load org-mode clocks or ledger timeclock entries, skipping the intervals overlapping tracked time (json version)
+    ImportClock {
+        #[clap(long, short)]
+        path: PathBuf,
+        #[clap(long, short, value_enum)]
+        format: ClockFormatArg,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ContextKindArg {
+    GitBranch,
+    WorkingDirectory,
+    WindowTitle,
+    CalendarEntry,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ImportModeArg {
+    Replace,
+    Merge,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ClockFormatArg {
+    Org,
+    Timeclock,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ChartKindArg {
+    DailyBars,
+    WeeklyBars,
+    Donut,
+    Timeline,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum RoundingModeArg {
+    Up,
+    Nearest,
+    Down,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum RoundingScopeArg {
+    Interval,
+    Day,
+}
+
+#[derive(Args, Debug)]
+pub struct RoundingArgs {
+    /// Round the durations to multiples of the given minutes, like 6, 15 or 30
+    #[clap(long)]
+    round_minutes: Option<u32>,
+    #[clap(long, value_enum, default_value_t = RoundingModeArg::Nearest)]
+    round_mode: RoundingModeArg,
+    /// Round each tracked interval or the total of each task in a day
+    #[clap(long, value_enum, default_value_t = RoundingScopeArg::Day)]
+    round_per: RoundingScopeArg,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum CalendarFieldArg {
+    Summary,
+    Category,
+}
+
+#[cfg(feature = "api")]
+#[derive(Clone, Debug)]
+pub struct CalendarRuleArg {
+    field: CalendarFieldArg,
+    pattern: String,
+    task_id: u64,
+}
+
+#[cfg(feature = "api")]
+fn parse_calendar_rule(rule: &str) -> Result<CalendarRuleArg, String> {
+    let invalid = || format!("Invalid rule '{}', expected FIELD:PATTERN=TASK_ID", rule);
+    let (field, rest) = rule.split_once(':').ok_or_else(invalid)?;
+    let (pattern, task_id) = rest.rsplit_once('=').ok_or_else(invalid)?;
+
+    Ok(CalendarRuleArg {
+        field: CalendarFieldArg::from_str(field, true)?,
+        pattern: pattern.to_string(),
+        task_id: task_id.parse().map_err(|_| invalid())?,
+    })
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum WeekdayArg {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum BudgetPeriodArg {
+    Day,
+    Week,
+    Month,
+    Total,
+}
+
+/// Paths are resolved by the client, the daemon may run in another directory
+#[cfg(feature = "api")]
+fn absolute_path(path: PathBuf) -> String {
+    std::env::current_dir()
+        .map(|dir| dir.join(&path))
+        .unwrap_or(path)
+        .display()
+        .to_string()
 }
 
 #[cfg(feature = "api")]
@@ -87,14 +515,18 @@ impl From<OClockClientCommandArg> for oclock::dto::command::OClockClientCommand
             OClockClientCommandArg::Exit => Self::Exit,
             OClockClientCommandArg::PushTask { name } => Self::PushTask { name },
             OClockClientCommandArg::DisableTask { task_id } => Self::DisableTask { task_id },
-            OClockClientCommandArg::SwitchTask { task_id } => Self::SwitchTask { task_id },
+            OClockClientCommandArg::SwitchTask { task_id, note } => {
+                Self::SwitchTask { task_id, note }
+            }
             OClockClientCommandArg::CurrentTask => Self::CurrentTask,
             OClockClientCommandArg::ListTasks => Self::ListTasks,
             OClockClientCommandArg::JsonPushTask { name } => Self::JsonPushTask { name },
             OClockClientCommandArg::JsonDisableTask { task_id } => {
                 Self::JsonDisableTask { task_id }
             }
-            OClockClientCommandArg::JsonSwitchTask { task_id } => Self::JsonSwitchTask { task_id },
+            OClockClientCommandArg::JsonSwitchTask { task_id, note } => {
+                Self::JsonSwitchTask { task_id, note }
+            }
             OClockClientCommandArg::JsonRetroSwitchTask {
                 task_id,
                 timestamp,
@@ -105,7 +537,347 @@ impl From<OClockClientCommandArg> for oclock::dto::command::OClockClientCommand
                 keep_previous_task,
             },
             OClockClientCommandArg::JsonState => Self::JsonState,
-            OClockClientCommandArg::Timesheet => Self::Timesheet,
+            OClockClientCommandArg::Timesheet { rounding } => Self::Timesheet {
+                rounding: rounding.into(),
+            },
+            OClockClientCommandArg::StartPomodoro {
+                task_id,
+                work_minutes,
+                break_minutes,
+                cycles,
+                break_task_id,
+            } => Self::StartPomodoro {
+                task_id,
+                work_minutes,
+                break_minutes,
+                cycles,
+                break_task_id,
+            },
+            OClockClientCommandArg::StopPomodoro => Self::StopPomodoro,
+            OClockClientCommandArg::SetTaskBudget {
+                task_id,
+                period,
+                amount_minutes,
+                warning_percent,
+            } => Self::SetTaskBudget {
+                task_id,
+                period: period.into(),
+                amount_minutes,
+                warning_percent,
+            },
+            OClockClientCommandArg::RemoveTaskBudget { task_id, period } => {
+                Self::RemoveTaskBudget {
+                    task_id,
+                    period: period.into(),
+                }
+            }
+            OClockClientCommandArg::SetTaskRate {
+                task_id,
+                hourly_rate,
+                currency,
+                effective_from,
+            } => Self::SetTaskRate {
+                task_id,
+                hourly_rate,
+                currency,
+                effective_from,
+            },
+            OClockClientCommandArg::RemoveTaskRate {
+                task_id,
+                effective_from,
+            } => Self::RemoveTaskRate {
+                task_id,
+                effective_from,
+            },
+            OClockClientCommandArg::ListTaskRates => Self::ListTaskRates,
+            OClockClientCommandArg::BillableTimesheet { from, to, rounding } => {
+                Self::BillableTimesheet {
+                    from,
+                    to,
+                    rounding: rounding.into(),
+                }
+            }
+            OClockClientCommandArg::HtmlReport {
+                path,
+                from,
+                to,
+                title,
+                client,
+                template,
+                rounding,
+            } => Self::HtmlReport {
+                path: absolute_path(path),
+                from,
+                to,
+                title,
+                client,
+                template: template.map(absolute_path),
+                rounding: rounding.into(),
+            },
+            OClockClientCommandArg::ExportChart {
+                path,
+                chart,
+                from,
+                to,
+            } => Self::ExportChart {
+                path: absolute_path(path),
+                chart: chart.into(),
+                from,
+                to,
+            },
+            OClockClientCommandArg::ExportJiraWorklogs {
+                path,
+                from,
+                to,
+                issue_pattern,
+                rounding,
+            } => Self::ExportJiraWorklogs {
+                path: absolute_path(path),
+                from,
+                to,
+                issue_pattern,
+                rounding: rounding.into(),
+            },
+            OClockClientCommandArg::Stats { from, to } => Self::Stats { from, to },
+            OClockClientCommandArg::SetWorkTarget {
+                daily_minutes,
+                weekdays,
+                effective_from,
+            } => Self::SetWorkTarget {
+                daily_minutes,
+                weekdays: weekdays.into_iter().map(Into::into).collect(),
+                effective_from,
+            },
+            OClockClientCommandArg::RemoveWorkTarget { effective_from } => {
+                Self::RemoveWorkTarget { effective_from }
+            }
+            OClockClientCommandArg::AddHoliday { day, description } => {
+                Self::AddHoliday { day, description }
+            }
+            OClockClientCommandArg::RemoveHoliday { day } => Self::RemoveHoliday { day },
+            OClockClientCommandArg::ListWorkTargets => Self::ListWorkTargets,
+            OClockClientCommandArg::OvertimeReport { from, to } => {
+                Self::OvertimeReport { from, to }
+            }
+            OClockClientCommandArg::AddWorkingHours {
+                weekdays,
+                start,
+                end,
+            } => Self::AddWorkingHours {
+                weekdays: weekdays.into_iter().map(Into::into).collect(),
+                start,
+                end,
+            },
+            OClockClientCommandArg::RemoveWorkingHours { hours_id } => {
+                Self::RemoveWorkingHours { hours_id }
+            }
+            OClockClientCommandArg::ListWorkingHours => Self::ListWorkingHours,
+            OClockClientCommandArg::UntrackedGaps {
+                from,
+                to,
+                min_minutes,
+            } => Self::UntrackedGaps {
+                from,
+                to,
+                min_minutes,
+            },
+            OClockClientCommandArg::AddScheduledSwitch {
+                task_id,
+                at,
+                weekdays,
+                duration_minutes,
+            } => Self::AddScheduledSwitch {
+                task_id,
+                at,
+                weekdays: weekdays.into_iter().map(Into::into).collect(),
+                duration_minutes,
+            },
+            OClockClientCommandArg::RemoveScheduledSwitch { switch_id } => {
+                Self::RemoveScheduledSwitch { switch_id }
+            }
+            OClockClientCommandArg::ListScheduledSwitches => Self::ListScheduledSwitches,
+            OClockClientCommandArg::Context { kind, value } => Self::Context {
+                kind: kind.into(),
+                value,
+            },
+            OClockClientCommandArg::AddContextRule {
+                kind,
+                pattern,
+                task_id,
+            } => Self::AddContextRule {
+                kind: kind.into(),
+                pattern,
+                task_id,
+            },
+            OClockClientCommandArg::RemoveContextRule { rule_id } => {
+                Self::RemoveContextRule { rule_id }
+            }
+            OClockClientCommandArg::ListContextRules => Self::ListContextRules,
+            OClockClientCommandArg::ListContextSwitches => Self::ListContextSwitches,
+            OClockClientCommandArg::Backup { path } => Self::Backup {
+                path: absolute_path(path),
+            },
+            OClockClientCommandArg::Restore { path } => Self::Restore {
+                path: absolute_path(path),
+            },
+            OClockClientCommandArg::Export { path } => Self::Export {
+                path: absolute_path(path),
+            },
+            OClockClientCommandArg::Import { path, mode } => Self::Import {
+                path: absolute_path(path),
+                mode: mode.into(),
+            },
+            OClockClientCommandArg::Fsck { fix } => Self::Fsck { fix },
+            OClockClientCommandArg::ImportTimewarrior { path } => Self::ImportTimewarrior {
+                path: absolute_path(path),
+            },
+            OClockClientCommandArg::ExportTimewarrior { path } => Self::ExportTimewarrior {
+                path: absolute_path(path),
+            },
+            OClockClientCommandArg::ImportCsv {
+                path,
+                dry_run,
+                date_format,
+            } => Self::ImportCsv {
+                path: absolute_path(path),
+                dry_run,
+                date_format,
+            },
+            OClockClientCommandArg::ImportCalendar {
+                path,
+                rules,
+                split_tracked,
+            } => Self::ImportCalendar {
+                path: absolute_path(path),
+                rules: rules.into_iter().map(|r| r.into()).collect(),
+                split_tracked,
+            },
+            OClockClientCommandArg::ExportClock { path, format } => Self::ExportClock {
+                path: absolute_path(path),
+                format: format.into(),
+            },
+            OClockClientCommandArg::ImportClock { path, format } => Self::ImportClock {
+                path: absolute_path(path),
+                format: format.into(),
+            },
+            OClockClientCommandArg::ExportCalendar {
+                path,
+                from,
+                to,
+                merge_daily,
+            } => Self::ExportCalendar {
+                path: absolute_path(path),
+                from,
+                to,
+                merge_daily,
+            },
+        }
+    }
+}
+
+#[cfg(feature = "api")]
+impl From<ClockFormatArg> for oclock::dto::command::ClockFormat {
+    fn from(value: ClockFormatArg) -> Self {
+        match value {
+            ClockFormatArg::Org => Self::Org,
+            ClockFormatArg::Timeclock => Self::Timeclock,
+        }
+    }
+}
+
+#[cfg(feature = "api")]
+impl From<CalendarRuleArg> for oclock::dto::command::CalendarRule {
+    fn from(value: CalendarRuleArg) -> Self {
+        Self {
+            field: match value.field {
+                CalendarFieldArg::Summary => oclock::dto::command::CalendarField::Summary,
+                CalendarFieldArg::Category => oclock::dto::command::CalendarField::Category,
+            },
+            pattern: value.pattern,
+            task_id: value.task_id,
+        }
+    }
+}
+
+#[cfg(feature = "api")]
+impl From<BudgetPeriodArg> for oclock::dto::command::BudgetPeriod {
+    fn from(value: BudgetPeriodArg) -> Self {
+        match value {
+            BudgetPeriodArg::Day => Self::Day,
+            BudgetPeriodArg::Week => Self::Week,
+            BudgetPeriodArg::Month => Self::Month,
+            BudgetPeriodArg::Total => Self::Total,
+        }
+    }
+}
+
+#[cfg(feature = "api")]
+impl From<ChartKindArg> for oclock::dto::command::ChartKind {
+    fn from(value: ChartKindArg) -> Self {
+        match value {
+            ChartKindArg::DailyBars => Self::DailyBars,
+            ChartKindArg::WeeklyBars => Self::WeeklyBars,
+            ChartKindArg::Donut => Self::Donut,
+            ChartKindArg::Timeline => Self::Timeline,
+        }
+    }
+}
+
+#[cfg(feature = "api")]
+impl From<RoundingArgs> for Option<oclock::dto::command::Rounding> {
+    fn from(value: RoundingArgs) -> Self {
+        use oclock::dto::command::{RoundingMode, RoundingScope};
+
+        value
+            .round_minutes
+            .map(|increment_minutes| oclock::dto::command::Rounding {
+                increment_minutes,
+                mode: match value.round_mode {
+                    RoundingModeArg::Up => RoundingMode::Up,
+                    RoundingModeArg::Nearest => RoundingMode::Nearest,
+                    RoundingModeArg::Down => RoundingMode::Down,
+                },
+                scope: match value.round_per {
+                    RoundingScopeArg::Interval => RoundingScope::Interval,
+                    RoundingScopeArg::Day => RoundingScope::Day,
+                },
+            })
+    }
+}
+
+#[cfg(feature = "api")]
+impl From<ImportModeArg> for oclock::dto::command::ImportMode {
+    fn from(value: ImportModeArg) -> Self {
+        match value {
+            ImportModeArg::Replace => Self::Replace,
+            ImportModeArg::Merge => Self::Merge,
+        }
+    }
+}
+
+#[cfg(feature = "api")]
+impl From<WeekdayArg> for oclock::dto::command::Weekday {
+    fn from(value: WeekdayArg) -> Self {
+        match value {
+            WeekdayArg::Monday => Self::Monday,
+            WeekdayArg::Tuesday => Self::Tuesday,
+            WeekdayArg::Wednesday => Self::Wednesday,
+            WeekdayArg::Thursday => Self::Thursday,
+            WeekdayArg::Friday => Self::Friday,
+            WeekdayArg::Saturday => Self::Saturday,
+            WeekdayArg::Sunday => Self::Sunday,
+        }
+    }
+}
+
+#[cfg(feature = "api")]
+impl From<ContextKindArg> for oclock::dto::command::ContextKind {
+    fn from(value: ContextKindArg) -> Self {
+        match value {
+            ContextKindArg::GitBranch => Self::GitBranch,
+            ContextKindArg::WorkingDirectory => Self::WorkingDirectory,
+            ContextKindArg::WindowTitle => Self::WindowTitle,
+            ContextKindArg::CalendarEntry => Self::CalendarEntry,
         }
     }
 }
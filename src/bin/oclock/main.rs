@@ -1,9 +1,31 @@
 use clap::Parser;
 
+#[cfg(feature = "client")]
+use oclock::client::handler::SrvInvocationError;
+
 use crate::cli::args::{OClockArgs, OClockCommand};
 
 mod cli;
 
+#[cfg(all(feature = "client", feature = "tracker"))]
+fn invoke_offline(
+    path: Option<std::path::PathBuf>,
+    command: oclock::dto::command::OClockClientCommand,
+) -> Result<serde_json::Value, String> {
+    let data_dir = path.unwrap_or_else(oclock::tracker::handler::default_data_dir);
+    oclock::Tracker::open(&data_dir)?.execute(command)
+}
+
+#[cfg(all(feature = "client", not(feature = "tracker")))]
+fn invoke_offline(
+    _path: Option<std::path::PathBuf>,
+    _command: oclock::dto::command::OClockClientCommand,
+) -> Result<serde_json::Value, String> {
+    Err(String::from(
+        "Offline mode is not available, oclock was built without the tracker feature",
+    ))
+}
+
 fn main() {
     env_logger::init();
     let args: OClockArgs = OClockArgs::parse();
@@ -12,10 +34,19 @@ fn main() {
     match args.subcommand {
         #[cfg(feature = "client")]
         OClockCommand::Client(client_args) => {
-            let reply = oclock::client::handler::invoke_server::<
-                oclock::dto::command::OClockClientCommand,
-                serde_json::Value,
-            >(client_args.command.into());
+            let command: oclock::dto::command::OClockClientCommand = client_args.command.into();
+            let reply = if client_args.offline {
+                invoke_offline(args.path, command)
+            } else {
+                match oclock::client::handler::invoke_server::<_, serde_json::Value>(&command) {
+                    // Nothing was sent when no daemon is listening, the command can run in-process
+                    Err(SrvInvocationError::Unreachable(err)) if cfg!(feature = "tracker") => {
+                        log::debug!("Daemon unreachable, running offline - {err}");
+                        invoke_offline(args.path, command)
+                    }
+                    reply => reply.map_err(|err| err.to_string()),
+                }
+            };
             match &reply {
                 Ok(serde_json::Value::String(msg)) => println!("{msg}"),
                 Ok(rep) => println!(
@@ -24,7 +55,7 @@ fn main() {
                 ),
                 Err(err) => eprintln!("{err}"),
             }
-            error_state = reply.is_ok();
+            error_state = reply.is_err();
         }
         #[cfg(feature = "server")]
         OClockCommand::Server => oclock::server::handler::server(),
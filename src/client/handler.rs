@@ -9,6 +9,8 @@ pub enum SrvInvocationError {
     ServerError(String),
     #[error("Communication Error - {0}")]
     CommunicationError(String),
+    #[error("Server unreachable - {0}")]
+    Unreachable(String),
 }
 pub fn invoke_server<Req, Rep>(req: Req) -> Result<Rep, SrvInvocationError>
 where
@@ -26,9 +28,7 @@ where
     socket
         .dial(crate::core::constants::SERVER_REQ_URL)
         .map_err(|err| {
-            SrvInvocationError::CommunicationError(format!(
-                "Error connecting to the socket - {err}"
-            ))
+            SrvInvocationError::Unreachable(format!("Error connecting to the socket - {err}"))
         })?;
 
     let serialized_req = serde_json::to_vec(&req).map_err(|err| {
@@ -12,9 +12,9 @@ pub enum OClockClientCommand {
     /// Disable the task with the given id
     #[serde(rename_all = "camelCase")]
     DisableTask { task_id: u64 },
-    /// Switch to the task with the given id
+    /// Switch to the task with the given id, eventually with a note on what is being done
     #[serde(rename_all = "camelCase")]
-    SwitchTask { task_id: u64 },
+    SwitchTask { task_id: u64, note: Option<String> },
     /// Read the current task
     #[serde(rename_all = "camelCase")]
     CurrentTask,
@@ -27,9 +27,9 @@ pub enum OClockClientCommand {
     /// Disable the task with the given id (json version)
     #[serde(rename_all = "camelCase")]
     JsonDisableTask { task_id: u64 },
-    /// Switch to the task with the given id (json version)
+    /// Switch to the task with the given id, eventually with a note on what is being done (json version)
     #[serde(rename_all = "camelCase")]
-    JsonSwitchTask { task_id: u64 },
+    JsonSwitchTask { task_id: u64, note: Option<String> },
     /// Switch to the task with the given id at the given time, eventually returning to the current task (json version)
     #[serde(rename_all = "camelCase")]
     JsonRetroSwitchTask {
@@ -40,7 +40,337 @@ pub enum OClockClientCommand {
     /// Read the current state (json version)
     #[serde(rename_all = "camelCase")]
     JsonState,
-    /// Produce the full timesheet
+    /// Produce the full timesheet, eventually rounding the durations
     #[serde(rename_all = "camelCase")]
-    Timesheet,
+    Timesheet { rounding: Option<Rounding> },
+    /// Switch to the task with the given id alternating work and break phases (json version)
+    #[serde(rename_all = "camelCase")]
+    StartPomodoro {
+        task_id: u64,
+        work_minutes: u32,
+        break_minutes: u32,
+        cycles: u32,
+        break_task_id: Option<u64>,
+    },
+    /// Stop the running pomodoro keeping the current task (json version)
+    #[serde(rename_all = "camelCase")]
+    StopPomodoro,
+    /// Set the time budget of the task with the given id for the given period (json version)
+    #[serde(rename_all = "camelCase")]
+    SetTaskBudget {
+        task_id: u64,
+        period: BudgetPeriod,
+        amount_minutes: u32,
+        warning_percent: u32,
+    },
+    /// Remove the time budget of the task with the given id for the given period (json version)
+    #[serde(rename_all = "camelCase")]
+    RemoveTaskBudget { task_id: u64, period: BudgetPeriod },
+    /// Set the hourly rate of the task with the given id, effective from the given day (YYYY-MM-DD) or always when missing (json version)
+    #[serde(rename_all = "camelCase")]
+    SetTaskRate {
+        task_id: u64,
+        hourly_rate: String,
+        currency: String,
+        effective_from: Option<String>,
+    },
+    /// Remove the hourly rate of the task with the given id effective from the given day, or the one always effective when missing (json version)
+    #[serde(rename_all = "camelCase")]
+    RemoveTaskRate {
+        task_id: u64,
+        effective_from: Option<String>,
+    },
+    /// List all task rates (json version)
+    #[serde(rename_all = "camelCase")]
+    ListTaskRates,
+    /// Produce the timesheet of the days between `from` and `to` (YYYY-MM-DD, inclusive) with the billable amount of each task per day and the totals per currency, eventually rounding the durations
+    #[serde(rename_all = "camelCase")]
+    BillableTimesheet {
+        from: String,
+        to: String,
+        rounding: Option<Rounding>,
+    },
+    /// Write a self-contained HTML report of the days between `from` and `to` (YYYY-MM-DD, inclusive) with tasks, rates, totals and a daily breakdown, eventually using the given template file
+    #[serde(rename_all = "camelCase")]
+    HtmlReport {
+        path: String,
+        from: String,
+        to: String,
+        title: Option<String>,
+        client: Option<String>,
+        template: Option<String>,
+        rounding: Option<Rounding>,
+    },
+    /// Write an SVG chart of the time tracked in the days between `from` and `to` (YYYY-MM-DD, inclusive) to the given file
+    #[serde(rename_all = "camelCase")]
+    ExportChart {
+        path: String,
+        chart: ChartKind,
+        from: String,
+        to: String,
+    },
+    /// Write the Jira worklogs of the days between `from` and `to` (YYYY-MM-DD, inclusive) to the given JSON file, a worklog per issue per day with the issue key extracted from the task names, eventually rounding the durations
+    #[serde(rename_all = "camelCase")]
+    ExportJiraWorklogs {
+        path: String,
+        from: String,
+        to: String,
+        issue_pattern: Option<String>,
+        rounding: Option<Rounding>,
+    },
+    /// Compute statistics of the time tracked in the days between `from` and `to` (YYYY-MM-DD, inclusive): totals, focus streaks, context switches and time of day distribution (json version)
+    #[serde(rename_all = "camelCase")]
+    Stats { from: String, to: String },
+    /// Set the minutes of work due each of the given weekdays (Monday to Friday when empty) from the given day (YYYY-MM-DD) on (json version)
+    #[serde(rename_all = "camelCase")]
+    SetWorkTarget {
+        daily_minutes: u32,
+        weekdays: Vec<Weekday>,
+        effective_from: String,
+    },
+    /// Remove the work target effective from the given day (json version)
+    #[serde(rename_all = "camelCase")]
+    RemoveWorkTarget { effective_from: String },
+    /// Mark the given day (YYYY-MM-DD) as a holiday, without any work due (json version)
+    #[serde(rename_all = "camelCase")]
+    AddHoliday {
+        day: String,
+        description: Option<String>,
+    },
+    /// Remove the holiday on the given day (json version)
+    #[serde(rename_all = "camelCase")]
+    RemoveHoliday { day: String },
+    /// List all work targets and holidays (json version)
+    #[serde(rename_all = "camelCase")]
+    ListWorkTargets,
+    /// Compare the tracked time with the work targets per day and per week between `from` and `to` (YYYY-MM-DD, inclusive), by default from the first target to today, along with the running balance (json version)
+    #[serde(rename_all = "camelCase")]
+    OvertimeReport {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    /// Add working hours between the given local times (HH:MM) on the given weekdays (Monday to Friday when empty) (json version)
+    #[serde(rename_all = "camelCase")]
+    AddWorkingHours {
+        weekdays: Vec<Weekday>,
+        start: String,
+        end: String,
+    },
+    /// Remove the working hours with the given id (json version)
+    #[serde(rename_all = "camelCase")]
+    RemoveWorkingHours { hours_id: u64 },
+    /// List all working hours (json version)
+    #[serde(rename_all = "camelCase")]
+    ListWorkingHours,
+    /// List the parts of the working hours between `from` and `to` (YYYY-MM-DD, inclusive) without any active task, eventually ignoring the ones shorter than the given minutes (json version)
+    #[serde(rename_all = "camelCase")]
+    UntrackedGaps {
+        from: String,
+        to: String,
+        min_minutes: Option<u32>,
+    },
+    /// Switch to the task with the given id (or stop tracking when missing) at the given local time, eventually returning to the previous task after the given duration (json version)
+    #[serde(rename_all = "camelCase")]
+    AddScheduledSwitch {
+        task_id: Option<u64>,
+        at: String,
+        weekdays: Vec<Weekday>,
+        duration_minutes: Option<u32>,
+    },
+    /// Remove the scheduled switch with the given id (json version)
+    #[serde(rename_all = "camelCase")]
+    RemoveScheduledSwitch { switch_id: u64 },
+    /// List all scheduled switches (json version)
+    #[serde(rename_all = "camelCase")]
+    ListScheduledSwitches,
+    /// Notify a change of the user context, eventually switching task according to the context rules (json version)
+    #[serde(rename_all = "camelCase")]
+    Context { kind: ContextKind, value: String },
+    /// Switch to the task with the given id when a context value matches the given regex (json version)
+    #[serde(rename_all = "camelCase")]
+    AddContextRule {
+        kind: ContextKind,
+        pattern: String,
+        task_id: u64,
+    },
+    /// Remove the context rule with the given id (json version)
+    #[serde(rename_all = "camelCase")]
+    RemoveContextRule { rule_id: u64 },
+    /// List all context rules (json version)
+    #[serde(rename_all = "camelCase")]
+    ListContextRules,
+    /// List the task switches triggered by context rules (json version)
+    #[serde(rename_all = "camelCase")]
+    ListContextSwitches,
+    /// Write a snapshot of the database to the given file
+    #[serde(rename_all = "camelCase")]
+    Backup { path: String },
+    /// Replace all data with the content of the given backup
+    #[serde(rename_all = "camelCase")]
+    Restore { path: String },
+    /// Write all data to the given file in a versioned json format
+    #[serde(rename_all = "camelCase")]
+    Export { path: String },
+    /// Load data exported in json format, either replacing or merging with the existing data
+    #[serde(rename_all = "camelCase")]
+    Import { path: String, mode: ImportMode },
+    /// Check the event log for inconsistencies, eventually fixing them (json version)
+    #[serde(rename_all = "camelCase")]
+    Fsck { fix: bool },
+    /// Load `timew export` json, a timewarrior data file or data directory, skipping the intervals overlapping tracked time (json version)
+    #[serde(rename_all = "camelCase")]
+    ImportTimewarrior { path: String },
+    /// Write the tracked intervals to the given file in the `timew export` json format
+    #[serde(rename_all = "camelCase")]
+    ExportTimewarrior { path: String },
+    /// Load the detailed csv report of Toggl Track or Clockify, skipping the intervals overlapping tracked time, eventually only showing what would be imported (json version); the date format (e.g. `%d/%m/%Y`) is required when dates can be read with the day or the month first
+    #[serde(rename_all = "camelCase")]
+    ImportCsv {
+        path: String,
+        dry_run: bool,
+        date_format: Option<String>,
+    },
+    /// Write the tracked intervals of the days between `from` and `to` (YYYY-MM-DD, inclusive) to the given iCalendar file, eventually merging the consecutive ones of the same task within each day
+    #[serde(rename_all = "camelCase")]
+    ExportCalendar {
+        path: String,
+        from: String,
+        to: String,
+        merge_daily: bool,
+    },
+    /// Create intervals for the past events and occurrences of the given iCalendar file and scheduled switches for the endless daily and weekly ones, mapping them to tasks with the first matching rule, eventually splitting the tracked intervals they fall within (json version)
+    #[serde(rename_all = "camelCase")]
+    ImportCalendar {
+        path: String,
+        rules: Vec<CalendarRule>,
+        split_tracked: bool,
+    },
+    /// Write the tracked intervals to the given file as org-mode clocks or ledger timeclock entries
+    #[serde(rename_all = "camelCase")]
+    ExportClock { path: String, format: ClockFormat },
+    /// Load org-mode clocks or ledger timeclock entries, skipping the intervals overlapping tracked time (json version)
+    #[serde(rename_all = "camelCase")]
+    ImportClock { path: String, format: ClockFormat },
+}
+
+impl OClockClientCommand {
+    /// Whether the command needs a long running daemon to be meaningful
+    pub fn requires_daemon(&self) -> bool {
+        matches!(
+            self,
+            OClockClientCommand::Exit
+                | OClockClientCommand::StartPomodoro { .. }
+                | OClockClientCommand::StopPomodoro
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BudgetPeriod {
+    Day,
+    Week,
+    Month,
+    Total,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ContextKind {
+    GitBranch,
+    WorkingDirectory,
+    WindowTitle,
+    CalendarEntry,
+}
+
+/// Plain text format of clocked time
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ClockFormat {
+    /// Emacs org-mode `CLOCK:` lines, in the LOGBOOK drawer of a heading per task
+    Org,
+    /// ledger and hledger `i`/`o` entries, using the tasks as accounts
+    Timeclock,
+}
+
+/// Chart showing how the tracked time is distributed
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ChartKind {
+    /// Bar per day stacking the time of each task
+    DailyBars,
+    /// Bar per week stacking the time of each task
+    WeeklyBars,
+    /// Share of each task in the total time
+    Donut,
+    /// Strip per day showing when each task was tracked
+    Timeline,
+}
+
+/// Direction in which durations are rounded to the increment
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RoundingMode {
+    Up,
+    /// Half increments are rounded up
+    Nearest,
+    Down,
+}
+
+/// Durations rounded one by one before summing them
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RoundingScope {
+    /// Each tracked interval
+    Interval,
+    /// The total of each task in a day
+    Day,
+}
+
+/// Round reported durations to multiples of the given minutes, like 6, 15 or 30
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct Rounding {
+    pub increment_minutes: u32,
+    pub mode: RoundingMode,
+    pub scope: RoundingScope,
+}
+
+/// Property of a calendar event matched by a rule
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CalendarField {
+    Summary,
+    /// Any of the event categories
+    Category,
+}
+
+/// Map the calendar events whose field matches the regex to the task with the given id
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarRule {
+    pub field: CalendarField,
+    pub pattern: String,
+    pub task_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ImportMode {
+    /// Delete the existing data, keeping the ids of the export
+    Replace,
+    /// Add the exported data to the existing one, assigning new ids
+    Merge,
 }
@@ -5,3 +5,8 @@ pub mod core;
 pub mod dto;
 #[cfg(feature = "server")]
 pub mod server;
+#[cfg(feature = "tracker")]
+pub mod tracker;
+
+#[cfg(feature = "tracker")]
+pub use tracker::handler::Tracker;
@@ -1,8 +1,7 @@
 extern crate ctrlc;
 
-use std::env;
-use std::error::Error;
 use std::fs;
+use std::path::Path;
 use std::str;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{Receiver, Sender, TryRecvError};
@@ -10,18 +9,19 @@ use std::sync::{mpsc, Arc};
 use std::thread;
 use std::time::Duration;
 
-use csv::Writer;
 use nng;
 use nng::options::{Options, RecvTimeout, SendTimeout};
 use nng::{Protocol, Socket};
 use oclock_sqlite::constants::SystemEventType;
 use schedule::{Agenda, Job};
-use serde;
 use serde::Serialize;
 use serde_json;
 use crate::core::constants::SERVER_SUB_URL;
 use crate::dto::command::OClockClientCommand;
-use crate::server::state::{State, TimesheetPivotRecord};
+use crate::server::hooks::run_hook;
+use crate::tracker::handler::{compute_state, default_data_dir, dispatch};
+use crate::tracker::notification::Notification;
+use crate::tracker::state::State;
 
 pub const SEP: &str = "#";
 
@@ -31,70 +31,6 @@ enum MsgListenerStatus {
     Fail,
 }
 
-fn vec_to_csv<T>(items: Vec<T>) -> Result<String, Box<dyn Error>>
-where
-    T: serde::ser::Serialize,
-{
-    let mut wtr = Writer::from_writer(vec![]);
-    for item in items {
-        let out = wtr.serialize(item);
-        if let Err(err) = out {
-            log::warn!("Error serializing item - {err}");
-        }
-    }
-
-    let data = String::from_utf8(wtr.into_inner()?)?;
-    Ok(data)
-}
-
-#[test]
-fn test_time_format() {
-    assert_eq!(format_time_interval(&0), "00:00:00");
-    assert_eq!(format_time_interval(&1), "00:00:01");
-    assert_eq!(format_time_interval(&60), "00:01:00");
-    assert_eq!(format_time_interval(&3600), "01:00:00");
-
-    assert_eq!(format_time_interval(&45296), "12:34:56");
-}
-
-fn format_time_interval(i: &i32) -> String {
-    format!(
-        "{:02}:{:02}:{:02}",
-        i / 3600,
-        (i - (i / 3600) * 3600) / 60,
-        i - (i / 60) * 60
-    )
-}
-
-fn timesheet_to_csv(
-    tasks: Vec<String>,
-    records: Vec<TimesheetPivotRecord>,
-) -> Result<String, Box<dyn Error>> {
-    let mut wtr = Writer::from_writer(vec![]);
-    let out = wtr.serialize(("day", tasks));
-    if let Err(err) = out {
-        log::warn!("Error serializing tasks - {err}");
-    }
-    for item in records {
-        let entries_str: Vec<String> = item.entries.iter().map(format_time_interval).collect();
-        let out = wtr.serialize((item.day, entries_str));
-        if let Err(err) = out {
-            log::warn!("Error serializing day entries - {err}");
-        }
-    }
-
-    let data = String::from_utf8(wtr.into_inner()?)?;
-    Ok(data)
-}
-
-fn compute_state(state: &State) -> Result<serde_json::Value, String> {
-    let exp_state = state.get_state()?;
-    match serde_json::to_value(&exp_state) {
-        Ok(json) => Ok(json),
-        Err(e) => Err(format!("Error serializing state {}", e)),
-    }
-}
-
 fn pub_state(state: &impl Serialize, sub_socket: &mut Socket) {
     log::info!("Sending state update");
     let out = sub_socket.send(&serde_json::to_vec(state).expect("error serializing state"));
@@ -103,93 +39,22 @@ fn pub_state(state: &impl Serialize, sub_socket: &mut Socket) {
     }
 }
 
-fn handle_msg(msg: OClockClientCommand, state: &State, pub_socket: &mut Socket) -> Result<serde_json::Value, String> {
-    match msg {
-        OClockClientCommand::Exit => Ok(serde_json::Value::String(String::from("bye bye..."))),
-        OClockClientCommand::CurrentTask => {
-            let task = state.get_current_task()?;
-            match task {
-                Some(t) => Ok(serde_json::Value::String(t.name)),
-                None => Ok(serde_json::Value::String(String::from("None"))),
-            }
-        }
-        OClockClientCommand::ListTasks => {
-            let tasks = state.list_tasks()?;
-            match vec_to_csv(tasks) {
-                Ok(csv) => Ok(serde_json::Value::String(csv)),
-                Err(e) => Err(format!("Error generating csv '{}'", e)),
-            }
-        }
-        OClockClientCommand::Timesheet => {
-            let (tasks, timesheet) = state.full_timesheet()?;
-
-            match timesheet_to_csv(tasks, timesheet) {
-                Ok(csv) => Ok(serde_json::Value::String(csv)),
-                Err(e) => Err(format!("Error generating csv '{}'", e)),
-            }
-        }
-        OClockClientCommand::PushTask { name } => {
-            let result = state.new_task(name);
-            if let Ok(state) = compute_state(state) {
-                pub_state(&state, pub_socket);
-            }
-            result
-        },
-        OClockClientCommand::DisableTask { task_id } => {
-            let result = state.change_task_enabled_flag(task_id, false);
-            if let Ok(state) = compute_state(state) {
-                pub_state(&state, pub_socket);
-            }
-            result
-        }
-        OClockClientCommand::SwitchTask { task_id } => {
-            let result = state.switch_task(task_id);
-            if let Ok(state) = compute_state(&state) {
-                pub_state(&state, pub_socket);
-            }
-            result
-        },
-        OClockClientCommand::JsonPushTask { name } => {
-            state.new_task(name)?;
-            let state = compute_state(state);
-            if let Ok(state) = &state {
-                pub_state(state, pub_socket);
-            }
-            state
-        }
-        OClockClientCommand::JsonDisableTask { task_id } => {
-            let out = state.change_task_enabled_flag(task_id, false);
-            if let Err(err) = out {
-                log::warn!("Error disabling task {task_id} - {err}");
-            }
-            let state = compute_state(state);
-            if let Ok(state) = &state {
-                pub_state(state, pub_socket);
-            }
-            state
-        }
-        OClockClientCommand::JsonSwitchTask { task_id } => {
-            state.switch_task(task_id)?;
-            let state = compute_state(state);
-            if let Ok(state) = &state {
-                pub_state(state, pub_socket);
-            }
-            state
-        }
-        OClockClientCommand::JsonRetroSwitchTask {
-            task_id,
-            timestamp,
-            keep_previous_task,
-        } => {
-            state.retro_switch_task(task_id as i32, timestamp as i32, keep_previous_task)?;
-            let state = compute_state(state);
-            if let Ok(state) = &state {
-                pub_state(state, pub_socket);
-            }
-            state
-        }
-        OClockClientCommand::JsonState => compute_state(state),
+fn pub_notification(notification: &Notification, sub_socket: &mut Socket, hooks_dir: &Path) {
+    let payload = serde_json::to_string(notification).expect("error serializing notification");
+    log::info!("Sending notification {payload}");
+    let out = sub_socket.send(payload.as_bytes());
+    if let Err(err) = out {
+        log::error!("Error publishing notification - {err:?}");
     }
+    run_hook(hooks_dir, notification.hook_name(), &payload);
+}
+
+fn handle_msg(
+    msg: OClockClientCommand,
+    state: &State,
+    pub_socket: &mut Socket,
+) -> Result<serde_json::Value, String> {
+    dispatch(msg, state, &mut |state| pub_state(state, pub_socket))
 }
 
 fn nanomsg_listen(socket: &mut Socket, pub_socket: &mut Socket, state: &State) -> MsgListenerStatus {
@@ -262,16 +127,22 @@ pub fn server() {
     let (command_tx, command_rx): (Sender<MsgListenerStatus>, Receiver<MsgListenerStatus>) =
         mpsc::channel();
 
-    let cfg_path = match env::var("HOME") {
-        Ok(path) => format!("{}/.oclock", path),
-        Err(_) => ".".to_string(),
-    };
+    let cfg_path = default_data_dir();
 
     fs::create_dir_all(&cfg_path).unwrap_or_else(|why| {
         println!("! {:?}", why.kind());
     });
 
-    let state = State::new(cfg_path);
+    let hooks_dir = cfg_path.join("hooks");
+    let backups_dir = cfg_path.join("backups");
+
+    let state = match State::new(cfg_path.display().to_string()) {
+        Ok(state) => state,
+        Err(err) => {
+            log::error!("Error opening database - {err}");
+            return;
+        }
+    };
     let out = state.system_event(SystemEventType::Startup);
     if let Err(err) = out {
         log::warn!("Error pushing system event startup - {err}");
@@ -281,6 +152,9 @@ pub fn server() {
         log::warn!("Error pushing system event ping - {err}");
     }
 
+    let mut pomodoro_pub_socket = nanomsg_sub_socket.clone();
+    let mut ping_pub_socket = nanomsg_sub_socket.clone();
+
     let mut a = Agenda::new();
 
     // Run every second
@@ -295,10 +169,51 @@ pub fn server() {
         "* * * * * *".parse().unwrap(),
     ));
 
+    // Advance the pomodoro timer publishing the countdown, the full state only when a phase is over
+    a.add(Job::new(
+        || {
+            if !state.has_pomodoro() {
+                return;
+            }
+            match state.pomodoro_tick() {
+                Ok(true) => {
+                    if let Ok(state) = compute_state(&state) {
+                        pub_state(&state, &mut pomodoro_pub_socket);
+                    }
+                }
+                Ok(false) => pub_state(&state.pomodoro_countdown(), &mut pomodoro_pub_socket),
+                Err(err) => log::error!("Error advancing pomodoro - {err}"),
+            }
+        },
+        "* * * * * *".parse().unwrap(),
+    ));
+
     // Run every minute
     a.add(Job::new(
         || {
             state.ping();
+            match state.run_scheduled_switches() {
+                Ok(true) => {
+                    if let Ok(state) = compute_state(&state) {
+                        pub_state(&state, &mut ping_pub_socket);
+                    }
+                }
+                Ok(false) => (),
+                Err(err) => log::error!("Error running scheduled switches - {err}"),
+            }
+            match state.check_budgets() {
+                Ok(notifications) => {
+                    for notification in notifications {
+                        pub_notification(&notification, &mut ping_pub_socket, &hooks_dir);
+                    }
+                }
+                Err(err) => log::error!("Error checking budgets - {err}"),
+            }
+            match state.daily_backup(&backups_dir) {
+                Ok(Some(path)) => log::info!("Daily backup written to {}", path.display()),
+                Ok(None) => (),
+                Err(err) => log::error!("Error writing daily backup - {err}"),
+            }
         },
         "0 * * * * *".parse().unwrap(),
     ));
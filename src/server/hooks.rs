@@ -0,0 +1,22 @@
+use std::path::Path;
+use std::process::Command;
+use std::thread;
+
+/// Run the executable `<hooks_dir>/<name>`, when present, passing the json payload as first argument
+pub fn run_hook(hooks_dir: &Path, name: &str, payload: &str) {
+    let hook_path = hooks_dir.join(name);
+    if !hook_path.is_file() {
+        return;
+    }
+
+    match Command::new(&hook_path).arg(payload).spawn() {
+        Ok(mut child) => {
+            thread::spawn(move || {
+                if let Err(err) = child.wait() {
+                    log::error!("Error waiting hook termination - {err}");
+                }
+            });
+        }
+        Err(err) => log::error!("Error running hook {} - {err}", hook_path.display()),
+    }
+}
@@ -1,3 +1,3 @@
-mod state;
+mod hooks;
 
 pub mod handler;
@@ -0,0 +1,66 @@
+use chrono::NaiveDate;
+
+/// Number of daily backups kept in the data directory
+pub const KEPT_DAILY_BACKUPS: usize = 7;
+
+const DAILY_BACKUP_PREFIX: &str = "oclock-";
+const DAILY_BACKUP_SUFFIX: &str = ".db";
+
+pub fn daily_backup_name(day: NaiveDate) -> String {
+    format!(
+        "{}{}{}",
+        DAILY_BACKUP_PREFIX,
+        day.format("%Y-%m-%d"),
+        DAILY_BACKUP_SUFFIX
+    )
+}
+
+fn is_daily_backup(file_name: &str) -> bool {
+    file_name
+        .strip_prefix(DAILY_BACKUP_PREFIX)
+        .and_then(|name| name.strip_suffix(DAILY_BACKUP_SUFFIX))
+        .map(|day| NaiveDate::parse_from_str(day, "%Y-%m-%d").is_ok())
+        .unwrap_or(false)
+}
+
+/// Daily backups to delete so that only the most recent `keep` remain, other files are ignored
+pub fn expired_backups(file_names: &[String], keep: usize) -> Vec<String> {
+    let mut backups: Vec<&String> = file_names
+        .iter()
+        .filter(|name| is_daily_backup(name))
+        .collect();
+    backups.sort();
+
+    let expired = backups.len().saturating_sub(keep);
+    backups.into_iter().take(expired).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expired_backups() {
+        let files: Vec<String> = [
+            "oclock-2024-03-05.db",
+            "notes.txt",
+            "oclock-2024-03-03.db",
+            "oclock-2024-03-04.db",
+            "oclock-2024-03-04.db.partial",
+            "oclock-latest.db",
+        ]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+
+        assert_eq!(
+            daily_backup_name(NaiveDate::from_ymd_opt(2024, 3, 5).unwrap()),
+            "oclock-2024-03-05.db"
+        );
+        assert_eq!(
+            expired_backups(&files, 2),
+            vec![String::from("oclock-2024-03-03.db")]
+        );
+        assert!(expired_backups(&files, 7).is_empty());
+    }
+}
@@ -0,0 +1,319 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use oclock_sqlite::models::TaskRate;
+use serde::Serialize;
+
+use crate::tracker::rounding::Durations;
+
+/// Day from which the rates set without an explicit start apply
+pub const ALWAYS_EFFECTIVE: &str = "1970-01-01";
+
+/// Rate of a task as listed to the user, with the amount in the currency units
+#[derive(Serialize)]
+pub struct RateEntry {
+    pub rate_id: i32,
+    pub task_id: i32,
+    pub task_name: String,
+    pub hourly_rate: String,
+    pub currency: String,
+    pub effective_from: String,
+}
+
+/// Time spent on a task in a day, along with the billable amount of the rounded time when the task has a rate
+#[derive(Debug, PartialEq)]
+pub struct BillableEntry {
+    pub day: NaiveDate,
+    pub task: String,
+    pub duration: i64,
+    pub rounded_duration: i64,
+    pub hourly_rate: Option<i32>,
+    pub currency: Option<String>,
+    pub amount: Option<i64>,
+}
+
+/// Span of time a billable total sums up
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum TotalPeriod {
+    /// Week starting on the given monday
+    Week(NaiveDate),
+    /// Month starting on the given day
+    Month(NaiveDate),
+    /// Whole requested range
+    All,
+}
+
+/// Time and amount of a period in a currency, `None` collecting the time without a rate
+#[derive(Debug, PartialEq)]
+pub struct BillableTotal {
+    pub period: TotalPeriod,
+    pub currency: Option<String>,
+    pub duration: i64,
+    pub rounded_duration: i64,
+    pub amount: i64,
+}
+
+/// Parse an amount like `85`, `85.5` or `85,50` into cents
+pub fn parse_amount(amount: &str) -> Result<i32, String> {
+    let invalid = || {
+        format!(
+            "Invalid amount '{}', expected a number with two decimals at most",
+            amount
+        )
+    };
+    let normalized = amount.trim().replace(',', ".");
+    let (units, cents) = normalized.split_once('.').unwrap_or((&normalized, ""));
+    if units.is_empty()
+        || cents.len() > 2
+        || !units
+            .chars()
+            .chain(cents.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let units: i32 = units.parse().map_err(|_| invalid())?;
+    let cents: i32 = format!("{:0<2}", cents).parse().map_err(|_| invalid())?;
+    units
+        .checked_mul(100)
+        .and_then(|units| units.checked_add(cents))
+        .ok_or_else(invalid)
+}
+
+/// Format cents as an amount with two decimals
+pub fn format_amount(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{}{}.{:02}", sign, cents.abs() / 100, cents.abs() % 100)
+}
+
+/// Cents earned in the given seconds, rounding half cents up
+pub fn amount(seconds: i64, hourly_rate: i32) -> i64 {
+    (seconds * i64::from(hourly_rate) + 1800) / 3600
+}
+
+/// Rate of the task in effect on the given day, the one starting last before it
+pub fn rate_on(rates: &[TaskRate], task_id: i32, day: NaiveDate) -> Option<&TaskRate> {
+    let day = day.format("%Y-%m-%d").to_string();
+    rates
+        .iter()
+        .filter(|r| r.task_id == task_id && r.effective_from <= day)
+        .max_by(|a, b| a.effective_from.cmp(&b.effective_from))
+}
+
+/// Time spent per day per task, valued with the rate in effect on each day
+///
+/// `task_ids` maps the task names of the durations to their ids.
+pub fn billable_entries(
+    durations: BTreeMap<(NaiveDate, String), Durations>,
+    task_ids: &BTreeMap<String, i32>,
+    rates: &[TaskRate],
+) -> Vec<BillableEntry> {
+    durations
+        .into_iter()
+        .map(|((day, task), durations)| {
+            let rate = task_ids
+                .get(&task)
+                .and_then(|task_id| rate_on(rates, *task_id, day));
+            BillableEntry {
+                day,
+                task,
+                duration: durations.raw,
+                rounded_duration: durations.rounded,
+                hourly_rate: rate.map(|r| r.hourly_rate),
+                currency: rate.map(|r| r.currency.clone()),
+                amount: rate.map(|r| amount(durations.rounded, r.hourly_rate)),
+            }
+        })
+        .collect()
+}
+
+/// Totals of the entries per currency, the weekly subtotals come first, then the monthly ones and last
+/// the totals of the whole range; in each period the time without a rate comes first
+pub fn billable_totals(entries: &[BillableEntry]) -> Vec<BillableTotal> {
+    let mut totals: BTreeMap<(TotalPeriod, Option<&str>), (Durations, i64)> = BTreeMap::new();
+    for entry in entries {
+        let periods = [
+            TotalPeriod::Week(entry.day.week(Weekday::Mon).first_day()),
+            TotalPeriod::Month(entry.day.with_day(1).unwrap_or(entry.day)),
+            TotalPeriod::All,
+        ];
+        for period in periods {
+            let total = totals
+                .entry((period, entry.currency.as_deref()))
+                .or_default();
+            total.0.raw += entry.duration;
+            total.0.rounded += entry.rounded_duration;
+            total.1 += entry.amount.unwrap_or(0);
+        }
+    }
+
+    totals
+        .into_iter()
+        .map(|((period, currency), (durations, amount))| BillableTotal {
+            period,
+            currency: currency.map(str::to_string),
+            duration: durations.raw,
+            rounded_duration: durations.rounded,
+            amount,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto::command::{Rounding, RoundingMode, RoundingScope};
+    use crate::tracker::interval::{self, test_day, Interval};
+    use crate::tracker::rounding;
+    use chrono::Utc;
+
+    fn rate(id: i32, task_id: i32, hourly_rate: i32, effective_from: &str) -> TaskRate {
+        TaskRate {
+            id,
+            task_id,
+            hourly_rate,
+            currency: String::from("EUR"),
+            effective_from: String::from(effective_from),
+        }
+    }
+
+    #[test]
+    fn test_amounts() {
+        assert_eq!(parse_amount("85"), Ok(8500));
+        assert_eq!(parse_amount(" 85.5"), Ok(8550));
+        assert_eq!(parse_amount("85,05"), Ok(8505));
+        assert!(parse_amount("85.505").is_err());
+        assert!(parse_amount("-85").is_err());
+        assert!(parse_amount(".5").is_err());
+
+        assert_eq!(format_amount(8505), "85.05");
+        assert_eq!(format_amount(-5), "-0.05");
+
+        assert_eq!(amount(5400, 8550), 12825);
+        assert_eq!(amount(1, 1800), 1);
+        assert_eq!(amount(1, 1799), 0);
+    }
+
+    #[test]
+    fn test_billable_entries() {
+        let first_day = interval::day_start(test_day("2024-03-05"), &Utc);
+        let interval = |task: &str, start: i64, end: i64| Interval {
+            task: String::from(task),
+            start: first_day + start,
+            end: Some(first_day + end),
+        };
+        let intervals = vec![
+            interval("dev", 0, 3600),
+            interval("meeting", 3600, 5400),
+            interval("dev", 5400, 7200),
+            interval("dev", 86400, 90000),
+        ];
+        let task_ids = BTreeMap::from([(String::from("dev"), 1), (String::from("meeting"), 2)]);
+        let rates = vec![
+            rate(1, 1, 6000, ALWAYS_EFFECTIVE),
+            rate(2, 1, 9000, "2024-03-06"),
+            rate(3, 2, 5000, "2024-04-01"),
+        ];
+
+        let entries = billable_entries(
+            rounding::day_durations(&intervals, &Utc, None),
+            &task_ids,
+            &rates,
+        );
+        assert_eq!(
+            entries
+                .iter()
+                .map(|e| (e.task.as_str(), e.duration, e.hourly_rate, e.amount))
+                .collect::<Vec<_>>(),
+            vec![
+                ("dev", 5400, Some(6000), Some(9000)),
+                ("meeting", 1800, None, None),
+                ("dev", 3600, Some(9000), Some(9000)),
+            ]
+        );
+        let totals = billable_totals(&entries);
+        assert_eq!(
+            totals
+                .iter()
+                .map(|t| (t.period, t.currency.as_deref(), t.duration, t.amount))
+                .collect::<Vec<_>>(),
+            vec![
+                (TotalPeriod::Week(test_day("2024-03-04")), None, 1800, 0),
+                (
+                    TotalPeriod::Week(test_day("2024-03-04")),
+                    Some("EUR"),
+                    9000,
+                    18000
+                ),
+                (TotalPeriod::Month(test_day("2024-03-01")), None, 1800, 0),
+                (
+                    TotalPeriod::Month(test_day("2024-03-01")),
+                    Some("EUR"),
+                    9000,
+                    18000
+                ),
+                (TotalPeriod::All, None, 1800, 0),
+                (TotalPeriod::All, Some("EUR"), 9000, 18000),
+            ]
+        );
+
+        // Subtotals split the entries by week and month
+        let entries = vec![
+            BillableEntry {
+                day: test_day("2024-02-29"),
+                task: String::from("dev"),
+                duration: 3600,
+                rounded_duration: 3600,
+                hourly_rate: Some(6000),
+                currency: Some(String::from("EUR")),
+                amount: Some(6000),
+            },
+            BillableEntry {
+                day: test_day("2024-03-04"),
+                task: String::from("dev"),
+                duration: 1800,
+                rounded_duration: 1800,
+                hourly_rate: Some(6000),
+                currency: Some(String::from("EUR")),
+                amount: Some(3000),
+            },
+        ];
+        assert_eq!(
+            billable_totals(&entries)
+                .iter()
+                .map(|t| (t.period, t.duration, t.amount))
+                .collect::<Vec<_>>(),
+            vec![
+                (TotalPeriod::Week(test_day("2024-02-26")), 3600, 6000),
+                (TotalPeriod::Week(test_day("2024-03-04")), 1800, 3000),
+                (TotalPeriod::Month(test_day("2024-02-01")), 3600, 6000),
+                (TotalPeriod::Month(test_day("2024-03-01")), 1800, 3000),
+                (TotalPeriod::All, 5400, 9000),
+            ]
+        );
+
+        // Amounts are computed on the rounded durations
+        let hours = Rounding {
+            increment_minutes: 60,
+            mode: RoundingMode::Up,
+            scope: RoundingScope::Day,
+        };
+        let entries = billable_entries(
+            rounding::day_durations(&intervals, &Utc, Some(&hours)),
+            &task_ids,
+            &rates,
+        );
+        assert_eq!(
+            entries
+                .iter()
+                .map(|e| (e.duration, e.rounded_duration, e.amount))
+                .collect::<Vec<_>>(),
+            vec![
+                (5400, 7200, Some(12000)),
+                (1800, 3600, None),
+                (3600, 3600, Some(9000)),
+            ]
+        );
+    }
+}
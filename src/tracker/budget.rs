@@ -0,0 +1,97 @@
+use chrono::{DateTime, Datelike, Duration, TimeZone};
+use oclock_sqlite::constants::BudgetPeriod;
+use oclock_sqlite::models::HistoryEntry;
+use serde::Serialize;
+
+use crate::dto::command;
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BudgetLevel {
+    Ok,
+    Warning,
+    Exceeded,
+}
+
+#[derive(Serialize)]
+pub struct BudgetStatus {
+    pub budget_id: i32,
+    pub task_id: i32,
+    pub task_name: String,
+    pub period: BudgetPeriod,
+    pub period_start: i64,
+    pub amount: i32,
+    pub spent: i32,
+    pub remaining: i32,
+    pub level: BudgetLevel,
+}
+
+impl From<command::BudgetPeriod> for BudgetPeriod {
+    fn from(value: command::BudgetPeriod) -> Self {
+        match value {
+            command::BudgetPeriod::Day => BudgetPeriod::Day,
+            command::BudgetPeriod::Week => BudgetPeriod::Week,
+            command::BudgetPeriod::Month => BudgetPeriod::Month,
+            command::BudgetPeriod::Total => BudgetPeriod::Total,
+        }
+    }
+}
+
+/// Unix timestamp of the beginning of the period containing `now`
+pub fn period_start<Tz: TimeZone>(period: BudgetPeriod, now: &DateTime<Tz>) -> i64 {
+    let today = now.date_naive();
+    let first_day = match period {
+        BudgetPeriod::Day => today,
+        BudgetPeriod::Week => {
+            today - Duration::days(i64::from(today.weekday().num_days_from_monday()))
+        }
+        BudgetPeriod::Month => today.with_day(1).unwrap_or(today),
+        BudgetPeriod::Total => return 0,
+    };
+
+    now.timezone()
+        .from_local_datetime(&first_day.and_hms_opt(0, 0, 0).unwrap_or_default())
+        .earliest()
+        .map(|start| start.timestamp())
+        .unwrap_or(0)
+}
+
+/// Seconds spent in the given intervals after `since`
+pub fn spent_since(history: &[HistoryEntry], since: i64) -> i32 {
+    history
+        .iter()
+        .filter_map(|entry| {
+            entry
+                .ts_end
+                .map(|ts_end| i64::from(ts_end) - since.max(i64::from(entry.ts_start)))
+        })
+        .filter(|amount| *amount > 0)
+        .sum::<i64>() as i32
+}
+
+pub fn budget_level(amount: i32, spent: i32, warning_percent: i32) -> BudgetLevel {
+    if spent >= amount {
+        BudgetLevel::Exceeded
+    } else if i64::from(spent) * 100 >= i64::from(amount) * i64::from(warning_percent) {
+        BudgetLevel::Warning
+    } else {
+        BudgetLevel::Ok
+    }
+}
+
+#[test]
+fn test_period_start() {
+    use chrono::Utc;
+
+    // Thursday 2024-02-15 10:30:00 UTC
+    let now = Utc.with_ymd_and_hms(2024, 2, 15, 10, 30, 0).unwrap();
+
+    assert_eq!(period_start(BudgetPeriod::Day, &now), 1707955200);
+    assert_eq!(period_start(BudgetPeriod::Week, &now), 1707696000);
+    assert_eq!(period_start(BudgetPeriod::Month, &now), 1706745600);
+    assert_eq!(period_start(BudgetPeriod::Total, &now), 0);
+
+    assert_eq!(budget_level(3600, 2000, 80), BudgetLevel::Ok);
+    assert_eq!(budget_level(3600, 2880, 80), BudgetLevel::Warning);
+    assert_eq!(budget_level(3600, 3600, 80), BudgetLevel::Exceeded);
+}
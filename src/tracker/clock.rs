@@ -0,0 +1,77 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use chrono::{DateTime, Local, TimeZone};
+
+/// Source of the current time for the tracker
+pub trait Clock {
+    fn now(&self) -> DateTime<Local>;
+
+    /// Seconds since the unix epoch
+    fn unix_now(&self) -> i64 {
+        self.now().timestamp()
+    }
+}
+
+/// Wall clock of the machine
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// Manually driven clock, clones share the same time so it can be moved forward after being
+/// handed over to the tracker
+#[derive(Clone)]
+pub struct FakeClock {
+    unix_now: Rc<Cell<i64>>,
+}
+
+impl FakeClock {
+    pub fn new(unix_now: i64) -> FakeClock {
+        FakeClock {
+            unix_now: Rc::new(Cell::new(unix_now)),
+        }
+    }
+
+    pub fn set(&self, unix_now: i64) {
+        self.unix_now.set(unix_now);
+    }
+
+    pub fn advance(&self, seconds: i64) {
+        self.unix_now.set(self.unix_now.get() + seconds);
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> DateTime<Local> {
+        Local
+            .timestamp_opt(self.unix_now.get(), 0)
+            .single()
+            .expect("fake clock out of range")
+    }
+
+    fn unix_now(&self) -> i64 {
+        self.unix_now.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fake_clock_shared() {
+        let clock = FakeClock::new(1_700_000_000);
+        let handle = clock.clone();
+
+        handle.advance(90);
+        assert_eq!(clock.unix_now(), 1_700_000_090);
+        assert_eq!(clock.now().timestamp(), 1_700_000_090);
+
+        handle.set(1_600_000_000);
+        assert_eq!(clock.unix_now(), 1_600_000_000);
+    }
+}
@@ -0,0 +1,79 @@
+use oclock_sqlite::constants::ContextKind;
+use oclock_sqlite::models::ContextRule;
+use regex::Regex;
+
+use crate::dto::command;
+
+impl From<command::ContextKind> for ContextKind {
+    fn from(value: command::ContextKind) -> Self {
+        match value {
+            command::ContextKind::GitBranch => ContextKind::GitBranch,
+            command::ContextKind::WorkingDirectory => ContextKind::WorkingDirectory,
+            command::ContextKind::WindowTitle => ContextKind::WindowTitle,
+            command::ContextKind::CalendarEntry => ContextKind::CalendarEntry,
+        }
+    }
+}
+
+pub fn validate_pattern(pattern: &str) -> Result<(), String> {
+    Regex::new(pattern)
+        .map(|_| ())
+        .map_err(|err| format!("Invalid rule pattern '{}' - {}", pattern, err))
+}
+
+/// First rule, in creation order, of the given kind matching the context value
+pub fn find_matching_rule<'a>(
+    rules: &'a [ContextRule],
+    kind: ContextKind,
+    value: &str,
+) -> Option<&'a ContextRule> {
+    let kind = kind.to_string();
+    rules
+        .iter()
+        .filter(|rule| rule.context_kind == kind)
+        .find(|rule| match Regex::new(&rule.pattern) {
+            Ok(regex) => regex.is_match(value),
+            Err(err) => {
+                log::warn!("Skipping context rule {} - {err}", rule.id);
+                false
+            }
+        })
+}
+
+#[test]
+fn test_find_matching_rule() {
+    let rules = vec![
+        ContextRule {
+            id: 1,
+            context_kind: ContextKind::GitBranch.to_string(),
+            pattern: String::from("^feature/PROJ-\\d+"),
+            task_id: 1,
+        },
+        ContextRule {
+            id: 2,
+            context_kind: ContextKind::WindowTitle.to_string(),
+            pattern: String::from("(?i)zoom meeting"),
+            task_id: 2,
+        },
+        ContextRule {
+            id: 3,
+            context_kind: ContextKind::GitBranch.to_string(),
+            pattern: String::from(".*"),
+            task_id: 3,
+        },
+    ];
+
+    let rule_id = |kind, value| find_matching_rule(&rules, kind, value).map(|rule| rule.id);
+
+    assert_eq!(
+        rule_id(ContextKind::GitBranch, "feature/PROJ-12-login"),
+        Some(1)
+    );
+    assert_eq!(rule_id(ContextKind::GitBranch, "main"), Some(3));
+    assert_eq!(
+        rule_id(ContextKind::WindowTitle, "Zoom Meeting - Daily"),
+        Some(2)
+    );
+    assert_eq!(rule_id(ContextKind::WindowTitle, "Terminal"), None);
+    assert_eq!(rule_id(ContextKind::CalendarEntry, "Standup"), None);
+}
@@ -0,0 +1,247 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use csv::{ReaderBuilder, StringRecord};
+
+use crate::tracker::interval::Interval;
+
+const DATE_FORMATS: [&str; 2] = ["%Y-%m-%d", "%d.%m.%Y"];
+/// Slash separated dates, month or day first depending on the locale of the exporting account
+const SLASH_DATE_FORMATS: [&str; 2] = ["%m/%d/%Y", "%d/%m/%Y"];
+const TIME_FORMATS: [&str; 4] = ["%H:%M:%S", "%H:%M", "%I:%M:%S %p", "%I:%M %p"];
+
+/// Separator between project and task in the name of the imported tasks
+const TASK_SEPARATOR: &str = " / ";
+
+/// Columns of the detailed reports, looked up by name since Toggl and Clockify order them differently
+struct Columns {
+    project: Option<usize>,
+    task: Option<usize>,
+    description: Option<usize>,
+    start_date: usize,
+    start_time: usize,
+    end_date: usize,
+    end_time: usize,
+}
+
+impl Columns {
+    fn find(headers: &StringRecord) -> Result<Columns, String> {
+        let position = |name: &str| {
+            headers.iter().position(|h| {
+                h.trim()
+                    .trim_start_matches('\u{feff}')
+                    .eq_ignore_ascii_case(name)
+            })
+        };
+        let required = |name: &str| position(name).ok_or(format!("Missing column '{}'", name));
+
+        Ok(Columns {
+            project: position("project"),
+            task: position("task"),
+            description: position("description"),
+            start_date: required("start date")?,
+            start_time: required("start time")?,
+            end_date: required("end date")?,
+            end_time: required("end time")?,
+        })
+    }
+}
+
+/// Day in the given format, or in any of the known ones when they all read it the same way
+fn parse_date(date: &str, date_format: Option<&str>) -> Result<NaiveDate, String> {
+    let date = date.trim();
+    if let Some(format) = date_format {
+        return NaiveDate::parse_from_str(date, format)
+            .map_err(|_| format!("Invalid date '{}', expected format '{}'", date, format));
+    }
+
+    let mut days: Vec<NaiveDate> = DATE_FORMATS
+        .iter()
+        .chain(SLASH_DATE_FORMATS.iter())
+        .filter_map(|f| NaiveDate::parse_from_str(date, f).ok())
+        .collect();
+    days.dedup();
+    match days[..] {
+        [day] => Ok(day),
+        [] => Err(format!("Invalid date '{}'", date)),
+        _ => Err(format!(
+            "Ambiguous date '{}', the date format must be given",
+            date
+        )),
+    }
+}
+
+fn parse_datetime<Tz: TimeZone>(
+    date: &str,
+    time: &str,
+    date_format: Option<&str>,
+    tz: &Tz,
+) -> Result<i64, String> {
+    let day = parse_date(date, date_format)?;
+    let time = TIME_FORMATS
+        .iter()
+        .find_map(|f| NaiveTime::parse_from_str(time.trim(), f).ok())
+        .ok_or(format!("Invalid time '{}'", time))?;
+
+    tz.from_local_datetime(&NaiveDateTime::new(day, time))
+        .earliest()
+        .map(|dt| dt.timestamp())
+        .ok_or(format!("Nonexistent local time {} {}", day, time))
+}
+
+/// Name of the oclock task, made of the project and the task, falling back to the description
+fn task_name(record: &StringRecord, columns: &Columns) -> String {
+    let field = |column: Option<usize>| {
+        column
+            .and_then(|c| record.get(c))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    };
+
+    let parts: Vec<&str> = [field(columns.project), field(columns.task)]
+        .into_iter()
+        .flatten()
+        .collect();
+    if parts.is_empty() {
+        field(columns.description)
+            .unwrap_or("No project")
+            .to_string()
+    } else {
+        parts.join(TASK_SEPARATOR)
+    }
+}
+
+/// Read the detailed csv report of Toggl Track or Clockify, dates are in the given time zone
+///
+/// Without a `date_format` (e.g. `%d/%m/%Y`) the dates that could be read with the day or the month
+/// first are rejected.
+pub fn read_intervals<Tz: TimeZone>(
+    content: &str,
+    date_format: Option<&str>,
+    tz: &Tz,
+) -> Result<Vec<Interval>, String> {
+    let mut reader = ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(content.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Error reading csv headers: '{}'", e))?;
+    let columns = Columns::find(headers)?;
+
+    let mut intervals = Vec::new();
+    for (idx, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| format!("Error reading csv: '{}'", e))?;
+        let field = |column: usize| record.get(column).unwrap_or_default();
+        let line_error = |e: String| format!("Line {}: {}", idx + 2, e);
+
+        intervals.push(Interval {
+            task: task_name(&record, &columns),
+            start: parse_datetime(
+                field(columns.start_date),
+                field(columns.start_time),
+                date_format,
+                tz,
+            )
+            .map_err(line_error)?,
+            end: Some(
+                parse_datetime(
+                    field(columns.end_date),
+                    field(columns.end_time),
+                    date_format,
+                    tz,
+                )
+                .map_err(line_error)?,
+            ),
+        });
+    }
+    Ok(intervals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_toggl_report() {
+        let content = "\u{feff}User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags,Amount ()\n\
+                       Jo,jo@example.com,ACME,Website,Backend,API,Yes,2024-03-05,09:00:00,2024-03-05,10:30:00,01:30:00,,\n\
+                       Jo,jo@example.com,,,,Email,No,2024-03-05,23:30:00,2024-03-06,00:15:00,00:45:00,,\n";
+
+        assert_eq!(
+            read_intervals(content, None, &Utc).unwrap(),
+            vec![
+                Interval {
+                    task: String::from("Website / Backend"),
+                    start: 1709629200,
+                    end: Some(1709634600),
+                },
+                Interval {
+                    task: String::from("Email"),
+                    start: 1709681400,
+                    end: Some(1709684100),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clockify_report() {
+        let content = "Project,Client,Description,Task,User,Group,Email,Tags,Billable,Start Date,Start Time,End Date,End Time,Duration (h),Duration (decimal)\n\
+                       Website,ACME,,,Jo,,jo@example.com,,Yes,03/05/2024,09:00:00 AM,03/05/2024,01:15:00 PM,04:15:00,4.25\n\
+                       ,,,,Jo,,jo@example.com,,No,03/05/2024,02:00 PM,03/05/2024,02:30 PM,00:30:00,0.50\n";
+
+        assert_eq!(
+            read_intervals(content, Some("%m/%d/%Y"), &Utc).unwrap(),
+            vec![
+                Interval {
+                    task: String::from("Website"),
+                    start: 1709629200,
+                    end: Some(1709644500),
+                },
+                Interval {
+                    task: String::from("No project"),
+                    start: 1709647200,
+                    end: Some(1709649000),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_report() {
+        assert_eq!(
+            read_intervals("Project,Start date\n", None, &Utc),
+            Err(String::from("Missing column 'start time'"))
+        );
+        assert_eq!(
+            read_intervals(
+                "Project,Start date,Start time,End date,End time\nWeb,2024-03-05,9am,2024-03-05,10:00\n",
+                None,
+                &Utc
+            ),
+            Err(String::from("Line 2: Invalid time '9am'"))
+        );
+    }
+
+    #[test]
+    fn test_slash_dates() {
+        let content = "Project,Start date,Start time,End date,End time\n\
+                       Web,03/05/2024,09:00,03/05/2024,10:00\n";
+        assert_eq!(
+            read_intervals(content, None, &Utc),
+            Err(String::from(
+                "Line 2: Ambiguous date '03/05/2024', the date format must be given"
+            ))
+        );
+        assert_eq!(
+            read_intervals(content, Some("%d/%m/%Y"), &Utc).unwrap()[0].start,
+            1714726800
+        );
+
+        let content = "Project,Start date,Start time,End date,End time\n\
+                       Web,25/03/2024,09:00,25/03/2024,10:00\n";
+        assert_eq!(
+            read_intervals(content, None, &Utc).unwrap()[0].start,
+            1711357200
+        );
+    }
+}
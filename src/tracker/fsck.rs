@@ -0,0 +1,325 @@
+use std::collections::HashSet;
+
+use chrono::TimeZone;
+use itertools::Itertools;
+use oclock_sqlite::constants::SystemEventType;
+use oclock_sqlite::models::Event;
+use serde::Serialize;
+
+/// Problem found in the event log
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(tag = "problem", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Finding {
+    /// Switch to a task that doesn't exist anymore, fixed turning it into a pause
+    MissingTask {
+        event_id: i32,
+        timestamp: i32,
+        task_id: i32,
+    },
+    /// Consecutive switches to the same task, or pauses, sharing the same timestamp, all but the
+    /// last one are removed
+    DuplicateEvent { timestamp: i32, event_ids: Vec<i32> },
+    /// Ping rows left behind, only the most recent one is kept
+    MultiplePings { event_ids: Vec<i32> },
+    /// Daemon session never shut down, fixed closing it right after its last event, reported only
+    /// when the next session starts in the same second as that event
+    StrayStartup {
+        event_id: i32,
+        timestamp: i32,
+        shutdown_at: Option<i32>,
+    },
+    /// Task tracked across days while the daemon was down, reported only since the real end is
+    /// unknown
+    UnattendedInterval {
+        event_id: i32,
+        task_id: i32,
+        ts_start: i32,
+        ts_end: i32,
+    },
+}
+
+/// Changes fixing the findings
+#[derive(Default, Debug, PartialEq)]
+pub struct Repairs {
+    pub delete_event_ids: Vec<i32>,
+    pub pause_event_ids: Vec<i32>,
+    pub shutdown_timestamps: Vec<i32>,
+}
+
+#[derive(Serialize)]
+pub struct FsckReport {
+    pub findings: Vec<Finding>,
+    pub fixed: bool,
+}
+
+fn is_system_event(event: &Event, event_type: SystemEventType) -> bool {
+    event.system_event_name.as_deref() == Some(event_type.to_string().as_str())
+}
+
+/// Scan the events, sorted by timestamp and id, looking for inconsistencies
+pub fn check<Tz: TimeZone>(events: &[Event], task_ids: &HashSet<i32>, tz: &Tz) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    let (pings, events): (Vec<&Event>, Vec<&Event>) = events
+        .iter()
+        .partition(|e| is_system_event(e, SystemEventType::Ping));
+    if pings.len() > 1 {
+        findings.push(Finding::MultiplePings {
+            event_ids: pings.iter().map(|e| e.id).collect(),
+        });
+    }
+
+    for event in &events {
+        if let Some(task_id) = event.task_id.filter(|id| !task_ids.contains(id)) {
+            findings.push(Finding::MissingTask {
+                event_id: event.id,
+                timestamp: event.event_timestamp,
+                task_id,
+            });
+        }
+    }
+
+    // Sessions are checked as they will be once the duplicates are removed and the missing tasks
+    // turned into pauses, startups and shutdowns are never considered duplicates
+    let mut distinct: Vec<&Event> = Vec::with_capacity(events.len());
+    for ((timestamp, ..), group) in &events.iter().chunk_by(|e| {
+        let session_id = (is_system_event(e, SystemEventType::Startup)
+            || is_system_event(e, SystemEventType::Shutdown))
+        .then_some(e.id);
+        match e.task_id.filter(|id| !task_ids.contains(id)) {
+            Some(_) => (
+                e.event_timestamp,
+                None,
+                Some(SystemEventType::Pause.to_string()),
+                session_id,
+            ),
+            None => (
+                e.event_timestamp,
+                e.task_id,
+                e.system_event_name.clone(),
+                session_id,
+            ),
+        }
+    }) {
+        let group: Vec<&Event> = group.copied().collect();
+        if group.len() > 1 {
+            findings.push(Finding::DuplicateEvent {
+                timestamp,
+                event_ids: group.iter().map(|e| e.id).collect(),
+            });
+        }
+        distinct.extend(group.last());
+    }
+    let events = distinct;
+
+    let day = |timestamp: i32| {
+        tz.timestamp_opt(i64::from(timestamp), 0)
+            .single()
+            .map(|dt| dt.date_naive())
+    };
+    let mut open_startup: Option<&Event> = None;
+    for (idx, event) in events.iter().enumerate() {
+        if is_system_event(event, SystemEventType::Startup) {
+            if let Some(startup) = open_startup {
+                // Right after the last event of the session, or in its same second when the next
+                // session starts a second later; the added row sorts after that event by its id
+                let last_timestamp = events[idx - 1].event_timestamp;
+                findings.push(Finding::StrayStartup {
+                    event_id: startup.id,
+                    timestamp: startup.event_timestamp,
+                    shutdown_at: (event.event_timestamp > last_timestamp).then(|| {
+                        (last_timestamp + 1)
+                            .min(event.event_timestamp - 1)
+                            .max(last_timestamp)
+                    }),
+                });
+            }
+            open_startup = Some(event);
+        } else if is_system_event(event, SystemEventType::Shutdown) {
+            open_startup = None;
+        }
+
+        if let (Some(task_id), None, Some(next)) =
+            (event.task_id, open_startup, events.get(idx + 1))
+        {
+            if day(event.event_timestamp) != day(next.event_timestamp) {
+                findings.push(Finding::UnattendedInterval {
+                    event_id: event.id,
+                    task_id,
+                    ts_start: event.event_timestamp,
+                    ts_end: next.event_timestamp,
+                });
+            }
+        }
+    }
+
+    findings
+}
+
+pub fn repairs(findings: &[Finding]) -> Repairs {
+    let mut repairs = Repairs::default();
+    for finding in findings {
+        match finding {
+            Finding::MissingTask { event_id, .. } => repairs.pause_event_ids.push(*event_id),
+            Finding::DuplicateEvent { event_ids, .. } | Finding::MultiplePings { event_ids } => {
+                repairs
+                    .delete_event_ids
+                    .extend(&event_ids[..event_ids.len() - 1]);
+            }
+            Finding::StrayStartup { shutdown_at, .. } => {
+                repairs.shutdown_timestamps.extend(shutdown_at)
+            }
+            Finding::UnattendedInterval { .. } => (),
+        }
+    }
+    repairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    const DAY: i32 = 24 * 3600;
+
+    fn event(
+        id: i32,
+        event_timestamp: i32,
+        task_id: Option<i32>,
+        system: Option<SystemEventType>,
+    ) -> Event {
+        Event {
+            id,
+            event_timestamp,
+            task_id,
+            system_event_name: system.map(|s| s.to_string()),
+            note: None,
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let task_ids: HashSet<i32> = [1, 2].into_iter().collect();
+        let events = vec![
+            event(1, 10 * 3600, None, Some(SystemEventType::Startup)),
+            event(2, 10 * 3600 + 60, Some(1), None),
+            event(3, 11 * 3600, Some(3), None),
+            event(4, 12 * 3600, Some(2), None),
+            event(5, DAY + 9 * 3600, None, Some(SystemEventType::Startup)),
+            event(6, DAY + 10 * 3600, Some(2), None),
+            event(7, DAY + 10 * 3600, Some(1), None),
+            event(8, DAY + 10 * 3600, Some(1), None),
+            event(9, DAY + 18 * 3600, None, Some(SystemEventType::Shutdown)),
+            event(10, DAY + 19 * 3600, Some(2), None),
+            event(11, 2 * DAY + 9 * 3600, None, Some(SystemEventType::Ping)),
+            event(12, 2 * DAY + 9 * 3600, None, Some(SystemEventType::Startup)),
+            event(13, 2 * DAY + 9 * 3600, Some(1), None),
+            event(14, 2 * DAY + 9 * 3600, None, Some(SystemEventType::Startup)),
+            event(15, 2 * DAY + 10 * 3600, None, Some(SystemEventType::Ping)),
+        ];
+
+        let findings = check(&events, &task_ids, &Utc);
+        assert_eq!(
+            findings,
+            vec![
+                Finding::MultiplePings {
+                    event_ids: vec![11, 15]
+                },
+                Finding::MissingTask {
+                    event_id: 3,
+                    timestamp: 11 * 3600,
+                    task_id: 3
+                },
+                Finding::DuplicateEvent {
+                    timestamp: DAY + 10 * 3600,
+                    event_ids: vec![7, 8]
+                },
+                Finding::StrayStartup {
+                    event_id: 1,
+                    timestamp: 10 * 3600,
+                    shutdown_at: Some(12 * 3600 + 1)
+                },
+                Finding::UnattendedInterval {
+                    event_id: 10,
+                    task_id: 2,
+                    ts_start: DAY + 19 * 3600,
+                    ts_end: 2 * DAY + 9 * 3600
+                },
+                Finding::StrayStartup {
+                    event_id: 12,
+                    timestamp: 2 * DAY + 9 * 3600,
+                    shutdown_at: None
+                },
+            ]
+        );
+
+        assert_eq!(
+            repairs(&findings),
+            Repairs {
+                delete_event_ids: vec![11, 7],
+                pause_event_ids: vec![3],
+                shutdown_timestamps: vec![12 * 3600 + 1],
+            }
+        );
+    }
+
+    /// Apply the repairs the way the tracker does, the added rows get the next ids
+    fn repaired(events: &[Event], repairs: Repairs) -> Vec<Event> {
+        let mut next_id = events.iter().map(|e| e.id).max().unwrap_or(0);
+        let mut events: Vec<Event> = events
+            .iter()
+            .filter(|e| !repairs.delete_event_ids.contains(&e.id))
+            .map(|e| match repairs.pause_event_ids.contains(&e.id) {
+                true => event(e.id, e.event_timestamp, None, Some(SystemEventType::Pause)),
+                false => Event {
+                    system_event_name: e.system_event_name.clone(),
+                    note: e.note.clone(),
+                    ..*e
+                },
+            })
+            .collect();
+        for timestamp in repairs.shutdown_timestamps {
+            next_id += 1;
+            events.push(event(
+                next_id,
+                timestamp,
+                None,
+                Some(SystemEventType::Shutdown),
+            ));
+        }
+        events.sort_by_key(|e| (e.event_timestamp, e.id));
+        events
+    }
+
+    #[test]
+    fn test_fix_leaves_nothing_to_fix() {
+        let task_ids: HashSet<i32> = [1, 2].into_iter().collect();
+        let events = vec![
+            event(1, 10 * 3600, None, Some(SystemEventType::Startup)),
+            event(2, 10 * 3600, Some(1), None),
+            event(3, 10 * 3600, Some(1), None),
+            event(4, 11 * 3600, Some(3), None),
+            event(5, 11 * 3600, None, Some(SystemEventType::Pause)),
+            event(6, 12 * 3600, Some(2), None),
+            event(7, 12 * 3600 + 1, None, Some(SystemEventType::Startup)),
+            event(8, 12 * 3600 + 1, Some(2), None),
+            event(9, 12 * 3600 + 1, Some(2), None),
+            event(10, 13 * 3600, None, Some(SystemEventType::Shutdown)),
+            event(11, 14 * 3600, None, Some(SystemEventType::Startup)),
+            event(12, 14 * 3600, None, Some(SystemEventType::Ping)),
+        ];
+
+        let findings = check(&events, &task_ids, &Utc);
+        assert_eq!(findings.len(), 5);
+
+        let events = repaired(&events, repairs(&findings));
+        assert_eq!(check(&events, &task_ids, &Utc), vec![]);
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| is_system_event(e, SystemEventType::Startup))
+                .count(),
+            3
+        );
+    }
+}
@@ -0,0 +1,252 @@
+use chrono::{Datelike, Days, NaiveDate, TimeZone};
+use oclock_sqlite::models::{HistoryEntry, Holiday, WorkingHours};
+use serde::Serialize;
+
+use crate::tracker::interval;
+
+/// Part of the working hours without any active task, timestamps are unix seconds
+#[derive(Serialize, Debug, PartialEq)]
+pub struct UntrackedGap {
+    pub start: i64,
+    pub end: i64,
+    pub duration: i64,
+    /// System event recorded when the gap starts (`Shutdown`, `Pause`, ...), missing when nothing was recorded
+    pub cause: Option<String>,
+}
+
+/// Unix timestamp of the given local minute of the day, the last one ending at the next midnight
+fn local_timestamp<Tz: TimeZone>(day: NaiveDate, minute_of_day: i32, tz: &Tz) -> i64 {
+    let day = day + Days::new(u64::from(minute_of_day as u32 / 1440));
+    let minute_of_day = minute_of_day as u32 % 1440;
+    day.and_hms_opt(minute_of_day / 60, minute_of_day % 60, 0)
+        .and_then(|local| tz.from_local_datetime(&local).earliest())
+        .map_or_else(|| interval::day_start(day, tz), |ts| ts.timestamp())
+}
+
+/// Working hours of the days between `from` and `to` (inclusive) as sorted and disjoint periods, holidays excluded
+pub fn working_periods<Tz: TimeZone>(
+    hours: &[WorkingHours],
+    holidays: &[Holiday],
+    from: NaiveDate,
+    to: NaiveDate,
+    tz: &Tz,
+) -> Vec<(i64, i64)> {
+    let mut periods: Vec<(i64, i64)> = from
+        .iter_days()
+        .take_while(|day| *day <= to)
+        .filter(|day| {
+            let day = day.format("%Y-%m-%d").to_string();
+            !holidays.iter().any(|h| h.day == day)
+        })
+        .flat_map(|day| {
+            let weekday_bit = 1 << day.weekday().num_days_from_monday();
+            hours
+                .iter()
+                .filter(move |h| h.weekdays & weekday_bit != 0)
+                .map(move |h| {
+                    (
+                        local_timestamp(day, h.start_minute, tz),
+                        local_timestamp(day, h.end_minute, tz),
+                    )
+                })
+        })
+        .filter(|(start, end)| end > start)
+        .collect();
+    periods.sort();
+
+    let mut merged: Vec<(i64, i64)> = Vec::new();
+    for (start, end) in periods {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Parts of the periods not covered by a task in the history, sorted by start time
+///
+/// Periods are cut at `now`, gaps shorter than `min_duration` seconds are left out. The running
+/// task covers the time up to `now`, see [interval::entry_ends].
+pub fn untracked_gaps(
+    periods: &[(i64, i64)],
+    history: &[HistoryEntry],
+    now: i64,
+    min_duration: i64,
+) -> Vec<UntrackedGap> {
+    let entries: Vec<(&HistoryEntry, i64)> = history
+        .iter()
+        .zip(interval::entry_ends(history, now))
+        .collect();
+
+    let mut tracked: Vec<(i64, i64)> = entries
+        .iter()
+        .filter(|(h, _)| h.task_id.is_some())
+        .map(|(h, end)| (i64::from(h.ts_start), *end))
+        .collect();
+    tracked.sort();
+
+    let cause = |at: i64| {
+        entries
+            .iter()
+            .filter(|(h, _)| h.task_id.is_none())
+            .find(|(h, end)| i64::from(h.ts_start) <= at && *end > at)
+            .and_then(|(h, _)| h.system_event.clone())
+    };
+
+    let mut gaps = Vec::new();
+    let mut add_gap = |start: i64, end: i64| {
+        if end - start >= min_duration.max(1) {
+            gaps.push(UntrackedGap {
+                start,
+                end,
+                duration: end - start,
+                cause: cause(start),
+            });
+        }
+    };
+
+    for (period_start, period_end) in periods {
+        let period_end = now.min(*period_end);
+        let mut covered_until = *period_start;
+        for (start, end) in tracked.iter().filter(|(start, _)| *start < period_end) {
+            if *end <= covered_until {
+                continue;
+            }
+            if *start > covered_until {
+                add_gap(covered_until, *start);
+            }
+            covered_until = *end;
+        }
+        if covered_until < period_end {
+            add_gap(covered_until, period_end);
+        }
+    }
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracker::interval::test_day;
+    use chrono::Utc;
+
+    const HOUR: i64 = 3600;
+
+    fn hours(id: i32, weekdays: i32, start: i32, end: i32) -> WorkingHours {
+        WorkingHours {
+            id,
+            weekdays,
+            start_minute: start * 60,
+            end_minute: end * 60,
+        }
+    }
+
+    fn entry(start: i64, end: Option<i64>, task_id: Option<i32>, event: &str) -> HistoryEntry {
+        HistoryEntry {
+            id: 0,
+            ts_start: start as i32,
+            ts_end: end.map(|end| end as i32),
+            system_event: task_id.map_or(Some(String::from(event)), |_| None),
+            task_name: task_id.map(|_| String::from(event)),
+            task_id,
+        }
+    }
+
+    #[test]
+    fn test_working_periods() {
+        // Monday 2024-03-04 to Sunday 2024-03-10, Wednesday is a holiday
+        let monday = interval::day_start(test_day("2024-03-04"), &Utc);
+        let hours = vec![
+            hours(1, 0b1_1111, 9, 12),
+            hours(2, 0b1_1111, 13, 18),
+            hours(3, 0b1, 11, 14),
+        ];
+        let holidays = vec![Holiday {
+            id: 1,
+            day: String::from("2024-03-06"),
+            description: String::new(),
+        }];
+
+        let periods = working_periods(
+            &hours,
+            &holidays,
+            test_day("2024-03-04"),
+            test_day("2024-03-10"),
+            &Utc,
+        );
+        let day_periods = |offset: i64| {
+            vec![
+                (
+                    monday + offset * 24 * HOUR + 9 * HOUR,
+                    monday + offset * 24 * HOUR + 12 * HOUR,
+                ),
+                (
+                    monday + offset * 24 * HOUR + 13 * HOUR,
+                    monday + offset * 24 * HOUR + 18 * HOUR,
+                ),
+            ]
+        };
+        let mut expected = vec![(monday + 9 * HOUR, monday + 18 * HOUR)];
+        expected.extend(day_periods(1));
+        expected.extend(day_periods(3));
+        expected.extend(day_periods(4));
+        assert_eq!(periods, expected);
+    }
+
+    #[test]
+    fn test_untracked_gaps() {
+        let periods = vec![(9 * HOUR, 12 * HOUR), (13 * HOUR, 18 * HOUR)];
+        let history = vec![
+            entry(8 * HOUR, Some(10 * HOUR), Some(1), "dev"),
+            entry(10 * HOUR, Some(10 * HOUR + 60), None, "Pause"),
+            entry(10 * HOUR + 60, Some(11 * HOUR), Some(1), "dev"),
+            entry(11 * HOUR, Some(14 * HOUR), None, "Shutdown"),
+            entry(14 * HOUR, Some(15 * HOUR), Some(2), "meeting"),
+            entry(15 * HOUR, None, None, "Startup"),
+        ];
+
+        let gap = |start: i64, end: i64, cause: Option<&str>| UntrackedGap {
+            start,
+            end,
+            duration: end - start,
+            cause: cause.map(String::from),
+        };
+        assert_eq!(
+            untracked_gaps(&periods, &history, 16 * HOUR, 0),
+            vec![
+                gap(10 * HOUR, 10 * HOUR + 60, Some("Pause")),
+                gap(11 * HOUR, 12 * HOUR, Some("Shutdown")),
+                gap(13 * HOUR, 14 * HOUR, Some("Shutdown")),
+                gap(15 * HOUR, 16 * HOUR, Some("Startup")),
+            ]
+        );
+        let running = vec![
+            entry(8 * HOUR, Some(13 * HOUR + 1800), None, "Startup"),
+            entry(13 * HOUR + 1800, Some(15 * HOUR), Some(1), "dev"),
+            entry(15 * HOUR, None, None, "Ping"),
+        ];
+        assert_eq!(
+            untracked_gaps(&periods, &running, 15 * HOUR + 50, 0),
+            vec![
+                gap(9 * HOUR, 12 * HOUR, Some("Startup")),
+                gap(13 * HOUR, 13 * HOUR + 1800, Some("Startup")),
+            ]
+        );
+        assert_eq!(
+            untracked_gaps(&periods, &running[..2], 15 * HOUR + 50, 0),
+            vec![
+                gap(9 * HOUR, 12 * HOUR, Some("Startup")),
+                gap(13 * HOUR, 13 * HOUR + 1800, Some("Startup")),
+                gap(15 * HOUR, 15 * HOUR + 50, None),
+            ]
+        );
+        assert_eq!(
+            untracked_gaps(&periods, &history[..1], 20 * HOUR, 300),
+            vec![
+                gap(10 * HOUR, 12 * HOUR, None),
+                gap(13 * HOUR, 18 * HOUR, None),
+            ]
+        );
+    }
+}
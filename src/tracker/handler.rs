@@ -0,0 +1,783 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use csv::Writer;
+use oclock_sqlite::connection::MemoryDB;
+use oclock_sqlite::dump::Dump;
+use serde;
+use serde_json;
+
+use crate::dto::command::{ChartKind, ClockFormat, OClockClientCommand};
+use crate::tracker::billing::{self, BillableEntry, BillableTotal, TotalPeriod};
+use crate::tracker::clock::{Clock, SystemClock};
+use crate::tracker::csv_import;
+use crate::tracker::html_report::{self, ReportHeader};
+use crate::tracker::ical;
+use crate::tracker::interval;
+use crate::tracker::jira;
+use crate::tracker::org;
+use crate::tracker::pomodoro::PomodoroSettings;
+use crate::tracker::rounding;
+use crate::tracker::state::{State, TimesheetPivotRecord};
+use crate::tracker::stats;
+use crate::tracker::svg_chart::{self, BarPeriod};
+use crate::tracker::timeclock;
+use crate::tracker::timewarrior;
+
+/// In-process time tracker operating directly on the oclock database, without the daemon
+pub struct Tracker {
+    state: State,
+}
+
+impl Tracker {
+    /// Open the database stored in the given data directory, creating it when missing
+    pub fn open(data_dir: &Path) -> Result<Tracker, String> {
+        Tracker::open_with_clock(data_dir, Box::new(SystemClock))
+    }
+
+    /// Open the database reading the current time from the given clock
+    pub fn open_with_clock(data_dir: &Path, clock: Box<dyn Clock>) -> Result<Tracker, String> {
+        fs::create_dir_all(data_dir).unwrap_or_else(|why| {
+            log::error!("Error creating data directory - {:?}", why.kind());
+        });
+
+        Ok(Tracker {
+            state: State::open_with_clock(format!("{}/oclock.db", data_dir.display()), clock)?,
+        })
+    }
+
+    /// Tracker working on a database kept in memory, nothing is persisted
+    pub fn in_memory() -> Result<Tracker, String> {
+        Ok(Tracker {
+            state: State::with_storage(Box::new(MemoryDB::new()?), Box::new(SystemClock)),
+        })
+    }
+
+    /// Execute the command returning the same reply the daemon would send
+    pub fn execute(&self, cmd: OClockClientCommand) -> Result<serde_json::Value, String> {
+        if cmd.requires_daemon() {
+            return Err(format!(
+                "Command {:?} is available only in daemon mode",
+                cmd
+            ));
+        }
+        dispatch(cmd, &self.state, &mut |_| ())
+    }
+}
+
+/// Directory holding the oclock database when no explicit path is given
+pub fn default_data_dir() -> PathBuf {
+    match env::var("HOME") {
+        Ok(path) => PathBuf::from(format!("{}/.oclock", path)),
+        Err(_) => PathBuf::from("."),
+    }
+}
+
+fn vec_to_csv<T>(items: Vec<T>) -> Result<String, Box<dyn Error>>
+where
+    T: serde::ser::Serialize,
+{
+    let mut wtr = Writer::from_writer(vec![]);
+    for item in items {
+        let out = wtr.serialize(item);
+        if let Err(err) = out {
+            log::warn!("Error serializing item - {err}");
+        }
+    }
+
+    let data = String::from_utf8(wtr.into_inner()?)?;
+    Ok(data)
+}
+
+#[test]
+fn test_time_format() {
+    assert_eq!(format_time_interval(&0), "00:00:00");
+    assert_eq!(format_time_interval(&1), "00:00:01");
+    assert_eq!(format_time_interval(&60), "00:01:00");
+    assert_eq!(format_time_interval(&3600), "01:00:00");
+
+    assert_eq!(format_time_interval(&45296), "12:34:56");
+}
+
+pub(crate) fn format_time_interval(i: &i32) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        i / 3600,
+        (i - (i / 3600) * 3600) / 60,
+        i - (i / 60) * 60
+    )
+}
+
+fn timesheet_to_csv(
+    tasks: Vec<String>,
+    records: Vec<TimesheetPivotRecord>,
+) -> Result<String, Box<dyn Error>> {
+    let mut wtr = Writer::from_writer(vec![]);
+    let out = wtr.serialize(("day", tasks));
+    if let Err(err) = out {
+        log::warn!("Error serializing tasks - {err}");
+    }
+    for item in records {
+        let entries_str: Vec<String> = item.entries.iter().map(format_time_interval).collect();
+        let out = wtr.serialize((item.day, entries_str));
+        if let Err(err) = out {
+            log::warn!("Error serializing day entries - {err}");
+        }
+    }
+
+    let data = String::from_utf8(wtr.into_inner()?)?;
+    Ok(data)
+}
+
+fn billable_timesheet_to_csv(
+    entries: Vec<BillableEntry>,
+    totals: Vec<BillableTotal>,
+) -> Result<String, Box<dyn Error>> {
+    let mut wtr = Writer::from_writer(vec![]);
+    wtr.write_record([
+        "day",
+        "task",
+        "duration",
+        "rounded_duration",
+        "hourly_rate",
+        "currency",
+        "amount",
+    ])?;
+    for entry in entries {
+        wtr.write_record([
+            entry.day.format("%Y-%m-%d").to_string(),
+            entry.task,
+            format_time_interval(&(entry.duration as i32)),
+            format_time_interval(&(entry.rounded_duration as i32)),
+            entry
+                .hourly_rate
+                .map(|rate| billing::format_amount(i64::from(rate)))
+                .unwrap_or_default(),
+            entry.currency.unwrap_or_default(),
+            entry.amount.map(billing::format_amount).unwrap_or_default(),
+        ])?;
+    }
+    for total in totals {
+        let amount = total
+            .currency
+            .as_ref()
+            .map(|_| billing::format_amount(total.amount))
+            .unwrap_or_default();
+        let label = match total.period {
+            TotalPeriod::Week(monday) => format!("WEEK {}", monday.format("%Y-%m-%d")),
+            TotalPeriod::Month(first) => format!("MONTH {}", first.format("%Y-%m")),
+            TotalPeriod::All => String::from("TOTAL"),
+        };
+        wtr.write_record([
+            label,
+            String::new(),
+            format_time_interval(&(total.duration as i32)),
+            format_time_interval(&(total.rounded_duration as i32)),
+            String::new(),
+            total.currency.unwrap_or_default(),
+            amount,
+        ])?;
+    }
+
+    let data = String::from_utf8(wtr.into_inner()?)?;
+    Ok(data)
+}
+
+pub fn compute_state(state: &State) -> Result<serde_json::Value, String> {
+    let exp_state = state.get_state()?;
+    match serde_json::to_value(&exp_state) {
+        Ok(json) => Ok(json),
+        Err(e) => Err(format!("Error serializing state {}", e)),
+    }
+}
+
+fn list_scheduled_switches(state: &State) -> Result<serde_json::Value, String> {
+    let switches = state.list_scheduled_switches()?;
+    match serde_json::to_value(&switches) {
+        Ok(json) => Ok(json),
+        Err(e) => Err(format!("Error serializing scheduled switches {}", e)),
+    }
+}
+
+fn list_task_rates(state: &State) -> Result<serde_json::Value, String> {
+    let rates = state.list_task_rates()?;
+    match serde_json::to_value(&rates) {
+        Ok(json) => Ok(json),
+        Err(e) => Err(format!("Error serializing task rates {}", e)),
+    }
+}
+
+fn list_work_targets(state: &State) -> Result<serde_json::Value, String> {
+    let targets = state.list_work_targets()?;
+    match serde_json::to_value(&targets) {
+        Ok(json) => Ok(json),
+        Err(e) => Err(format!("Error serializing work targets {}", e)),
+    }
+}
+
+fn list_working_hours(state: &State) -> Result<serde_json::Value, String> {
+    let hours = state.list_working_hours()?;
+    match serde_json::to_value(&hours) {
+        Ok(json) => Ok(json),
+        Err(e) => Err(format!("Error serializing working hours {}", e)),
+    }
+}
+
+fn list_context_rules(state: &State) -> Result<serde_json::Value, String> {
+    let rules = state.list_context_rules()?;
+    match serde_json::to_value(&rules) {
+        Ok(json) => Ok(json),
+        Err(e) => Err(format!("Error serializing context rules {}", e)),
+    }
+}
+
+/// Execute the command against the state, invoking `publish` with the new state when it changes
+pub fn dispatch(
+    msg: OClockClientCommand,
+    state: &State,
+    publish: &mut impl FnMut(&serde_json::Value),
+) -> Result<serde_json::Value, String> {
+    match msg {
+        OClockClientCommand::Exit => Ok(serde_json::Value::String(String::from("bye bye..."))),
+        OClockClientCommand::CurrentTask => {
+            let task = state.get_current_task()?;
+            match task {
+                Some(t) => Ok(serde_json::Value::String(t.name)),
+                None => Ok(serde_json::Value::String(String::from("None"))),
+            }
+        }
+        OClockClientCommand::ListTasks => {
+            let tasks = state.list_tasks()?;
+            match vec_to_csv(tasks) {
+                Ok(csv) => Ok(serde_json::Value::String(csv)),
+                Err(e) => Err(format!("Error generating csv '{}'", e)),
+            }
+        }
+        OClockClientCommand::Timesheet { rounding } => {
+            let (tasks, timesheet) = state.full_timesheet(rounding.as_ref())?;
+
+            match timesheet_to_csv(tasks, timesheet) {
+                Ok(csv) => Ok(serde_json::Value::String(csv)),
+                Err(e) => Err(format!("Error generating csv '{}'", e)),
+            }
+        }
+        OClockClientCommand::PushTask { name } => {
+            let result = state.new_task(name);
+            if let Ok(state) = compute_state(state) {
+                publish(&state);
+            }
+            result
+        }
+        OClockClientCommand::DisableTask { task_id } => {
+            let result = state.change_task_enabled_flag(task_id, false);
+            if let Ok(state) = compute_state(state) {
+                publish(&state);
+            }
+            result
+        }
+        OClockClientCommand::SwitchTask { task_id, note } => {
+            let result = state.switch_task_with_note(task_id, note);
+            if let Ok(state) = compute_state(state) {
+                publish(&state);
+            }
+            result
+        }
+        OClockClientCommand::JsonPushTask { name } => {
+            state.new_task(name)?;
+            let state = compute_state(state);
+            if let Ok(state) = &state {
+                publish(state);
+            }
+            state
+        }
+        OClockClientCommand::JsonDisableTask { task_id } => {
+            let out = state.change_task_enabled_flag(task_id, false);
+            if let Err(err) = out {
+                log::warn!("Error disabling task {task_id} - {err}");
+            }
+            let state = compute_state(state);
+            if let Ok(state) = &state {
+                publish(state);
+            }
+            state
+        }
+        OClockClientCommand::JsonSwitchTask { task_id, note } => {
+            state.switch_task_with_note(task_id, note)?;
+            let state = compute_state(state);
+            if let Ok(state) = &state {
+                publish(state);
+            }
+            state
+        }
+        OClockClientCommand::JsonRetroSwitchTask {
+            task_id,
+            timestamp,
+            keep_previous_task,
+        } => {
+            state.retro_switch_task(task_id as i32, timestamp as i32, keep_previous_task)?;
+            let state = compute_state(state);
+            if let Ok(state) = &state {
+                publish(state);
+            }
+            state
+        }
+        OClockClientCommand::JsonState => compute_state(state),
+        OClockClientCommand::StartPomodoro {
+            task_id,
+            work_minutes,
+            break_minutes,
+            cycles,
+            break_task_id,
+        } => {
+            state.start_pomodoro(PomodoroSettings {
+                task_id,
+                work_minutes,
+                break_minutes,
+                cycles,
+                break_task_id,
+            })?;
+            let state = compute_state(state);
+            if let Ok(state) = &state {
+                publish(state);
+            }
+            state
+        }
+        OClockClientCommand::StopPomodoro => {
+            state.stop_pomodoro()?;
+            let state = compute_state(state);
+            if let Ok(state) = &state {
+                publish(state);
+            }
+            state
+        }
+        OClockClientCommand::SetTaskBudget {
+            task_id,
+            period,
+            amount_minutes,
+            warning_percent,
+        } => {
+            state.set_task_budget(task_id, period.into(), amount_minutes * 60, warning_percent)?;
+            let state = compute_state(state);
+            if let Ok(state) = &state {
+                publish(state);
+            }
+            state
+        }
+        OClockClientCommand::RemoveTaskBudget { task_id, period } => {
+            state.remove_task_budget(task_id, period.into())?;
+            let state = compute_state(state);
+            if let Ok(state) = &state {
+                publish(state);
+            }
+            state
+        }
+        OClockClientCommand::SetTaskRate {
+            task_id,
+            hourly_rate,
+            currency,
+            effective_from,
+        } => {
+            state.set_task_rate(task_id, &hourly_rate, &currency, effective_from.as_deref())?;
+            list_task_rates(state)
+        }
+        OClockClientCommand::RemoveTaskRate {
+            task_id,
+            effective_from,
+        } => {
+            state.remove_task_rate(task_id, effective_from.as_deref())?;
+            list_task_rates(state)
+        }
+        OClockClientCommand::ListTaskRates => list_task_rates(state),
+        OClockClientCommand::BillableTimesheet { from, to, rounding } => {
+            let (entries, totals) = state.billable_timesheet(
+                interval::parse_day(&from)?,
+                interval::parse_day(&to)?,
+                rounding.as_ref(),
+            )?;
+
+            match billable_timesheet_to_csv(entries, totals) {
+                Ok(csv) => Ok(serde_json::Value::String(csv)),
+                Err(e) => Err(format!("Error generating csv '{}'", e)),
+            }
+        }
+        OClockClientCommand::HtmlReport {
+            path,
+            from,
+            to,
+            title,
+            client,
+            template,
+            rounding,
+        } => {
+            let template = match template {
+                Some(template) => Some(
+                    fs::read_to_string(&template)
+                        .map_err(|e| format!("Error reading {}: '{}'", template, e))?,
+                ),
+                None => None,
+            };
+            let header = ReportHeader {
+                title: title.unwrap_or(String::from("Timesheet")),
+                client,
+                from: interval::parse_day(&from)?,
+                to: interval::parse_day(&to)?,
+            };
+            let (entries, totals) =
+                state.billable_timesheet(header.from, header.to, rounding.as_ref())?;
+            let html = html_report::render(template.as_deref(), &header, &entries, &totals);
+            fs::write(&path, html).map_err(|e| format!("Error writing {}: '{}'", path, e))?;
+            Ok(serde_json::Value::String(format!(
+                "Report written to {}",
+                path
+            )))
+        }
+        OClockClientCommand::ExportChart {
+            path,
+            chart,
+            from,
+            to,
+        } => {
+            let (from, to) = (interval::parse_day(&from)?, interval::parse_day(&to)?);
+            let parts = interval::split_by_day(&state.tracked_intervals(from, to)?, &Local);
+            let svg = match chart {
+                ChartKind::DailyBars => svg_chart::stacked_bars(&parts, BarPeriod::Day, from, to),
+                ChartKind::WeeklyBars => svg_chart::stacked_bars(&parts, BarPeriod::Week, from, to),
+                ChartKind::Donut => svg_chart::donut(&parts),
+                ChartKind::Timeline => svg_chart::timeline(&parts, from, to, &Local),
+            };
+            fs::write(&path, svg).map_err(|e| format!("Error writing {}: '{}'", path, e))?;
+            Ok(serde_json::Value::String(format!(
+                "Chart written to {}",
+                path
+            )))
+        }
+        OClockClientCommand::ExportJiraWorklogs {
+            path,
+            from,
+            to,
+            issue_pattern,
+            rounding,
+        } => {
+            rounding::validate(rounding.as_ref())?;
+            let pattern = jira::issue_pattern(issue_pattern.as_deref())?;
+            let (from, to) = (interval::parse_day(&from)?, interval::parse_day(&to)?);
+            let intervals = state.noted_intervals(from, to)?;
+            let export = jira::worklogs(&intervals, &pattern, rounding.as_ref(), &Local);
+
+            let json = serde_json::to_string_pretty(&export.worklogs)
+                .map_err(|e| format!("Error serializing worklogs {}", e))?;
+            fs::write(&path, json).map_err(|e| format!("Error writing {}: '{}'", path, e))?;
+            let mut message = format!("{} worklogs written to {}", export.worklogs.len(), path);
+            if !export.skipped_tasks.is_empty() {
+                message.push_str(&format!(
+                    ", tasks without issue key: {}",
+                    export.skipped_tasks.join(", ")
+                ));
+            }
+            if export.short_worklogs > 0 {
+                message.push_str(&format!(
+                    ", {} worklogs shorter than a minute left out",
+                    export.short_worklogs
+                ));
+            }
+            Ok(serde_json::Value::String(message))
+        }
+        OClockClientCommand::Stats { from, to } => {
+            let (from, to) = (interval::parse_day(&from)?, interval::parse_day(&to)?);
+            let stats = stats::compute(&state.tracked_intervals(from, to)?, from, to, &Local);
+            match serde_json::to_value(&stats) {
+                Ok(json) => Ok(json),
+                Err(e) => Err(format!("Error serializing stats {}", e)),
+            }
+        }
+        OClockClientCommand::SetWorkTarget {
+            daily_minutes,
+            weekdays,
+            effective_from,
+        } => {
+            state.set_work_target(daily_minutes, &weekdays, &effective_from)?;
+            let state = compute_state(state);
+            if let Ok(state) = &state {
+                publish(state);
+            }
+            state
+        }
+        OClockClientCommand::RemoveWorkTarget { effective_from } => {
+            state.remove_work_target(&effective_from)?;
+            let state = compute_state(state);
+            if let Ok(state) = &state {
+                publish(state);
+            }
+            state
+        }
+        OClockClientCommand::AddHoliday { day, description } => {
+            state.add_holiday(&day, description.as_deref())?;
+            let state = compute_state(state);
+            if let Ok(state) = &state {
+                publish(state);
+            }
+            state
+        }
+        OClockClientCommand::RemoveHoliday { day } => {
+            state.remove_holiday(&day)?;
+            let state = compute_state(state);
+            if let Ok(state) = &state {
+                publish(state);
+            }
+            state
+        }
+        OClockClientCommand::ListWorkTargets => list_work_targets(state),
+        OClockClientCommand::OvertimeReport { from, to } => {
+            let report = state.overtime_report(
+                from.as_deref().map(interval::parse_day).transpose()?,
+                to.as_deref().map(interval::parse_day).transpose()?,
+            )?;
+            match serde_json::to_value(&report) {
+                Ok(json) => Ok(json),
+                Err(e) => Err(format!("Error serializing overtime report {}", e)),
+            }
+        }
+        OClockClientCommand::AddWorkingHours {
+            weekdays,
+            start,
+            end,
+        } => {
+            state.add_working_hours(&weekdays, &start, &end)?;
+            list_working_hours(state)
+        }
+        OClockClientCommand::RemoveWorkingHours { hours_id } => {
+            state.remove_working_hours(hours_id)?;
+            list_working_hours(state)
+        }
+        OClockClientCommand::ListWorkingHours => list_working_hours(state),
+        OClockClientCommand::UntrackedGaps {
+            from,
+            to,
+            min_minutes,
+        } => {
+            let gaps = state.untracked_gaps(
+                interval::parse_day(&from)?,
+                interval::parse_day(&to)?,
+                min_minutes.unwrap_or(0),
+            )?;
+            match serde_json::to_value(&gaps) {
+                Ok(json) => Ok(json),
+                Err(e) => Err(format!("Error serializing untracked gaps {}", e)),
+            }
+        }
+        OClockClientCommand::AddScheduledSwitch {
+            task_id,
+            at,
+            weekdays,
+            duration_minutes,
+        } => {
+            state.add_scheduled_switch(
+                task_id,
+                &at,
+                &weekdays,
+                duration_minutes.map(|m| m * 60),
+            )?;
+            list_scheduled_switches(state)
+        }
+        OClockClientCommand::RemoveScheduledSwitch { switch_id } => {
+            state.remove_scheduled_switch(switch_id)?;
+            list_scheduled_switches(state)
+        }
+        OClockClientCommand::ListScheduledSwitches => list_scheduled_switches(state),
+        OClockClientCommand::Context { kind, value } => {
+            let switched = state.context_event(kind.into(), &value)?;
+            let state = compute_state(state);
+            if let (true, Ok(state)) = (switched, &state) {
+                publish(state);
+            }
+            state
+        }
+        OClockClientCommand::AddContextRule {
+            kind,
+            pattern,
+            task_id,
+        } => {
+            state.add_context_rule(kind.into(), pattern, task_id)?;
+            list_context_rules(state)
+        }
+        OClockClientCommand::RemoveContextRule { rule_id } => {
+            state.remove_context_rule(rule_id)?;
+            list_context_rules(state)
+        }
+        OClockClientCommand::ListContextRules => list_context_rules(state),
+        OClockClientCommand::ListContextSwitches => {
+            let switches = state.list_context_switches()?;
+            match serde_json::to_value(&switches) {
+                Ok(json) => Ok(json),
+                Err(e) => Err(format!("Error serializing context switches {}", e)),
+            }
+        }
+        OClockClientCommand::Backup { path } => {
+            state.backup(Path::new(&path))?;
+            Ok(serde_json::Value::String(format!("Backup written to {}", path)))
+        }
+        OClockClientCommand::Restore { path } => {
+            state.restore(Path::new(&path))?;
+            let state = compute_state(state);
+            if let Ok(state) = &state {
+                publish(state);
+            }
+            state
+        }
+        OClockClientCommand::Export { path } => {
+            let data = state.export_data()?;
+            let json = serde_json::to_string_pretty(&data)
+                .map_err(|e| format!("Error serializing export {}", e))?;
+            fs::write(&path, json).map_err(|e| format!("Error writing {}: '{}'", path, e))?;
+            Ok(serde_json::Value::String(format!(
+                "Exported {} tasks and {} events to {}",
+                data.tasks.len(),
+                data.events.len(),
+                path
+            )))
+        }
+        OClockClientCommand::Import { path, mode } => {
+            let json =
+                fs::read_to_string(&path).map_err(|e| format!("Error reading {}: '{}'", path, e))?;
+            let data: Dump = serde_json::from_str(&json)
+                .map_err(|e| format!("Invalid export file {}: '{}'", path, e))?;
+            let summary = state.import_data(&data, mode)?;
+            if let Ok(state) = compute_state(state) {
+                publish(&state);
+            }
+            match serde_json::to_value(&summary) {
+                Ok(json) => Ok(json),
+                Err(e) => Err(format!("Error serializing import summary {}", e)),
+            }
+        }
+        OClockClientCommand::Fsck { fix } => {
+            let report = state.fsck(fix)?;
+            if fix {
+                if let Ok(state) = compute_state(state) {
+                    publish(&state);
+                }
+            }
+            match serde_json::to_value(&report) {
+                Ok(json) => Ok(json),
+                Err(e) => Err(format!("Error serializing fsck report {}", e)),
+            }
+        }
+        OClockClientCommand::ImportTimewarrior { path } => {
+            let mut intervals = Vec::new();
+            for timew_interval in timewarrior::read_intervals(Path::new(&path))? {
+                intervals.extend(timew_interval.to_interval()?);
+            }
+            let summary = state.import_intervals(intervals, false)?;
+            if let Ok(state) = compute_state(state) {
+                publish(&state);
+            }
+            match serde_json::to_value(&summary) {
+                Ok(json) => Ok(json),
+                Err(e) => Err(format!("Error serializing import summary {}", e)),
+            }
+        }
+        OClockClientCommand::ExportTimewarrior { path } => {
+            let intervals = timewarrior::export_intervals(&state.history()?);
+            let json = serde_json::to_string_pretty(&intervals)
+                .map_err(|e| format!("Error serializing export {}", e))?;
+            fs::write(&path, json).map_err(|e| format!("Error writing {}: '{}'", path, e))?;
+            Ok(serde_json::Value::String(format!(
+                "Exported {} intervals to {}",
+                intervals.len(),
+                path
+            )))
+        }
+        OClockClientCommand::ImportCsv {
+            path,
+            dry_run,
+            date_format,
+        } => {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| format!("Error reading {}: '{}'", path, e))?;
+            let intervals = csv_import::read_intervals(&content, date_format.as_deref(), &Local)
+                .map_err(|e| format!("Invalid csv report {}: {}", path, e))?;
+            let summary = state.import_intervals(intervals, dry_run)?;
+            if !dry_run {
+                if let Ok(state) = compute_state(state) {
+                    publish(&state);
+                }
+            }
+            match serde_json::to_value(&summary) {
+                Ok(json) => Ok(json),
+                Err(e) => Err(format!("Error serializing import summary {}", e)),
+            }
+        }
+        OClockClientCommand::ExportCalendar {
+            path,
+            from,
+            to,
+            merge_daily,
+        } => {
+            let intervals =
+                state.tracked_intervals(interval::parse_day(&from)?, interval::parse_day(&to)?)?;
+            let events = if merge_daily {
+                ical::daily_events(&intervals, &Local)
+            } else {
+                ical::interval_events(&intervals)
+            };
+            let calendar = ical::calendar(&events, state.clock().unix_now());
+            fs::write(&path, calendar).map_err(|e| format!("Error writing {}: '{}'", path, e))?;
+            Ok(serde_json::Value::String(format!(
+                "Exported {} events to {}",
+                events.len(),
+                path
+            )))
+        }
+        OClockClientCommand::ImportCalendar {
+            path,
+            rules,
+            split_tracked,
+        } => {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| format!("Error reading {}: '{}'", path, e))?;
+            let events = ical::parse_events(&content, &Local)
+                .map_err(|e| format!("Invalid calendar {}: {}", path, e))?;
+            let summary = state.import_calendar(&events, &rules, split_tracked)?;
+            if let Ok(state) = compute_state(state) {
+                publish(&state);
+            }
+            match serde_json::to_value(&summary) {
+                Ok(json) => Ok(json),
+                Err(e) => Err(format!("Error serializing import summary {}", e)),
+            }
+        }
+        OClockClientCommand::ExportClock { path, format } => {
+            let intervals = interval::tracked(&state.history()?);
+            let content = match format {
+                ClockFormat::Org => org::write_intervals(&intervals, &Local),
+                ClockFormat::Timeclock => timeclock::write_intervals(&intervals, &Local),
+            };
+            fs::write(&path, content).map_err(|e| format!("Error writing {}: '{}'", path, e))?;
+            Ok(serde_json::Value::String(format!(
+                "Exported {} intervals to {}",
+                intervals.len(),
+                path
+            )))
+        }
+        OClockClientCommand::ImportClock { path, format } => {
+            let content = fs::read_to_string(&path)
+                .map_err(|e| format!("Error reading {}: '{}'", path, e))?;
+            let intervals = match format {
+                ClockFormat::Org => org::read_intervals(&content, &Local),
+                ClockFormat::Timeclock => timeclock::read_intervals(&content, &Local),
+            }
+            .map_err(|e| format!("Invalid clock file {}: {}", path, e))?;
+            let summary = state.import_intervals(intervals, false)?;
+            if let Ok(state) = compute_state(state) {
+                publish(&state);
+            }
+            match serde_json::to_value(&summary) {
+                Ok(json) => Ok(json),
+                Err(e) => Err(format!("Error serializing import summary {}", e)),
+            }
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use regex::{Captures, Regex};
+
+use crate::tracker::billing::{self, BillableEntry, BillableTotal, TotalPeriod};
+use crate::tracker::handler::format_time_interval;
+
+/// Template used when none is given, the placeholders are replaced with the report content
+///
+/// Available placeholders are `{{title}}`, `{{client}}`, `{{from}}`, `{{to}}`, `{{tasks}}`, `{{totals}}`
+/// and `{{daily}}`.
+pub const DEFAULT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{{title}}</title>
+<style>
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.6em; margin-bottom: 0.2em; }
+h2 { font-size: 1.2em; margin-top: 2em; }
+.client { margin: 1em 0; white-space: pre-line; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border-bottom: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }
+td.number, th.number { text-align: right; }
+tr.total td { font-weight: bold; border-top: 2px solid #222; }
+tr.subtotal td { color: #555; border-top: 1px solid #ccc; }
+@media print { body { margin: 0; } }
+</style>
+</head>
+<body>
+<h1>{{title}}</h1>
+<div class="period">{{from}} &ndash; {{to}}</div>
+<div class="client">{{client}}</div>
+<h2>Tasks</h2>
+{{tasks}}
+{{totals}}
+<h2>Daily breakdown</h2>
+{{daily}}
+</body>
+</html>
+"#;
+
+/// Content of the report header
+pub struct ReportHeader {
+    pub title: String,
+    pub client: Option<String>,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn duration_cell(seconds: i64) -> String {
+    format!(
+        "<td class=\"number\">{}</td>",
+        format_time_interval(&(seconds as i32))
+    )
+}
+
+fn amount_cell(amount: Option<i64>, currency: Option<&str>) -> String {
+    match (amount, currency) {
+        (Some(amount), Some(currency)) => format!(
+            "<td class=\"number\">{} {}</td>",
+            billing::format_amount(amount),
+            escape(currency)
+        ),
+        _ => String::from("<td class=\"number\"></td>"),
+    }
+}
+
+#[derive(Default)]
+struct TaskRow {
+    duration: i64,
+    rounded_duration: i64,
+    amount: Option<i64>,
+}
+
+/// Time and amount of each task over the whole range, a row per rate when it changes in the range
+fn tasks_table(entries: &[BillableEntry]) -> String {
+    let mut rows: BTreeMap<(&str, Option<i32>, Option<&str>), TaskRow> = BTreeMap::new();
+    for entry in entries {
+        let row = rows
+            .entry((
+                entry.task.as_str(),
+                entry.hourly_rate,
+                entry.currency.as_deref(),
+            ))
+            .or_default();
+        row.duration += entry.duration;
+        row.rounded_duration += entry.rounded_duration;
+        row.amount = entry.amount.map(|amount| row.amount.unwrap_or(0) + amount);
+    }
+
+    let mut table = String::from(
+        "<table class=\"tasks\">\n<tr><th>Task</th><th class=\"number\">Tracked</th>\
+         <th class=\"number\">Billed</th><th class=\"number\">Rate</th>\
+         <th class=\"number\">Amount</th></tr>\n",
+    );
+    for ((task, rate, currency), row) in rows {
+        table.push_str(&format!(
+            "<tr><td>{}</td>{}{}{}{}</tr>\n",
+            escape(task),
+            duration_cell(row.duration),
+            duration_cell(row.rounded_duration),
+            amount_cell(rate.map(i64::from), currency),
+            amount_cell(row.amount, currency)
+        ));
+    }
+    table.push_str("</table>\n");
+    table
+}
+
+fn totals_table(totals: &[BillableTotal]) -> String {
+    let mut table = String::from("<table class=\"totals\">\n");
+    for total in totals {
+        let (class, label) = match total.period {
+            TotalPeriod::Week(monday) => {
+                ("subtotal", format!("Week {}", monday.format("%Y-%m-%d")))
+            }
+            TotalPeriod::Month(first) => ("subtotal", format!("Month {}", first.format("%Y-%m"))),
+            TotalPeriod::All => ("total", String::from("Total")),
+        };
+        table.push_str(&format!(
+            "<tr class=\"{}\"><td>{}{}</td>{}{}{}{}</tr>\n",
+            class,
+            label,
+            total
+                .currency
+                .as_deref()
+                .map_or(String::from(" not billable"), |c| format!(" {}", escape(c))),
+            duration_cell(total.duration),
+            duration_cell(total.rounded_duration),
+            amount_cell(None, None),
+            amount_cell(Some(total.amount), total.currency.as_deref())
+        ));
+    }
+    table.push_str("</table>\n");
+    table
+}
+
+fn daily_table(entries: &[BillableEntry]) -> String {
+    let mut table = String::from(
+        "<table class=\"daily\">\n<tr><th>Day</th><th>Task</th><th class=\"number\">Tracked</th>\
+         <th class=\"number\">Billed</th><th class=\"number\">Amount</th></tr>\n",
+    );
+    for entry in entries {
+        table.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td>{}{}{}</tr>\n",
+            entry.day.format("%Y-%m-%d"),
+            escape(&entry.task),
+            duration_cell(entry.duration),
+            duration_cell(entry.rounded_duration),
+            amount_cell(entry.amount, entry.currency.as_deref())
+        ));
+    }
+    table.push_str("</table>\n");
+    table
+}
+
+/// Self-contained HTML document of the billable timesheet, using the default template when none is given
+///
+/// The placeholders are substituted in a single pass, the ones appearing in the substituted values are kept as
+/// they are, as well as the unknown ones.
+pub fn render(
+    template: Option<&str>,
+    header: &ReportHeader,
+    entries: &[BillableEntry],
+    totals: &[BillableTotal],
+) -> String {
+    let placeholder = Regex::new(r"\{\{(\w+)\}\}").expect("Invalid placeholder pattern");
+    placeholder
+        .replace_all(
+            template.unwrap_or(DEFAULT_TEMPLATE),
+            |captures: &Captures| match &captures[1] {
+                "title" => escape(&header.title),
+                "client" => escape(header.client.as_deref().unwrap_or_default()),
+                "from" => header.from.format("%Y-%m-%d").to_string(),
+                "to" => header.to.format("%Y-%m-%d").to_string(),
+                "tasks" => tasks_table(entries),
+                "totals" => totals_table(totals),
+                "daily" => daily_table(entries),
+                _ => captures[0].to_string(),
+            },
+        )
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracker::interval::test_day;
+
+    fn entry(day_str: &str, task: &str, duration: i64, amount: Option<i64>) -> BillableEntry {
+        BillableEntry {
+            day: test_day(day_str),
+            task: String::from(task),
+            duration,
+            rounded_duration: duration,
+            hourly_rate: amount.map(|_| 6000),
+            currency: amount.map(|_| String::from("EUR")),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let entries = vec![
+            entry("2024-03-05", "dev <backend>", 5400, Some(9000)),
+            entry("2024-03-05", "meeting", 1800, None),
+            entry("2024-03-06", "dev <backend>", 3600, Some(6000)),
+        ];
+        let totals = billing::billable_totals(&entries);
+        let header = ReportHeader {
+            title: String::from("Invoice"),
+            client: Some(String::from("ACME & Sons\nMain street 1")),
+            from: test_day("2024-03-01"),
+            to: test_day("2024-03-31"),
+        };
+
+        let html = render(None, &header, &entries, &totals);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<div class=\"client\">ACME &amp; Sons\nMain street 1</div>"));
+        assert!(html.contains(
+            "<tr><td>dev &lt;backend&gt;</td><td class=\"number\">02:30:00</td>\
+             <td class=\"number\">02:30:00</td><td class=\"number\">60.00 EUR</td>\
+             <td class=\"number\">150.00 EUR</td></tr>"
+        ));
+        assert!(html.contains(
+            "<tr class=\"total\"><td>Total not billable</td><td class=\"number\">00:30:00</td>"
+        ));
+        assert!(html.contains(
+            "<tr><td>2024-03-06</td><td>dev &lt;backend&gt;</td><td class=\"number\">01:00:00</td>"
+        ));
+        assert!(!html.contains("{{"));
+
+        let html = render(
+            Some("<h1>{{title}} {{from}}/{{to}}</h1>{{totals}}"),
+            &header,
+            &entries,
+            &totals,
+        );
+        assert!(html.starts_with("<h1>Invoice 2024-03-01/2024-03-31</h1><table class=\"totals\">"));
+        assert!(html.contains("<td>Total EUR</td>"));
+        assert!(html.contains("<tr class=\"subtotal\"><td>Week 2024-03-04 EUR</td>"));
+        assert!(html.contains("<tr class=\"subtotal\"><td>Month 2024-03 EUR</td>"));
+
+        // Placeholders in the user content are not expanded
+        let header = ReportHeader {
+            title: String::from("{{client}} {{daily}}"),
+            client: Some(String::from("{{title}}")),
+            ..header
+        };
+        let html = render(
+            Some("<h1>{{title}}</h1><p>{{client}}</p>{{unknown}}"),
+            &header,
+            &entries,
+            &totals,
+        );
+        assert_eq!(
+            html,
+            "<h1>{{client}} {{daily}}</h1><p>{{title}}</p>{{unknown}}"
+        );
+    }
+}
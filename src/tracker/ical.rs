@@ -0,0 +1,1036 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, NaiveDateTime, TimeZone};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::dto::command::{CalendarField, CalendarRule, Weekday};
+use crate::tracker::handler::format_time_interval;
+use crate::tracker::interval::{self, Interval, IntervalImportSummary};
+
+const PRODID: &str = "-//oclock//oclock//EN";
+const UID_DOMAIN: &str = "oclock";
+/// Longest content line allowed by RFC 5545, in octets, excluding the line break
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Event of the exported calendar
+#[derive(Debug, PartialEq)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub start: i64,
+    pub end: i64,
+}
+
+/// One event per tracked interval, identified by its start so that exporting again updates it
+pub fn interval_events(intervals: &[Interval]) -> Vec<CalendarEvent> {
+    intervals
+        .iter()
+        .filter_map(|i| {
+            i.end.map(|end| CalendarEvent {
+                uid: format!("{}@{}", i.start, UID_DOMAIN),
+                summary: i.task.clone(),
+                description: None,
+                start: i.start,
+                end,
+            })
+        })
+        .collect()
+}
+
+/// One event per task per day for each block of consecutive intervals, describing the tracked time
+pub fn daily_events<Tz: TimeZone>(intervals: &[Interval], tz: &Tz) -> Vec<CalendarEvent> {
+    let day_parts = interval::split_by_day(intervals, tz);
+    let mut parts: Vec<(NaiveDate, &str, i64, i64)> = day_parts
+        .iter()
+        .filter_map(|(day, part)| {
+            part.end
+                .map(|end| (*day, part.task.as_str(), part.start, end))
+        })
+        .collect();
+    parts.sort_by_key(|(_, _, start, _)| *start);
+
+    let mut events: Vec<CalendarEvent> = Vec::new();
+    let mut last_blocks: HashMap<(NaiveDate, &str), usize> = HashMap::new();
+    for (day, task, start, end) in parts {
+        if let Some(event) = last_blocks
+            .get(&(day, task))
+            .map(|idx| &mut events[*idx])
+            .filter(|event| event.end == start)
+        {
+            event.end = end;
+            continue;
+        }
+        last_blocks.insert((day, task), events.len());
+        events.push(CalendarEvent {
+            uid: format!("{}-{}@{}", start, task, UID_DOMAIN),
+            summary: String::from(task),
+            description: None,
+            start,
+            end,
+        });
+    }
+
+    for event in &mut events {
+        event.description = Some(format!(
+            "Tracked {}",
+            format_time_interval(&((event.end - event.start) as i32))
+        ));
+    }
+    events.sort_by(|a, b| (a.start, &a.summary).cmp(&(b.start, &b.summary)));
+    events
+}
+
+fn format_utc(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Split the line in chunks of at most 75 octets, continuation lines start with a space
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_octets = 0;
+    for c in line.chars() {
+        if line_octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            line_octets = 1;
+        }
+        folded.push(c);
+        line_octets += c.len_utf8();
+    }
+    folded
+}
+
+/// RFC 5545 calendar holding the given events, `dtstamp` is the creation time of the calendar
+pub fn calendar(events: &[CalendarEvent], dtstamp: i64) -> String {
+    let mut lines = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        format!("PRODID:{}", PRODID),
+        String::from("CALSCALE:GREGORIAN"),
+    ];
+    for event in events {
+        lines.push(String::from("BEGIN:VEVENT"));
+        lines.push(format!("UID:{}", escape_text(&event.uid)));
+        lines.push(format!("DTSTAMP:{}", format_utc(dtstamp)));
+        lines.push(format!("DTSTART:{}", format_utc(event.start)));
+        lines.push(format!("DTEND:{}", format_utc(event.end)));
+        lines.push(format!("SUMMARY:{}", escape_text(&event.summary)));
+        if let Some(description) = &event.description {
+            lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        lines.push(String::from("END:VEVENT"));
+    }
+    lines.push(String::from("END:VCALENDAR"));
+
+    lines.iter().map(|line| fold_line(line) + "\r\n").collect()
+}
+
+/// Zone of the event times, recurrences repeat at the same wall clock time in it
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum EventZone {
+    Utc,
+    /// Floating times, read in the local time zone
+    Floating,
+    /// Time zone of the `TZID` parameter
+    Named(chrono_tz::Tz),
+}
+
+impl EventZone {
+    fn timestamp<Tz: TimeZone>(&self, time: &NaiveDateTime, tz: &Tz) -> Option<i64> {
+        match self {
+            EventZone::Utc => Some(time.and_utc().timestamp()),
+            EventZone::Floating => tz
+                .from_local_datetime(time)
+                .earliest()
+                .map(|dt| dt.timestamp()),
+            EventZone::Named(zone) => zone
+                .from_local_datetime(time)
+                .earliest()
+                .map(|dt| dt.timestamp()),
+        }
+    }
+
+    fn local_time<Tz: TimeZone>(&self, timestamp: i64, tz: &Tz) -> Option<NaiveDateTime> {
+        match self {
+            EventZone::Utc => DateTime::from_timestamp(timestamp, 0).map(|dt| dt.naive_utc()),
+            EventZone::Floating => tz
+                .timestamp_opt(timestamp, 0)
+                .earliest()
+                .map(|dt| dt.naive_local()),
+            EventZone::Named(zone) => zone
+                .timestamp_opt(timestamp, 0)
+                .earliest()
+                .map(|dt| dt.naive_local()),
+        }
+    }
+}
+
+/// Event read from an iCalendar file, `rrule` holds its recurrence rule when repeating and
+/// `exdates` the start of the occurrences excluded from it
+#[derive(Debug, PartialEq)]
+pub struct IcsEvent {
+    pub summary: String,
+    pub categories: Vec<String>,
+    pub start: i64,
+    pub end: i64,
+    pub zone: EventZone,
+    pub rrule: Option<String>,
+    pub exdates: Vec<i64>,
+}
+
+/// Join the folded lines, continuation lines start with a space or a tab
+fn unfold_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+type Property = (String, Vec<(String, String)>, String);
+
+/// Split a content line in name, parameters and value, parameters may quote colons
+///
+/// Names are upper cased, parameter values are kept as they are since time zone ids are case
+/// sensitive.
+fn split_property(line: &str) -> Option<Property> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(idx, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(idx),
+        _ => None,
+    })?;
+
+    let mut parts = line[..colon].split(';');
+    let name = parts.next()?.to_ascii_uppercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(key, value)| {
+            (
+                key.to_ascii_uppercase(),
+                value.trim_matches('"').to_string(),
+            )
+        })
+        .collect();
+    Some((name, params, line[colon + 1..].to_string()))
+}
+
+fn param<'a>(params: &'a [(String, String)], wanted: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|(key, _)| key == wanted)
+        .map(|(_, value)| value.as_str())
+}
+
+fn is_date(params: &[(String, String)]) -> bool {
+    param(params, "VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE"))
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => (),
+        }
+    }
+    unescaped
+}
+
+/// Zone of a date-time value, times with a `TZID` are read in that IANA time zone
+fn event_zone(params: &[(String, String)], value: &str) -> Result<EventZone, String> {
+    if value.ends_with('Z') {
+        return Ok(EventZone::Utc);
+    }
+    match param(params, "TZID") {
+        Some(tzid) => tzid
+            .parse::<chrono_tz::Tz>()
+            .map(EventZone::Named)
+            .map_err(|_| format!("Unknown time zone '{}'", tzid)),
+        None => Ok(EventZone::Floating),
+    }
+}
+
+/// Timestamp of a date-time value, along with the zone it is expressed in
+///
+/// All-day dates have no time and are not supported.
+fn parse_date_time<Tz: TimeZone>(
+    params: &[(String, String)],
+    value: &str,
+    tz: &Tz,
+) -> Result<(i64, EventZone), String> {
+    if is_date(params) {
+        return Err(format!("All-day date '{}'", value));
+    }
+
+    let zone = event_zone(params, value)?;
+    let time = NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S")
+        .map_err(|e| format!("Invalid date-time '{}': '{}'", value, e))?;
+    zone.timestamp(&time, tz)
+        .map(|timestamp| (timestamp, zone))
+        .ok_or(format!("Nonexistent local time '{}'", value))
+}
+
+/// Seconds of a duration value like `PT1H30M` or `P1D`
+fn parse_duration(value: &str) -> Result<i64, String> {
+    let invalid = || format!("Invalid duration '{}'", value);
+    let (sign, unsigned) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+
+    let mut seconds = 0;
+    let mut amount = String::new();
+    let mut in_time = false;
+    for c in unsigned.strip_prefix('P').ok_or_else(invalid)?.chars() {
+        let unit = match (c, in_time) {
+            ('T', false) => {
+                in_time = true;
+                continue;
+            }
+            (c, _) if c.is_ascii_digit() => {
+                amount.push(c);
+                continue;
+            }
+            ('W', false) => 7 * 24 * 3600,
+            ('D', false) => 24 * 3600,
+            ('H', true) => 3600,
+            ('M', true) => 60,
+            ('S', true) => 1,
+            _ => return Err(invalid()),
+        };
+        seconds += amount.parse::<i64>().map_err(|_| invalid())? * unit;
+        amount.clear();
+    }
+    if !amount.is_empty() {
+        return Err(invalid());
+    }
+    Ok(sign * seconds)
+}
+
+/// Event of the file along with the properties tying an override to its recurring event, `event`
+/// is missing for all-day events
+struct ParsedEvent {
+    event: Option<IcsEvent>,
+    uid: Option<String>,
+    recurrence_id: Option<(Vec<(String, String)>, String)>,
+    cancelled: bool,
+}
+
+/// Timed events of an iCalendar file, all-day and cancelled events are skipped
+///
+/// Occurrences overridden by another event with the same `UID` and a `RECURRENCE-ID` are removed
+/// from the recurring event, the override is listed on its own unless cancelled.
+pub fn parse_events<Tz: TimeZone>(content: &str, tz: &Tz) -> Result<Vec<IcsEvent>, String> {
+    let mut parsed = Vec::new();
+    let mut properties: Option<Vec<Property>> = None;
+
+    for line in unfold_lines(content) {
+        let Some((name, params, value)) = split_property(&line) else {
+            continue;
+        };
+        match (
+            name.as_str(),
+            value.to_ascii_uppercase().as_str(),
+            &mut properties,
+        ) {
+            ("BEGIN", "VEVENT", _) => properties = Some(Vec::new()),
+            ("END", "VEVENT", Some(_)) => {
+                parsed.push(parse_event(&properties.take().unwrap_or_default(), tz)?)
+            }
+            (_, _, Some(properties)) => properties.push((name, params, value)),
+            _ => (),
+        }
+    }
+
+    let mut overridden = Vec::new();
+    for (uid, (params, value)) in parsed
+        .iter()
+        .filter_map(|p| p.uid.as_ref().zip(p.recurrence_id.as_ref()))
+    {
+        let master = parsed.iter().enumerate().find_map(|(idx, p)| {
+            match (&p.event, &p.uid, &p.recurrence_id) {
+                (Some(event), Some(master_uid), None) if master_uid == uid => Some((idx, event)),
+                _ => None,
+            }
+        });
+        if let Some((idx, event)) = master {
+            overridden.push((
+                idx,
+                occurrence_start(params, value, event.start, &event.zone, tz)?,
+            ));
+        }
+    }
+    for (idx, start) in overridden {
+        if let Some(event) = parsed[idx].event.as_mut() {
+            event.exdates.push(start);
+        }
+    }
+
+    Ok(parsed
+        .into_iter()
+        .filter(|p| !p.cancelled)
+        .filter_map(|p| p.event)
+        .collect())
+}
+
+fn parse_event<Tz: TimeZone>(properties: &[Property], tz: &Tz) -> Result<ParsedEvent, String> {
+    let property = |wanted: &str| properties.iter().find(|(name, _, _)| name == wanted);
+
+    Ok(ParsedEvent {
+        event: event_from_properties(properties, tz)?,
+        uid: property("UID").map(|(_, _, value)| value.clone()),
+        recurrence_id: property("RECURRENCE-ID")
+            .map(|(_, params, value)| (params.clone(), value.clone())),
+        cancelled: property("STATUS")
+            .is_some_and(|(_, _, value)| value.eq_ignore_ascii_case("CANCELLED")),
+    })
+}
+
+/// Start of an occurrence of the recurring event starting at `start`, days are matched at the
+/// time of day of the first occurrence
+fn occurrence_start<Tz: TimeZone>(
+    params: &[(String, String)],
+    value: &str,
+    start: i64,
+    zone: &EventZone,
+    tz: &Tz,
+) -> Result<i64, String> {
+    let start_time = zone.local_time(start, tz).map(|time| time.time());
+    match (is_date(params), start_time) {
+        (true, Some(start_time)) => NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .and_then(|day| zone.timestamp(&day.and_time(start_time), tz))
+            .ok_or(format!("Invalid date '{}'", value)),
+        _ => Ok(parse_date_time(params, value, tz)?.0),
+    }
+}
+
+fn event_from_properties<Tz: TimeZone>(
+    properties: &[Property],
+    tz: &Tz,
+) -> Result<Option<IcsEvent>, String> {
+    let property = |wanted: &str| properties.iter().find(|(name, _, _)| name == wanted);
+
+    let Some((_, start_params, start_value)) = property("DTSTART") else {
+        return Ok(None);
+    };
+    if is_date(start_params) {
+        return Ok(None);
+    }
+    let (start, zone) = parse_date_time(start_params, start_value, tz)?;
+    let end = match (property("DTEND"), property("DURATION")) {
+        (Some((_, params, value)), _) => parse_date_time(params, value, tz)?.0,
+        (None, Some((_, _, value))) => start + parse_duration(value)?,
+        (None, None) => start,
+    };
+
+    let mut exdates = Vec::new();
+    for (_, params, value) in properties.iter().filter(|(name, _, _)| name == "EXDATE") {
+        for exdate in value.split(',') {
+            exdates.push(occurrence_start(params, exdate, start, &zone, tz)?);
+        }
+    }
+
+    Ok(Some(IcsEvent {
+        summary: property("SUMMARY")
+            .map(|(_, _, value)| unescape_text(value))
+            .unwrap_or_default(),
+        categories: properties
+            .iter()
+            .filter(|(name, _, _)| name == "CATEGORIES")
+            .flat_map(|(_, _, value)| value.split(','))
+            .map(|category| unescape_text(category.trim()))
+            .filter(|category| !category.is_empty())
+            .collect(),
+        start,
+        end,
+        zone,
+        rrule: property("RRULE").map(|(_, _, value)| value.clone()),
+        exdates,
+    }))
+}
+
+fn parse_weekday(day: &str) -> Option<chrono::Weekday> {
+    match day {
+        "MO" => Some(chrono::Weekday::Mon),
+        "TU" => Some(chrono::Weekday::Tue),
+        "WE" => Some(chrono::Weekday::Wed),
+        "TH" => Some(chrono::Weekday::Thu),
+        "FR" => Some(chrono::Weekday::Fri),
+        "SA" => Some(chrono::Weekday::Sat),
+        "SU" => Some(chrono::Weekday::Sun),
+        _ => None,
+    }
+}
+
+const ALL_WEEKDAYS: [Weekday; 7] = [
+    Weekday::Monday,
+    Weekday::Tuesday,
+    Weekday::Wednesday,
+    Weekday::Thursday,
+    Weekday::Friday,
+    Weekday::Saturday,
+    Weekday::Sunday,
+];
+
+/// Periods a recurrence is expanded over, bounds the series without `COUNT` nor `UNTIL`
+const MAX_PERIODS: u32 = 100_000;
+
+#[derive(Debug, PartialEq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// Supported subset of the recurrence rules: daily and weekly ones on given weekdays, monthly and
+/// yearly ones on the day of the first occurrence
+#[derive(Debug)]
+struct Recurrence {
+    frequency: Frequency,
+    interval: u32,
+    by_day: Option<Vec<chrono::Weekday>>,
+    count: Option<usize>,
+    until: Option<i64>,
+}
+
+impl Recurrence {
+    fn parse<Tz: TimeZone>(rrule: &str, zone: &EventZone, tz: &Tz) -> Option<Recurrence> {
+        let mut recurrence = Recurrence {
+            frequency: Frequency::Daily,
+            interval: 1,
+            by_day: None,
+            count: None,
+            until: None,
+        };
+        let mut frequency = None;
+        for (key, value) in rrule.split(';').filter_map(|p| p.split_once('=')) {
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Some(Frequency::Daily),
+                        "WEEKLY" => Some(Frequency::Weekly),
+                        "MONTHLY" => Some(Frequency::Monthly),
+                        "YEARLY" => Some(Frequency::Yearly),
+                        _ => return None,
+                    }
+                }
+                "INTERVAL" => recurrence.interval = value.parse().ok().filter(|i| *i > 0)?,
+                "COUNT" => recurrence.count = Some(value.parse().ok()?),
+                "UNTIL" => {
+                    // A date includes the whole day
+                    let until =
+                        NaiveDateTime::parse_from_str(value.trim_end_matches('Z'), "%Y%m%dT%H%M%S")
+                            .ok()
+                            .or_else(|| {
+                                NaiveDate::parse_from_str(value, "%Y%m%d")
+                                    .ok()?
+                                    .and_hms_opt(23, 59, 59)
+                            })?;
+                    recurrence.until = match value.ends_with('Z') {
+                        true => Some(until.and_utc().timestamp()),
+                        false => zone.timestamp(&until, tz),
+                    };
+                }
+                "BYDAY" => {
+                    recurrence.by_day = Some(
+                        value
+                            .split(',')
+                            .map(|day| parse_weekday(&day.to_ascii_uppercase()))
+                            .collect::<Option<Vec<_>>>()?,
+                    )
+                }
+                "WKST" => (),
+                _ => return None,
+            }
+        }
+        recurrence.frequency = frequency?;
+        if recurrence.by_day.is_some()
+            && matches!(recurrence.frequency, Frequency::Monthly | Frequency::Yearly)
+        {
+            return None;
+        }
+        Some(recurrence)
+    }
+
+    /// Days matching the recurrence, in order, starting from the week, month or year of `first`
+    fn days(&self, first: NaiveDate) -> Box<dyn Iterator<Item = NaiveDate> + '_> {
+        let interval = self.interval;
+        let periods = (0..MAX_PERIODS).map(move |period| period * interval);
+        match self.frequency {
+            Frequency::Daily => Box::new(
+                periods
+                    .filter_map(move |days| first.checked_add_days(Days::new(u64::from(days))))
+                    .filter(|day| {
+                        self.by_day
+                            .as_ref()
+                            .is_none_or(|by_day| by_day.contains(&day.weekday()))
+                    }),
+            ),
+            Frequency::Weekly => {
+                let monday = first.week(chrono::Weekday::Mon).first_day();
+                let mut weekdays = self.by_day.clone().unwrap_or(vec![first.weekday()]);
+                weekdays.sort_by_key(|day| day.num_days_from_monday());
+                Box::new(periods.flat_map(move |weeks| {
+                    let week = monday.checked_add_days(Days::new(u64::from(weeks) * 7));
+                    weekdays.clone().into_iter().filter_map(move |day| {
+                        week?.checked_add_days(Days::new(u64::from(day.num_days_from_monday())))
+                    })
+                }))
+            }
+            Frequency::Monthly => Box::new(periods.filter_map(move |months| {
+                first
+                    .with_day(1)?
+                    .checked_add_months(Months::new(months))?
+                    .with_day(first.day())
+            })),
+            Frequency::Yearly => Box::new(periods.filter_map(move |years| {
+                NaiveDate::from_ymd_opt(
+                    first.year() + i32::try_from(years).ok()?,
+                    first.month(),
+                    first.day(),
+                )
+            })),
+        }
+    }
+}
+
+/// Occurrences of a recurring event, as start and end, ending up to `now`, along with whether
+/// the series goes on after it
+///
+/// `None` when the event doesn't repeat or its recurrence rule is not supported. Occurrences in
+/// progress are not listed.
+pub fn past_occurrences<Tz: TimeZone>(
+    event: &IcsEvent,
+    now: i64,
+    tz: &Tz,
+) -> Option<(Vec<(i64, i64)>, bool)> {
+    let recurrence = Recurrence::parse(event.rrule.as_ref()?, &event.zone, tz)?;
+    let first = event.zone.local_time(event.start, tz)?;
+    let duration = event.end - event.start;
+
+    let mut occurrences = Vec::new();
+    let mut count = 0;
+    for day in recurrence.days(first.date()) {
+        let Some(start) = event.zone.timestamp(&day.and_time(first.time()), tz) else {
+            continue;
+        };
+        if start < event.start {
+            continue;
+        }
+        if recurrence.count.is_some_and(|c| count >= c)
+            || recurrence.until.is_some_and(|until| start > until)
+        {
+            return Some((occurrences, false));
+        }
+        count += 1;
+        if start + duration > now {
+            return Some((occurrences, true));
+        }
+        if !event.exdates.contains(&start) {
+            occurrences.push((start, start + duration));
+        }
+    }
+    Some((occurrences, false))
+}
+
+/// Weekdays of an endless daily or weekly recurrence, other recurrences can't be scheduled
+pub fn recurring_weekdays<Tz: TimeZone>(event: &IcsEvent, tz: &Tz) -> Option<Vec<Weekday>> {
+    let recurrence = Recurrence::parse(event.rrule.as_ref()?, &event.zone, tz)?;
+    if recurrence.interval != 1 || recurrence.count.is_some() || recurrence.until.is_some() {
+        return None;
+    }
+
+    let weekdays = match (recurrence.frequency, recurrence.by_day) {
+        (Frequency::Daily, None) => ALL_WEEKDAYS.to_vec(),
+        (Frequency::Daily | Frequency::Weekly, Some(days)) => days
+            .iter()
+            .map(|day| ALL_WEEKDAYS[day.num_days_from_monday() as usize])
+            .collect(),
+        (Frequency::Weekly, None) => {
+            let weekday = tz.timestamp_opt(event.start, 0).earliest()?.weekday();
+            vec![ALL_WEEKDAYS[weekday.num_days_from_monday() as usize]]
+        }
+        _ => return None,
+    };
+    Some(weekdays)
+}
+
+#[derive(Serialize, Default, Debug)]
+pub struct CalendarImportSummary {
+    pub intervals: IntervalImportSummary,
+    pub scheduled_switches: usize,
+    /// Events not matching any rule
+    pub unmatched: usize,
+    /// Single events not over yet, left for a later import
+    pub upcoming: usize,
+    /// Recurrences that can't be expanded or, still going on, scheduled
+    pub skipped: usize,
+}
+
+pub fn validate_rules(rules: &[CalendarRule]) -> Result<(), String> {
+    for rule in rules {
+        Regex::new(&rule.pattern)
+            .map_err(|err| format!("Invalid rule pattern '{}' - {}", rule.pattern, err))?;
+    }
+    Ok(())
+}
+
+/// First rule matching the summary or one of the categories of the event
+pub fn find_matching_rule<'a>(
+    rules: &'a [CalendarRule],
+    event: &IcsEvent,
+) -> Option<&'a CalendarRule> {
+    rules.iter().find(|rule| match Regex::new(&rule.pattern) {
+        Ok(regex) => match rule.field {
+            CalendarField::Summary => regex.is_match(&event.summary),
+            CalendarField::Category => event.categories.iter().any(|c| regex.is_match(c)),
+        },
+        Err(_) => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracker::interval::test_interval;
+    use chrono::Utc;
+
+    #[test]
+    fn test_calendar() {
+        let intervals = vec![test_interval("dev, backend", 1709629200, Some(1709634600))];
+
+        assert_eq!(
+            calendar(&interval_events(&intervals), 1709700000),
+            "BEGIN:VCALENDAR\r\n\
+             VERSION:2.0\r\n\
+             PRODID:-//oclock//oclock//EN\r\n\
+             CALSCALE:GREGORIAN\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:1709629200@oclock\r\n\
+             DTSTAMP:20240306T044000Z\r\n\
+             DTSTART:20240305T090000Z\r\n\
+             DTEND:20240305T103000Z\r\n\
+             SUMMARY:dev\\, backend\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n"
+        );
+    }
+
+    #[test]
+    fn test_daily_events() {
+        let intervals = vec![
+            test_interval("dev", 1709629200, Some(1709634600)),
+            test_interval("meeting", 1709634600, Some(1709636400)),
+            test_interval("dev", 1709636400, Some(1709640000)),
+            test_interval("dev", 1709640000, Some(1709641800)),
+            test_interval("dev", 1709676000, Some(1709694000)),
+        ];
+
+        let event = |task: &str, tracked: &str, start: i64, end: i64| CalendarEvent {
+            uid: format!("{}-{}@oclock", start, task),
+            summary: String::from(task),
+            description: Some(format!("Tracked {}", tracked)),
+            start,
+            end,
+        };
+        assert_eq!(
+            daily_events(&intervals, &Utc),
+            vec![
+                event("dev", "01:30:00", 1709629200, 1709634600),
+                event("meeting", "00:30:00", 1709634600, 1709636400),
+                event("dev", "01:30:00", 1709636400, 1709641800),
+                event("dev", "02:00:00", 1709676000, 1709683200),
+                event("dev", "03:00:00", 1709683200, 1709694000),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fold_line() {
+        let line = format!("SUMMARY:{}", "é".repeat(40));
+        let folded = fold_line(&line);
+
+        assert!(folded.split("\r\n").all(|l| l.len() <= MAX_LINE_OCTETS));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let content = "BEGIN:VCALENDAR\r\n\
+                       BEGIN:VTIMEZONE\r\n\
+                       TZID:Europe/Rome\r\n\
+                       END:VTIMEZONE\r\n\
+                       BEGIN:VEVENT\r\n\
+                       SUMMARY:Sprint review\\, team A\r\n\
+                       CATEGORIES:Meeting,Scrum\r\n\
+                       DTSTART:20240305T090000Z\r\n\
+                       DTEND:20240305T100000Z\r\n\
+                       END:VEVENT\r\n\
+                       BEGIN:VEVENT\r\n\
+                       SUMMARY:Daily stand\r\n \
+                       up\r\n\
+                       DTSTART;TZID=\"Europe/Rome\":20240305T093000\r\n\
+                       DURATION:PT15M\r\n\
+                       RRULE:FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR\r\n\
+                       EXDATE;TZID=Europe/Rome:20240306T093000,20240307T093000\r\n\
+                       END:VEVENT\r\n\
+                       BEGIN:VEVENT\r\n\
+                       SUMMARY:Holiday\r\n\
+                       DTSTART;VALUE=DATE:20240306\r\n\
+                       END:VEVENT\r\n\
+                       END:VCALENDAR\r\n";
+
+        let events = parse_events(content, &Utc).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                IcsEvent {
+                    summary: String::from("Sprint review, team A"),
+                    categories: vec![String::from("Meeting"), String::from("Scrum")],
+                    start: 1709629200,
+                    end: 1709632800,
+                    zone: EventZone::Utc,
+                    rrule: None,
+                    exdates: vec![],
+                },
+                IcsEvent {
+                    summary: String::from("Daily standup"),
+                    categories: vec![],
+                    start: 1709627400,
+                    end: 1709628300,
+                    zone: EventZone::Named(chrono_tz::Europe::Rome),
+                    rrule: Some(String::from("FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR")),
+                    exdates: vec![1709713800, 1709800200],
+                },
+            ]
+        );
+
+        assert!(recurring_weekdays(&events[0], &Utc).is_none());
+        assert_eq!(recurring_weekdays(&events[1], &Utc).unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_overridden_occurrences() {
+        let content = "BEGIN:VCALENDAR\r\n\
+                       BEGIN:VEVENT\r\n\
+                       UID:standup\r\n\
+                       SUMMARY:Standup\r\n\
+                       DTSTART:20240305T093000Z\r\n\
+                       DURATION:PT15M\r\n\
+                       RRULE:FREQ=DAILY\r\n\
+                       END:VEVENT\r\n\
+                       BEGIN:VEVENT\r\n\
+                       UID:standup\r\n\
+                       RECURRENCE-ID:20240306T093000Z\r\n\
+                       SUMMARY:Standup\r\n\
+                       DTSTART:20240306T110000Z\r\n\
+                       DTEND:20240306T111500Z\r\n\
+                       END:VEVENT\r\n\
+                       BEGIN:VEVENT\r\n\
+                       UID:standup\r\n\
+                       RECURRENCE-ID;VALUE=DATE:20240307\r\n\
+                       STATUS:CANCELLED\r\n\
+                       SUMMARY:Standup\r\n\
+                       DTSTART:20240307T093000Z\r\n\
+                       DURATION:PT15M\r\n\
+                       END:VEVENT\r\n\
+                       BEGIN:VEVENT\r\n\
+                       UID:review\r\n\
+                       STATUS:CANCELLED\r\n\
+                       SUMMARY:Review\r\n\
+                       DTSTART:20240305T140000Z\r\n\
+                       DURATION:PT1H\r\n\
+                       END:VEVENT\r\n\
+                       END:VCALENDAR\r\n";
+
+        let events = parse_events(content, &Utc).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                IcsEvent {
+                    summary: String::from("Standup"),
+                    categories: vec![],
+                    start: 1709631000,
+                    end: 1709631900,
+                    zone: EventZone::Utc,
+                    rrule: Some(String::from("FREQ=DAILY")),
+                    exdates: vec![1709717400, 1709803800],
+                },
+                IcsEvent {
+                    summary: String::from("Standup"),
+                    categories: vec![],
+                    start: 1709722800,
+                    end: 1709723700,
+                    zone: EventZone::Utc,
+                    rrule: None,
+                    exdates: vec![],
+                },
+            ]
+        );
+
+        let (occurrences, _) = past_occurrences(&events[0], 1709890200 + 900, &Utc).unwrap();
+        assert_eq!(
+            occurrences,
+            vec![(1709631000, 1709631900), (1709890200, 1709891100)]
+        );
+    }
+
+    #[test]
+    fn test_recurring_weekdays() {
+        let event = |rrule: &str| IcsEvent {
+            summary: String::from("1:1"),
+            categories: vec![],
+            start: 1709631000,
+            end: 1709634600,
+            zone: EventZone::Utc,
+            rrule: Some(String::from(rrule)),
+            exdates: vec![],
+        };
+
+        assert!(matches!(
+            recurring_weekdays(&event("FREQ=WEEKLY"), &Utc).as_deref(),
+            Some([Weekday::Tuesday])
+        ));
+        assert_eq!(
+            recurring_weekdays(&event("FREQ=DAILY"), &Utc).map(|d| d.len()),
+            Some(7)
+        );
+        assert!(recurring_weekdays(&event("FREQ=DAILY;COUNT=10"), &Utc).is_none());
+        assert!(recurring_weekdays(&event("FREQ=WEEKLY;INTERVAL=2"), &Utc).is_none());
+        assert!(recurring_weekdays(&event("FREQ=MONTHLY"), &Utc).is_none());
+        assert!(recurring_weekdays(&event("FREQ=MONTHLY;BYDAY=1MO"), &Utc).is_none());
+    }
+
+    #[test]
+    fn test_past_occurrences() {
+        const HOUR: i64 = 3600;
+        const DAY: i64 = 24 * HOUR;
+        // Tuesday 2024-03-05 09:30 in Rome, an hour ahead of UTC until the end of March
+        let first = 1709627400;
+        let event = |rrule: &str, exdates: Vec<i64>| IcsEvent {
+            summary: String::from("1:1"),
+            categories: vec![],
+            start: first,
+            end: first + HOUR,
+            zone: EventZone::Named(chrono_tz::Europe::Rome),
+            rrule: Some(String::from(rrule)),
+            exdates,
+        };
+        let starts = |rrule: &str, exdates: Vec<i64>, now: i64| {
+            past_occurrences(&event(rrule, exdates), now, &Utc)
+                .map(|(occurrences, open)| (occurrences.iter().map(|o| o.0).collect(), open))
+        };
+
+        // Same wall clock time once daylight saving time starts
+        assert_eq!(
+            starts("FREQ=WEEKLY", vec![], first + 28 * DAY),
+            Some((
+                vec![
+                    first,
+                    first + 7 * DAY,
+                    first + 14 * DAY,
+                    first + 21 * DAY,
+                    first + 28 * DAY - HOUR
+                ],
+                true
+            ))
+        );
+        assert_eq!(
+            starts(
+                "FREQ=WEEKLY;BYDAY=TU,TH;COUNT=3",
+                vec![first + 2 * DAY],
+                first + 30 * DAY
+            ),
+            Some((vec![first, first + 7 * DAY], false))
+        );
+        assert_eq!(
+            starts(
+                "FREQ=DAILY;UNTIL=20240307T083000Z",
+                vec![],
+                first + 30 * DAY
+            ),
+            Some((vec![first, first + DAY, first + 2 * DAY], false))
+        );
+        assert_eq!(
+            starts(
+                "FREQ=DAILY;INTERVAL=2;UNTIL=20240309",
+                vec![],
+                first + 3 * DAY
+            ),
+            Some((vec![first, first + 2 * DAY], true))
+        );
+        assert_eq!(
+            starts("FREQ=MONTHLY", vec![], first + 70 * DAY),
+            Some((
+                vec![first, first + 31 * DAY - HOUR, first + 61 * DAY - HOUR],
+                true
+            ))
+        );
+        assert_eq!(starts("FREQ=MONTHLY;BYMONTHDAY=5", vec![], first), None);
+        assert_eq!(starts("FREQ=HOURLY", vec![], first), None);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("PT1H30M"), Ok(5400));
+        assert_eq!(parse_duration("P1DT2S"), Ok(86402));
+        assert_eq!(parse_duration("P1W"), Ok(604800));
+        assert!(parse_duration("1H").is_err());
+    }
+
+    #[test]
+    fn test_find_matching_rule() {
+        let rules = vec![
+            CalendarRule {
+                field: CalendarField::Summary,
+                pattern: String::from("(?i)standup"),
+                task_id: 1,
+            },
+            CalendarRule {
+                field: CalendarField::Category,
+                pattern: String::from("^Meeting$"),
+                task_id: 2,
+            },
+        ];
+        let event = |summary: &str, category: &str| IcsEvent {
+            summary: String::from(summary),
+            categories: vec![String::from(category)],
+            start: 0,
+            end: 0,
+            zone: EventZone::Utc,
+            rrule: None,
+            exdates: vec![],
+        };
+
+        let task_id = |e: &IcsEvent| find_matching_rule(&rules, e).map(|r| r.task_id);
+        assert_eq!(task_id(&event("Daily Standup", "Meeting")), Some(1));
+        assert_eq!(task_id(&event("Review", "Meeting")), Some(2));
+        assert_eq!(task_id(&event("Review", "Meetings")), None);
+    }
+}
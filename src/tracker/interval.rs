@@ -0,0 +1,500 @@
+use std::collections::HashSet;
+
+use chrono::{Days, NaiveDate, TimeZone};
+use oclock_sqlite::constants::SystemEventType;
+use oclock_sqlite::models::HistoryEntry;
+use serde::Serialize;
+
+/// Time spent on a task coming from another tool, open ended when `end` is missing
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Interval {
+    pub task: String,
+    pub start: i64,
+    pub end: Option<i64>,
+}
+
+impl Interval {
+    fn overlaps(&self, start: i64, end: Option<i64>) -> bool {
+        end.is_none_or(|end| self.start < end) && self.end.is_none_or(|e| start < e)
+    }
+
+    fn contains(&self, other: &Interval) -> bool {
+        other
+            .end
+            .is_some_and(|end| self.start <= other.start && self.end.is_none_or(|e| end <= e))
+    }
+}
+
+/// Interval overlapping tracked time, either already in the history or imported before it
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Conflict {
+    pub interval: Interval,
+    pub overlapping: Interval,
+}
+
+/// Intervals split between the ones fitting the existing history and the ones overlapping it
+#[derive(Default, Debug, PartialEq)]
+pub struct ImportPlan {
+    pub accepted: Vec<Interval>,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Outcome of an interval import, nothing is written when `dry_run` is set
+#[derive(Serialize, Default, Debug, PartialEq)]
+pub struct IntervalImportSummary {
+    pub dry_run: bool,
+    pub new_tasks: Vec<String>,
+    pub intervals: Vec<Interval>,
+    pub conflicts: Vec<Conflict>,
+}
+
+/// Tracked intervals of the history, the running one is open ended
+pub fn tracked(history: &[HistoryEntry]) -> Vec<Interval> {
+    history
+        .iter()
+        .filter(|h| h.task_id.is_some())
+        .map(|h| Interval {
+            task: h.task_name.clone().unwrap_or_default(),
+            start: i64::from(h.ts_start),
+            end: h.ts_end.map(i64::from),
+        })
+        .collect()
+}
+
+/// Accept the intervals not overlapping any tracked time, existing or accepted before them
+///
+/// With `split_tracked` the closed intervals within a single tracked interval are accepted as
+/// well, splitting it. Empty intervals are dropped, the result is sorted by start time.
+pub fn plan_import(
+    history: &[HistoryEntry],
+    mut intervals: Vec<Interval>,
+    split_tracked: bool,
+) -> ImportPlan {
+    intervals.retain(|i| i.end.is_none_or(|end| end > i.start));
+    intervals.sort_by_key(|i| i.start);
+
+    let tracked = tracked(history);
+
+    let mut plan = ImportPlan::default();
+    for interval in intervals {
+        let overlapping = tracked
+            .iter()
+            .filter(|t| !(split_tracked && t.contains(&interval)))
+            .chain(plan.accepted.iter())
+            .find(|other| interval.overlaps(other.start, other.end))
+            .cloned();
+        match overlapping {
+            Some(overlapping) => plan.conflicts.push(Conflict {
+                interval,
+                overlapping,
+            }),
+            None => plan.accepted.push(interval),
+        }
+    }
+    plan
+}
+
+/// Event written to record an imported interval
+#[derive(Debug, PartialEq)]
+pub enum IntervalEvent<'a> {
+    Task(&'a str),
+    System(String),
+}
+
+/// Events recording the accepted intervals, as timestamp and event
+///
+/// Intervals are closed unless something else already happens at their end, going back to the
+/// history entry containing the end: the task of a split interval or a system event like a
+/// shutdown, with a pause when there is none.
+pub fn interval_events<'a>(
+    history: &'a [HistoryEntry],
+    accepted: &'a [Interval],
+) -> Vec<(i64, IntervalEvent<'a>)> {
+    let timestamps: HashSet<i64> = history.iter().map(|h| i64::from(h.ts_start)).collect();
+
+    let mut events = Vec::new();
+    for (idx, interval) in accepted.iter().enumerate() {
+        events.push((interval.start, IntervalEvent::Task(interval.task.as_str())));
+        if let Some(end) = interval.end {
+            let followed = accepted.get(idx + 1).map(|next| next.start) == Some(end);
+            if !followed && !timestamps.contains(&end) {
+                events.push((end, closing_event(history, end)));
+            }
+        }
+    }
+    events
+}
+
+fn closing_event(history: &[HistoryEntry], timestamp: i64) -> IntervalEvent<'_> {
+    let entry = history.iter().find(|h| {
+        i64::from(h.ts_start) <= timestamp && h.ts_end.is_none_or(|end| timestamp < i64::from(end))
+    });
+    match entry {
+        Some(HistoryEntry {
+            task_name: Some(task),
+            ..
+        }) => IntervalEvent::Task(task),
+        Some(HistoryEntry {
+            system_event: Some(event),
+            ..
+        }) => IntervalEvent::System(event.clone()),
+        _ => IntervalEvent::System(SystemEventType::Pause.to_string()),
+    }
+}
+
+/// Parse a day in the `YYYY-MM-DD` format
+pub fn parse_day(day: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(day, "%Y-%m-%d")
+        .map_err(|err| format!("Invalid day '{}', expected YYYY-MM-DD - {}", day, err))
+}
+
+/// Unix timestamp of the local midnight starting the given day
+pub fn day_start<Tz: TimeZone>(day: NaiveDate, tz: &Tz) -> i64 {
+    tz.from_local_datetime(&day.and_hms_opt(0, 0, 0).unwrap_or_default())
+        .earliest()
+        .map(|start| start.timestamp())
+        .unwrap_or(0)
+}
+
+/// Tracked intervals clipped to `[from, to)`, the running one ends at `now`
+pub fn tracked_between(history: &[HistoryEntry], from: i64, to: i64, now: i64) -> Vec<Interval> {
+    tracked_events_between(history, from, to, now)
+        .into_iter()
+        .map(|(_, interval)| interval)
+        .collect()
+}
+
+/// End of each history entry, `now` for the running one
+///
+/// The daemon keeps a ping after the last event to tell it is alive, the entry before it is still
+/// running.
+pub fn entry_ends(history: &[HistoryEntry], now: i64) -> Vec<i64> {
+    let trailing_ping = history.last().is_some_and(|h| {
+        h.ts_end.is_none() && h.system_event == Some(SystemEventType::Ping.to_string())
+    });
+    history
+        .iter()
+        .enumerate()
+        .map(|(idx, h)| match h.ts_end {
+            Some(_) if trailing_ping && idx + 2 == history.len() => now,
+            Some(end) => i64::from(end),
+            None => now,
+        })
+        .collect()
+}
+
+/// Same as [`tracked_between`], pairing each interval with the id of the event starting it
+pub fn tracked_events_between(
+    history: &[HistoryEntry],
+    from: i64,
+    to: i64,
+    now: i64,
+) -> Vec<(i32, Interval)> {
+    history
+        .iter()
+        .zip(entry_ends(history, now))
+        .filter(|(h, _)| h.task_id.is_some())
+        .map(|(h, end)| {
+            (
+                h.id,
+                Interval {
+                    task: h.task_name.clone().unwrap_or_default(),
+                    start: from.max(i64::from(h.ts_start)),
+                    end: Some(to.min(end)),
+                },
+            )
+        })
+        .filter(|(_, i)| i.end.is_some_and(|end| end > i.start))
+        .collect()
+}
+
+/// Split the closed intervals at local midnight, pairing each part with its day
+pub fn split_by_day<Tz: TimeZone>(intervals: &[Interval], tz: &Tz) -> Vec<(NaiveDate, Interval)> {
+    let mut parts = Vec::new();
+    for interval in intervals {
+        let Some(end) = interval.end else {
+            continue;
+        };
+        let mut start = interval.start;
+        while start < end {
+            let Some(day) = tz
+                .timestamp_opt(start, 0)
+                .earliest()
+                .map(|s| s.date_naive())
+            else {
+                break;
+            };
+            let next_day = day
+                .checked_add_days(Days::new(1))
+                .map_or(end, |next| day_start(next, tz));
+            let part_end = end.min(next_day).max(start + 1);
+            parts.push((
+                day,
+                Interval {
+                    task: interval.task.clone(),
+                    start,
+                    end: Some(part_end),
+                },
+            ));
+            start = part_end;
+        }
+    }
+    parts
+}
+
+/// Interval of the given task, shared by the tests
+#[cfg(test)]
+pub(crate) fn test_interval(task: &str, start: i64, end: Option<i64>) -> Interval {
+    Interval {
+        task: String::from(task),
+        start,
+        end,
+    }
+}
+
+/// Day in the `YYYY-MM-DD` format, shared by the tests
+#[cfg(test)]
+pub(crate) fn test_day(day: &str) -> NaiveDate {
+    parse_day(day).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(ts_start: i64, ts_end: Option<i64>, task_id: Option<i32>) -> HistoryEntry {
+        HistoryEntry {
+            id: ts_start as i32,
+            ts_start: ts_start as i32,
+            ts_end: ts_end.map(|ts| ts as i32),
+            system_event: task_id.map_or(Some(String::from("Pause")), |_| None),
+            task_name: task_id.map(|id| format!("task {}", id)),
+            task_id,
+        }
+    }
+
+    #[test]
+    fn test_plan_import() {
+        let history = vec![
+            entry(100, Some(200), Some(1)),
+            entry(200, Some(500), None),
+            entry(500, None, Some(2)),
+        ];
+
+        let plan = plan_import(
+            &history,
+            vec![
+                test_interval("late", 600, Some(700)),
+                test_interval("review", 250, Some(300)),
+                test_interval("meeting", 200, Some(250)),
+                test_interval("empty", 400, Some(400)),
+                test_interval("early", 50, Some(150)),
+                test_interval("again", 280, Some(320)),
+            ],
+            false,
+        );
+        assert_eq!(
+            plan,
+            ImportPlan {
+                accepted: vec![
+                    test_interval("meeting", 200, Some(250)),
+                    test_interval("review", 250, Some(300)),
+                ],
+                conflicts: vec![
+                    Conflict {
+                        interval: test_interval("early", 50, Some(150)),
+                        overlapping: test_interval("task 1", 100, Some(200)),
+                    },
+                    Conflict {
+                        interval: test_interval("again", 280, Some(320)),
+                        overlapping: test_interval("review", 250, Some(300)),
+                    },
+                    Conflict {
+                        interval: test_interval("late", 600, Some(700)),
+                        overlapping: test_interval("task 2", 500, None),
+                    },
+                ],
+            }
+        );
+
+        assert_eq!(
+            interval_events(&history, &plan.accepted),
+            vec![
+                (200, IntervalEvent::Task("meeting")),
+                (250, IntervalEvent::Task("review")),
+                (300, IntervalEvent::System(String::from("Pause"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_interval_in_shutdown_gap() {
+        let history = vec![
+            entry(100, Some(200), Some(1)),
+            HistoryEntry {
+                system_event: Some(String::from("Shutdown")),
+                ..entry(200, Some(500), None)
+            },
+            entry(500, None, Some(2)),
+        ];
+
+        let plan = plan_import(
+            &history,
+            vec![test_interval("meeting", 250, Some(300))],
+            false,
+        );
+        assert_eq!(
+            interval_events(&history, &plan.accepted),
+            vec![
+                (250, IntervalEvent::Task("meeting")),
+                (300, IntervalEvent::System(String::from("Shutdown"))),
+            ]
+        );
+
+        let plan = plan_import(
+            &history,
+            vec![test_interval("meeting", 10, Some(50))],
+            false,
+        );
+        assert_eq!(
+            interval_events(&history, &plan.accepted),
+            vec![
+                (10, IntervalEvent::Task("meeting")),
+                (50, IntervalEvent::System(String::from("Pause"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_tracked() {
+        let history = vec![
+            entry(100, Some(200), Some(1)),
+            entry(200, Some(500), None),
+            entry(500, None, Some(2)),
+        ];
+        let intervals = vec![
+            test_interval("meeting", 120, Some(150)),
+            test_interval("review", 150, Some(180)),
+            test_interval("across", 180, Some(250)),
+            test_interval("standup", 600, Some(650)),
+            test_interval("open", 700, None),
+        ];
+
+        let plan = plan_import(&history, intervals.clone(), false);
+        assert!(plan.accepted.is_empty());
+
+        let plan = plan_import(&history, intervals, true);
+        assert_eq!(
+            plan.accepted,
+            vec![
+                test_interval("meeting", 120, Some(150)),
+                test_interval("review", 150, Some(180)),
+                test_interval("standup", 600, Some(650)),
+            ]
+        );
+        assert_eq!(
+            plan.conflicts
+                .iter()
+                .map(|c| c.interval.task.as_str())
+                .collect::<Vec<_>>(),
+            vec!["across", "open"]
+        );
+        assert_eq!(
+            interval_events(&history, &plan.accepted),
+            vec![
+                (120, IntervalEvent::Task("meeting")),
+                (150, IntervalEvent::Task("review")),
+                (180, IntervalEvent::Task("task 1")),
+                (600, IntervalEvent::Task("standup")),
+                (650, IntervalEvent::Task("task 2")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_open_interval() {
+        let history = vec![entry(100, Some(200), Some(1)), entry(200, None, None)];
+
+        let plan = plan_import(&history, vec![test_interval("dev", 300, None)], false);
+        assert_eq!(plan.accepted, vec![test_interval("dev", 300, None)]);
+        assert_eq!(
+            interval_events(&history, &plan.accepted),
+            vec![(300, IntervalEvent::Task("dev"))]
+        );
+
+        let plan = plan_import(&history, vec![test_interval("dev", 150, None)], false);
+        assert_eq!(
+            plan.conflicts,
+            vec![Conflict {
+                interval: test_interval("dev", 150, None),
+                overlapping: test_interval("task 1", 100, Some(200)),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_trailing_ping() {
+        let history = vec![
+            entry(100, Some(200), Some(1)),
+            entry(200, Some(300), Some(2)),
+            HistoryEntry {
+                system_event: Some(String::from("Ping")),
+                ..entry(300, None, None)
+            },
+        ];
+
+        assert_eq!(entry_ends(&history, 350), vec![200, 350, 350]);
+        assert_eq!(
+            tracked_between(&history, 0, 1000, 350),
+            vec![
+                test_interval("task 1", 100, Some(200)),
+                test_interval("task 2", 200, Some(350)),
+            ]
+        );
+        assert_eq!(
+            tracked_between(&history[..2], 0, 1000, 350),
+            vec![
+                test_interval("task 1", 100, Some(200)),
+                test_interval("task 2", 200, Some(300)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_by_day() {
+        use chrono::Utc;
+
+        const DAY: i64 = 24 * 3600;
+        let history = vec![
+            entry(3600, Some(2 * DAY + 3600), Some(1)),
+            entry(2 * DAY + 3600, Some(2 * DAY + 7200), None),
+            entry(2 * DAY + 7200, None, Some(2)),
+        ];
+
+        let tracked = tracked_between(&history, DAY + 3600, 3 * DAY, 2 * DAY + 9000);
+        assert_eq!(
+            tracked,
+            vec![
+                test_interval("task 1", DAY + 3600, Some(2 * DAY + 3600)),
+                test_interval("task 2", 2 * DAY + 7200, Some(2 * DAY + 9000)),
+            ]
+        );
+
+        let day = |offset: u64| parse_day("1970-01-01").unwrap() + Days::new(offset);
+        assert_eq!(
+            split_by_day(&tracked, &Utc),
+            vec![
+                (day(1), test_interval("task 1", DAY + 3600, Some(2 * DAY))),
+                (
+                    day(2),
+                    test_interval("task 1", 2 * DAY, Some(2 * DAY + 3600))
+                ),
+                (
+                    day(2),
+                    test_interval("task 2", 2 * DAY + 7200, Some(2 * DAY + 9000))
+                ),
+            ]
+        );
+        assert_eq!(day_start(day(2), &Utc), 2 * DAY);
+    }
+}
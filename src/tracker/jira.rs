@@ -0,0 +1,223 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+
+use chrono::{NaiveDate, TimeZone};
+use regex::Regex;
+use serde::Serialize;
+
+use crate::dto::command::Rounding;
+use crate::tracker::interval::{self, Interval};
+use crate::tracker::rounding;
+
+/// Jira issue keys, a project key followed by the issue number (e.g. `OCLOCK-42`)
+pub const DEFAULT_ISSUE_PATTERN: &str = r"\b[A-Z][A-Z0-9_]+-[0-9]+\b";
+
+/// Record of the Jira worklog API, along with the key of the issue it belongs to
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Worklog {
+    pub issue_key: String,
+    pub started: String,
+    pub time_spent_seconds: i64,
+    pub comment: String,
+}
+
+/// Worklogs sorted by start time, along with the tracked tasks without an issue key and the number of
+/// worklogs left out for being too short
+#[derive(Debug, PartialEq)]
+pub struct WorklogExport {
+    pub worklogs: Vec<Worklog>,
+    pub skipped_tasks: Vec<String>,
+    pub short_worklogs: usize,
+}
+
+/// Regex extracting the issue key from the task names, the first group is used when there is one
+pub fn issue_pattern(pattern: Option<&str>) -> Result<Regex, String> {
+    let pattern = pattern.unwrap_or(DEFAULT_ISSUE_PATTERN);
+    Regex::new(pattern).map_err(|e| format!("Invalid issue pattern '{}': {}", pattern, e))
+}
+
+pub fn issue_key(pattern: &Regex, task: &str) -> Option<String> {
+    pattern
+        .captures(task)
+        .and_then(|captures| captures.get(1).or_else(|| captures.get(0)))
+        .map(|key| String::from(key.as_str()))
+}
+
+/// Worklogs shorter than a minute are rejected by Jira
+pub const MIN_WORKLOG_SECONDS: i64 = 60;
+
+#[derive(Default)]
+struct IssueDay {
+    started: Option<i64>,
+    tasks: BTreeSet<String>,
+    notes: Vec<String>,
+}
+
+impl IssueDay {
+    /// Notes of the switches in the order they were taken, the task names when there is none
+    fn comment(self) -> String {
+        if self.notes.is_empty() {
+            self.tasks.into_iter().collect::<Vec<_>>().join(", ")
+        } else {
+            self.notes.join("; ")
+        }
+    }
+}
+
+/// A worklog per issue per day, starting with the first interval of the day and lasting the eventually rounded
+/// time spent on all the tasks of the issue; the comment joins the notes of the switches, or lists the task names
+/// when there are none. Worklogs shorter than a minute are left out since Jira rejects them.
+pub fn worklogs<Tz: TimeZone>(
+    intervals: &[(Interval, Option<String>)],
+    pattern: &Regex,
+    rounding: Option<&Rounding>,
+    tz: &Tz,
+) -> WorklogExport
+where
+    Tz::Offset: Display,
+{
+    let mut skipped_tasks = BTreeSet::new();
+    let mut days: BTreeMap<(NaiveDate, String), IssueDay> = BTreeMap::new();
+    let mut issue_intervals = Vec::new();
+    for (interval, note) in intervals {
+        let Some(key) = issue_key(pattern, &interval.task) else {
+            skipped_tasks.insert(interval.task.clone());
+            continue;
+        };
+        for (day, part) in interval::split_by_day(std::slice::from_ref(interval), tz) {
+            let issue_day = days.entry((day, key.clone())).or_default();
+            issue_day.started = Some(issue_day.started.map_or(part.start, |s| s.min(part.start)));
+            issue_day.tasks.insert(part.task);
+            if let Some(note) = note.as_ref().filter(|n| !issue_day.notes.contains(n)) {
+                issue_day.notes.push(note.clone());
+            }
+        }
+        issue_intervals.push(Interval {
+            task: key,
+            ..interval.clone()
+        });
+    }
+
+    let (durations, short): (Vec<_>, Vec<_>) =
+        rounding::day_durations(&issue_intervals, tz, rounding)
+            .into_iter()
+            .filter(|(_, durations)| durations.rounded > 0)
+            .partition(|(_, durations)| durations.rounded >= MIN_WORKLOG_SECONDS);
+    let mut worklogs: Vec<(i64, Worklog)> = durations
+        .into_iter()
+        .filter_map(|(key, durations)| {
+            let issue_day = days.remove(&key)?;
+            let started = issue_day.started?;
+            let local_started = tz.timestamp_opt(started, 0).earliest()?;
+            Some((
+                started,
+                Worklog {
+                    issue_key: key.1,
+                    started: local_started.format("%Y-%m-%dT%H:%M:%S%.3f%z").to_string(),
+                    time_spent_seconds: durations.rounded,
+                    comment: issue_day.comment(),
+                },
+            ))
+        })
+        .collect();
+    worklogs.sort_by_key(|(started, _)| *started);
+
+    WorklogExport {
+        worklogs: worklogs.into_iter().map(|(_, worklog)| worklog).collect(),
+        skipped_tasks: skipped_tasks.into_iter().collect(),
+        short_worklogs: short.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto::command::{RoundingMode, RoundingScope};
+    use chrono::FixedOffset;
+
+    const MINUTE: i64 = 60;
+
+    #[test]
+    fn test_issue_key() {
+        let pattern = issue_pattern(None).unwrap();
+        assert_eq!(
+            issue_key(&pattern, "OCLOCK-42 fix the export"),
+            Some(String::from("OCLOCK-42"))
+        );
+        assert_eq!(
+            issue_key(&pattern, "review [WEB2-7]"),
+            Some(String::from("WEB2-7"))
+        );
+        assert_eq!(issue_key(&pattern, "meeting"), None);
+        assert_eq!(issue_key(&pattern, "dev-1 lowercase"), None);
+
+        let pattern = issue_pattern(Some(r"#(\d+)")).unwrap();
+        assert_eq!(issue_key(&pattern, "bug #123"), Some(String::from("123")));
+        assert!(issue_pattern(Some("(")).is_err());
+    }
+
+    #[test]
+    fn test_worklogs() {
+        let tz = FixedOffset::east_opt(3600).unwrap();
+        let day = NaiveDate::parse_from_str("2024-03-05", "%Y-%m-%d").unwrap();
+        let start = interval::day_start(day, &tz) + 9 * 60 * MINUTE;
+        let interval = |task: &str, from: i64, seconds: i64, note: Option<&str>| {
+            (
+                Interval {
+                    task: String::from(task),
+                    start: start + from * MINUTE,
+                    end: Some(start + from * MINUTE + seconds),
+                },
+                note.map(String::from),
+            )
+        };
+        let intervals = vec![
+            interval("OCLOCK-1 export", 0, 20 * MINUTE, Some("csv writer")),
+            interval("meeting", 20, 30 * MINUTE, Some("planning")),
+            interval("OCLOCK-2 review", 50, 5 * MINUTE, None),
+            interval("OCLOCK-1 export tests", 55, 22 * MINUTE, Some("rounding")),
+            interval("OCLOCK-1 export", 77, 3 * MINUTE, Some("csv writer")),
+            interval("OCLOCK-3 typo", 80, 30, Some("fix typo")),
+        ];
+        let pattern = issue_pattern(None).unwrap();
+
+        let export = worklogs(&intervals, &pattern, None, &tz);
+        assert_eq!(export.skipped_tasks, vec![String::from("meeting")]);
+        // Jira rejects the worklogs shorter than a minute
+        assert_eq!(export.short_worklogs, 1);
+        assert_eq!(
+            export.worklogs,
+            vec![
+                Worklog {
+                    issue_key: String::from("OCLOCK-1"),
+                    started: String::from("2024-03-05T09:00:00.000+0100"),
+                    time_spent_seconds: 45 * MINUTE,
+                    comment: String::from("csv writer; rounding"),
+                },
+                Worklog {
+                    issue_key: String::from("OCLOCK-2"),
+                    started: String::from("2024-03-05T09:50:00.000+0100"),
+                    time_spent_seconds: 5 * MINUTE,
+                    comment: String::from("OCLOCK-2 review"),
+                },
+            ]
+        );
+
+        let rounding = Rounding {
+            increment_minutes: 15,
+            mode: RoundingMode::Nearest,
+            scope: RoundingScope::Day,
+        };
+        let export = worklogs(&intervals, &pattern, Some(&rounding), &tz);
+        assert_eq!(
+            export
+                .worklogs
+                .iter()
+                .map(|w| (w.issue_key.as_str(), w.time_spent_seconds))
+                .collect::<Vec<_>>(),
+            vec![("OCLOCK-1", 45 * MINUTE)]
+        );
+        assert_eq!(export.short_worklogs, 0);
+    }
+}
@@ -0,0 +1,25 @@
+pub mod backup;
+pub mod billing;
+pub mod budget;
+pub mod clock;
+pub mod context_rule;
+pub mod csv_import;
+pub mod fsck;
+pub mod gaps;
+pub mod html_report;
+pub mod ical;
+pub mod interval;
+pub mod jira;
+pub mod notification;
+pub mod org;
+pub mod overtime;
+pub mod pomodoro;
+pub mod rounding;
+pub mod scheduled_switch;
+pub mod state;
+pub mod stats;
+pub mod svg_chart;
+pub mod timeclock;
+pub mod timewarrior;
+
+pub mod handler;
@@ -0,0 +1,19 @@
+use serde::Serialize;
+
+use crate::tracker::budget::BudgetStatus;
+
+/// Message published on the pub socket and passed to hooks when something needs the user attention
+#[derive(Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", tag = "notification")]
+pub enum Notification {
+    BudgetAlert(BudgetStatus),
+}
+
+impl Notification {
+    /// Name of the executable, inside the hooks directory, run on this notification
+    pub fn hook_name(&self) -> &'static str {
+        match self {
+            Notification::BudgetAlert(_) => "budget-alert",
+        }
+    }
+}
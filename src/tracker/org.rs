@@ -0,0 +1,177 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use itertools::Itertools;
+use regex::Regex;
+
+use crate::tracker::interval::Interval;
+
+const ORG_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %a %H:%M";
+const TODO_KEYWORDS: [&str; 2] = ["TODO", "DONE"];
+
+fn format_timestamp<Tz: TimeZone>(timestamp: i64, tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    tz.timestamp_opt(timestamp, 0)
+        .earliest()
+        .map(|dt| dt.format(ORG_TIMESTAMP_FORMAT).to_string())
+        .unwrap_or_default()
+}
+
+/// `CLOCK:` line of an interval, the running one has no end
+fn clock_line<Tz: TimeZone>(interval: &Interval, tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let start = format_timestamp(interval.start, tz);
+    match interval.end {
+        Some(end) => {
+            let minutes = (end - interval.start) / 60;
+            format!(
+                "CLOCK: [{}]--[{}] => {:>2}:{:02}",
+                start,
+                format_timestamp(end, tz),
+                minutes / 60,
+                minutes % 60
+            )
+        }
+        None => format!("CLOCK: [{}]", start),
+    }
+}
+
+/// Org document with a heading per task, clocks are listed newest first in its LOGBOOK drawer
+pub fn write_intervals<Tz: TimeZone>(intervals: &[Interval], tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let mut document = String::new();
+    for (task, clocks) in intervals
+        .iter()
+        .into_group_map_by(|i| i.task.as_str())
+        .into_iter()
+        .sorted_by_key(|(task, _)| *task)
+    {
+        document.push_str(&format!("* {}\n  :LOGBOOK:\n", task));
+        for interval in clocks.iter().rev() {
+            document.push_str(&format!("  {}\n", clock_line(interval, tz)));
+        }
+        document.push_str("  :END:\n");
+    }
+    document
+}
+
+/// Title of a heading line without stars, TODO keyword and tags
+fn heading_title(line: &str) -> Option<String> {
+    let title = line.strip_prefix('*')?.trim_start_matches('*');
+    if !title.starts_with(' ') {
+        return None;
+    }
+    let mut words: Vec<&str> = title.split_whitespace().collect();
+    if words.first().is_some_and(|w| TODO_KEYWORDS.contains(w)) {
+        words.remove(0);
+    }
+    if words
+        .last()
+        .is_some_and(|w| w.len() > 1 && w.starts_with(':') && w.ends_with(':'))
+    {
+        words.pop();
+    }
+    Some(words.join(" "))
+}
+
+fn parse_timestamp<Tz: TimeZone>(day: &str, time: &str, tz: &Tz) -> Result<i64, String> {
+    let day = NaiveDate::parse_from_str(day, "%Y-%m-%d")
+        .map_err(|e| format!("Invalid date '{}': '{}'", day, e))?;
+    let time = NaiveTime::parse_from_str(time, "%H:%M")
+        .map_err(|e| format!("Invalid time '{}': '{}'", time, e))?;
+    tz.from_local_datetime(&NaiveDateTime::new(day, time))
+        .earliest()
+        .map(|dt| dt.timestamp())
+        .ok_or(format!("Nonexistent local time {} {}", day, time))
+}
+
+/// Intervals of the `CLOCK:` lines, each belonging to the task named after the closest heading
+pub fn read_intervals<Tz: TimeZone>(content: &str, tz: &Tz) -> Result<Vec<Interval>, String> {
+    let clock = Regex::new(
+        r"^\s*CLOCK:\s*\[(\d{4}-\d{2}-\d{2})[^\d\]]*(\d{1,2}:\d{2})\](?:--\[(\d{4}-\d{2}-\d{2})[^\d\]]*(\d{1,2}:\d{2})\])?",
+    )
+    .map_err(|e| format!("Invalid clock pattern: '{}'", e))?;
+
+    let mut intervals = Vec::new();
+    let mut heading: Option<String> = None;
+    for (idx, line) in content.lines().enumerate() {
+        if let Some(title) = heading_title(line) {
+            heading = Some(title);
+            continue;
+        }
+        let Some(captures) = clock.captures(line) else {
+            continue;
+        };
+        let line_error = |e: String| format!("Line {}: {}", idx + 1, e);
+        let task = heading
+            .clone()
+            .ok_or(line_error(String::from("Clock outside of a heading")))?;
+
+        let start = parse_timestamp(&captures[1], &captures[2], tz).map_err(line_error)?;
+        let end = match (captures.get(3), captures.get(4)) {
+            (Some(day), Some(time)) => {
+                Some(parse_timestamp(day.as_str(), time.as_str(), tz).map_err(line_error)?)
+            }
+            _ => None,
+        };
+        intervals.push(Interval { task, start, end });
+    }
+    Ok(intervals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracker::interval::test_interval;
+    use chrono::Utc;
+
+    #[test]
+    fn test_write_intervals() {
+        let intervals = vec![
+            test_interval("meeting", 1709629200, Some(1709632800)),
+            test_interval("dev", 1709632800, Some(1709673300)),
+            test_interval("dev", 1709676000, None),
+        ];
+
+        assert_eq!(
+            write_intervals(&intervals, &Utc),
+            "* dev\n  \
+             :LOGBOOK:\n  \
+             CLOCK: [2024-03-05 Tue 22:00]\n  \
+             CLOCK: [2024-03-05 Tue 10:00]--[2024-03-05 Tue 21:15] => 11:15\n  \
+             :END:\n\
+             * meeting\n  \
+             :LOGBOOK:\n  \
+             CLOCK: [2024-03-05 Tue 09:00]--[2024-03-05 Tue 10:00] =>  1:00\n  \
+             :END:\n"
+        );
+    }
+
+    #[test]
+    fn test_read_intervals() {
+        let content = "#+TITLE: Work\n\
+                       * Project\n\
+                       ** TODO Write docs :writing:\n\
+                       :LOGBOOK:\n\
+                       CLOCK: [2024-03-05 mar 09:00]--[2024-03-05 mar 10:30] =>  1:30\n\
+                       CLOCK: [2024-03-05 Tue 11:00]\n\
+                       :END:\n\
+                       Some notes\n";
+
+        assert_eq!(
+            read_intervals(content, &Utc).unwrap(),
+            vec![
+                test_interval("Write docs", 1709629200, Some(1709634600)),
+                test_interval("Write docs", 1709636400, None),
+            ]
+        );
+        assert_eq!(
+            read_intervals("CLOCK: [2024-03-05 Tue 11:00]", &Utc),
+            Err(String::from("Line 1: Clock outside of a heading"))
+        );
+    }
+}
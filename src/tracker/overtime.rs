@@ -0,0 +1,277 @@
+use std::collections::{BTreeMap, HashSet};
+
+use chrono::{Datelike, Days, NaiveDate};
+use oclock_sqlite::models::{Holiday, WorkTarget};
+use serde::Serialize;
+
+/// Configured work targets and holidays
+#[derive(Serialize, Debug)]
+pub struct WorkTargets {
+    pub targets: Vec<WorkTarget>,
+    pub holidays: Vec<Holiday>,
+}
+
+/// Target, tracked time and their difference over a day or a week, `balance` sums the differences
+/// since the first target up to the end of the period
+#[derive(Serialize, Debug, PartialEq)]
+pub struct PeriodBalance {
+    pub start: String,
+    pub target: i64,
+    pub tracked: i64,
+    pub difference: i64,
+    pub balance: i64,
+}
+
+/// Over and under time per day and per week, weeks start on Monday
+#[derive(Serialize, Debug, PartialEq)]
+pub struct OvertimeReport {
+    pub days: Vec<PeriodBalance>,
+    pub weeks: Vec<PeriodBalance>,
+    pub balance: i64,
+}
+
+/// Progress of the current day and week, `balance` sums the differences of the days before today
+#[derive(Serialize, Debug, PartialEq)]
+pub struct OvertimeStatus {
+    pub day_target: i64,
+    pub day_tracked: i64,
+    pub week_target: i64,
+    pub week_tracked: i64,
+    pub balance: i64,
+}
+
+/// Weekdays mask from Monday to Friday, used when a target doesn't list its weekdays
+pub const WORKING_DAYS: i32 = 0b1_1111;
+
+fn format_day(day: NaiveDate) -> String {
+    day.format("%Y-%m-%d").to_string()
+}
+
+fn week_start(day: NaiveDate) -> NaiveDate {
+    day - Days::new(u64::from(day.weekday().num_days_from_monday()))
+}
+
+/// Targets and holidays in effect, the targets sorted by the day they start from
+pub struct WorkSchedule {
+    targets: Vec<WorkTarget>,
+    holidays: HashSet<String>,
+}
+
+impl WorkSchedule {
+    pub fn new(mut targets: Vec<WorkTarget>, holidays: &[Holiday]) -> WorkSchedule {
+        targets.sort_by(|a, b| a.effective_from.cmp(&b.effective_from));
+        WorkSchedule {
+            targets,
+            holidays: holidays.iter().map(|h| h.day.clone()).collect(),
+        }
+    }
+
+    /// Day of the first target, no time is due before it
+    pub fn first_day(&self) -> Option<NaiveDate> {
+        self.targets
+            .first()
+            .and_then(|t| NaiveDate::parse_from_str(&t.effective_from, "%Y-%m-%d").ok())
+    }
+
+    /// Seconds due on the given day, nothing on holidays and on the weekdays outside the target
+    pub fn target_on(&self, day: NaiveDate) -> i64 {
+        let day_str = format_day(day);
+        if self.holidays.contains(&day_str) {
+            return 0;
+        }
+        let weekday_bit = 1 << day.weekday().num_days_from_monday();
+        self.targets
+            .iter()
+            .rev()
+            .find(|t| t.effective_from <= day_str)
+            .filter(|t| t.weekdays & weekday_bit != 0)
+            .map_or(0, |t| i64::from(t.daily_minutes) * 60)
+    }
+
+    /// Balance of each day and week from the first target to `to`, listing the periods ending from `from` on
+    ///
+    /// `tracked` holds the seconds tracked per day.
+    pub fn report(
+        &self,
+        tracked: &BTreeMap<NaiveDate, i64>,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> OvertimeReport {
+        let mut report = OvertimeReport {
+            days: Vec::new(),
+            weeks: Vec::new(),
+            balance: 0,
+        };
+        let Some(first_day) = self.first_day() else {
+            return report;
+        };
+
+        let mut week: Option<(NaiveDate, PeriodBalance)> = None;
+        for day in first_day.iter_days().take_while(|day| *day <= to) {
+            let target = self.target_on(day);
+            let day_tracked = tracked.get(&day).copied().unwrap_or(0);
+            report.balance += day_tracked - target;
+
+            if day >= from {
+                report.days.push(PeriodBalance {
+                    start: format_day(day),
+                    target,
+                    tracked: day_tracked,
+                    difference: day_tracked - target,
+                    balance: report.balance,
+                });
+            }
+
+            let monday = week_start(day);
+            if week.as_ref().is_some_and(|(start, _)| *start != monday) {
+                report.weeks.extend(week.take().map(|(_, balance)| balance));
+            }
+            let (_, balance) = week.get_or_insert_with(|| {
+                (
+                    monday,
+                    PeriodBalance {
+                        start: format_day(monday),
+                        target: 0,
+                        tracked: 0,
+                        difference: 0,
+                        balance: 0,
+                    },
+                )
+            });
+            balance.target += target;
+            balance.tracked += day_tracked;
+            balance.difference += day_tracked - target;
+            balance.balance = report.balance;
+        }
+        report.weeks.extend(week.map(|(_, balance)| balance));
+
+        let first_week = format_day(week_start(from));
+        report.weeks.retain(|w| w.start >= first_week);
+        report
+    }
+
+    /// Progress of `today` and its week, `None` when no target is set
+    pub fn status(
+        &self,
+        tracked: &BTreeMap<NaiveDate, i64>,
+        today: NaiveDate,
+    ) -> Option<OvertimeStatus> {
+        self.first_day()?;
+        let report = self.report(tracked, week_start(today), today);
+        let day = report.days.last();
+        let week = report.weeks.last();
+        let day_target = day.map_or(0, |d| d.target);
+        let day_tracked = day.map_or(0, |d| d.tracked);
+
+        Some(OvertimeStatus {
+            day_target,
+            day_tracked,
+            week_target: week.map_or(0, |w| w.target),
+            week_tracked: week.map_or(0, |w| w.tracked),
+            balance: report.balance - (day_tracked - day_target),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracker::interval::test_day;
+
+    const HOUR: i64 = 3600;
+
+    fn schedule() -> WorkSchedule {
+        let target =
+            |id: i32, effective_from: &str, weekdays: i32, daily_minutes: i32| WorkTarget {
+                id,
+                effective_from: String::from(effective_from),
+                weekdays,
+                daily_minutes,
+            };
+        WorkSchedule::new(
+            vec![
+                target(2, "2024-03-11", WORKING_DAYS & !0b10000, 8 * 60),
+                target(1, "2024-03-06", WORKING_DAYS, 8 * 60),
+            ],
+            &[Holiday {
+                id: 1,
+                day: String::from("2024-03-08"),
+                description: String::from("Holiday"),
+            }],
+        )
+    }
+
+    #[test]
+    fn test_target_on() {
+        let schedule = schedule();
+        assert_eq!(schedule.first_day(), Some(test_day("2024-03-06")));
+        assert_eq!(schedule.target_on(test_day("2024-03-05")), 0);
+        assert_eq!(schedule.target_on(test_day("2024-03-07")), 8 * HOUR);
+        assert_eq!(schedule.target_on(test_day("2024-03-08")), 0);
+        assert_eq!(schedule.target_on(test_day("2024-03-09")), 0);
+        assert_eq!(schedule.target_on(test_day("2024-03-14")), 8 * HOUR);
+        assert_eq!(schedule.target_on(test_day("2024-03-15")), 0);
+    }
+
+    #[test]
+    fn test_report() {
+        let tracked = BTreeMap::from([
+            (test_day("2024-03-05"), 4 * HOUR),
+            (test_day("2024-03-06"), 9 * HOUR),
+            (test_day("2024-03-07"), 7 * HOUR),
+            (test_day("2024-03-09"), 2 * HOUR),
+            (test_day("2024-03-11"), 8 * HOUR),
+        ]);
+
+        let report = schedule().report(&tracked, test_day("2024-03-07"), test_day("2024-03-11"));
+        assert_eq!(
+            report
+                .days
+                .iter()
+                .map(|d| (d.start.as_str(), d.difference, d.balance))
+                .collect::<Vec<_>>(),
+            vec![
+                ("2024-03-07", -HOUR, 0),
+                ("2024-03-08", 0, 0),
+                ("2024-03-09", 2 * HOUR, 2 * HOUR),
+                ("2024-03-10", 0, 2 * HOUR),
+                ("2024-03-11", 0, 2 * HOUR),
+            ]
+        );
+        assert_eq!(
+            report.weeks,
+            vec![
+                PeriodBalance {
+                    start: String::from("2024-03-04"),
+                    target: 16 * HOUR,
+                    tracked: 18 * HOUR,
+                    difference: 2 * HOUR,
+                    balance: 2 * HOUR,
+                },
+                PeriodBalance {
+                    start: String::from("2024-03-11"),
+                    target: 8 * HOUR,
+                    tracked: 8 * HOUR,
+                    difference: 0,
+                    balance: 2 * HOUR,
+                },
+            ]
+        );
+        assert_eq!(report.balance, 2 * HOUR);
+
+        assert_eq!(
+            schedule().status(&tracked, test_day("2024-03-07")),
+            Some(OvertimeStatus {
+                day_target: 8 * HOUR,
+                day_tracked: 7 * HOUR,
+                week_target: 16 * HOUR,
+                week_tracked: 16 * HOUR,
+                balance: HOUR,
+            })
+        );
+        assert_eq!(
+            WorkSchedule::new(vec![], &[]).status(&tracked, test_day("2024-03-07")),
+            None
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PomodoroPhase {
+    Work,
+    Break,
+}
+
+#[derive(Clone, Debug)]
+pub struct PomodoroSettings {
+    pub task_id: u64,
+    pub work_minutes: u32,
+    pub break_minutes: u32,
+    pub cycles: u32,
+    /// Task to switch to during breaks, when missing breaks are recorded as pauses
+    pub break_task_id: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct PomodoroStatus {
+    task_id: u64,
+    phase: PomodoroPhase,
+    cycle: u32,
+    cycles: u32,
+    completed_cycles: u32,
+    remaining_seconds: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PomodoroTransition {
+    /// A work phase is over and a break begins
+    BreakStarted { started_at: u64, completed_at: u64 },
+    /// A break is over and a new work phase begins
+    WorkStarted,
+    /// The last work phase is over
+    Finished { started_at: u64, completed_at: u64 },
+}
+
+pub struct PomodoroTimer {
+    settings: PomodoroSettings,
+    phase: PomodoroPhase,
+    cycle: u32,
+    completed_cycles: u32,
+    phase_started_at: u64,
+    phase_ends_at: u64,
+}
+
+impl PomodoroTimer {
+    pub fn start(settings: PomodoroSettings, now: u64) -> PomodoroTimer {
+        let phase_ends_at = now + u64::from(settings.work_minutes) * 60;
+        PomodoroTimer {
+            settings,
+            phase: PomodoroPhase::Work,
+            cycle: 1,
+            completed_cycles: 0,
+            phase_started_at: now,
+            phase_ends_at,
+        }
+    }
+
+    pub fn settings(&self) -> &PomodoroSettings {
+        &self.settings
+    }
+
+    pub fn status(&self, now: u64) -> PomodoroStatus {
+        PomodoroStatus {
+            task_id: self.settings.task_id,
+            phase: self.phase,
+            cycle: self.cycle,
+            cycles: self.settings.cycles,
+            completed_cycles: self.completed_cycles,
+            remaining_seconds: self.phase_ends_at.saturating_sub(now),
+        }
+    }
+
+    /// Move to the next phase when the current one is over
+    pub fn advance(&mut self, now: u64) -> Option<PomodoroTransition> {
+        if now < self.phase_ends_at {
+            return None;
+        }
+
+        match self.phase {
+            PomodoroPhase::Work => {
+                let started_at = self.phase_started_at;
+                let completed_at = self.phase_ends_at;
+                self.completed_cycles += 1;
+
+                if self.cycle >= self.settings.cycles {
+                    return Some(PomodoroTransition::Finished {
+                        started_at,
+                        completed_at,
+                    });
+                }
+
+                self.phase = PomodoroPhase::Break;
+                self.phase_started_at = now;
+                self.phase_ends_at = now + u64::from(self.settings.break_minutes) * 60;
+                Some(PomodoroTransition::BreakStarted {
+                    started_at,
+                    completed_at,
+                })
+            }
+            PomodoroPhase::Break => {
+                self.phase = PomodoroPhase::Work;
+                self.cycle += 1;
+                self.phase_started_at = now;
+                self.phase_ends_at = now + u64::from(self.settings.work_minutes) * 60;
+                Some(PomodoroTransition::WorkStarted)
+            }
+        }
+    }
+}
+
+#[test]
+fn test_pomodoro_cycles() {
+    let mut timer = PomodoroTimer::start(
+        PomodoroSettings {
+            task_id: 1,
+            work_minutes: 25,
+            break_minutes: 5,
+            cycles: 2,
+            break_task_id: None,
+        },
+        0,
+    );
+
+    assert_eq!(timer.advance(60), None);
+    assert_eq!(timer.status(60).remaining_seconds, 1440);
+    assert_eq!(
+        timer.advance(1500),
+        Some(PomodoroTransition::BreakStarted {
+            started_at: 0,
+            completed_at: 1500
+        })
+    );
+    assert_eq!(timer.advance(1799), None);
+    assert_eq!(timer.advance(1800), Some(PomodoroTransition::WorkStarted));
+    assert_eq!(
+        timer.advance(3300),
+        Some(PomodoroTransition::Finished {
+            started_at: 1800,
+            completed_at: 3300
+        })
+    );
+    assert_eq!(timer.status(3300).completed_cycles, 2);
+}
@@ -0,0 +1,227 @@
+use std::collections::BTreeMap;
+
+use chrono::{NaiveDate, TimeZone};
+
+use crate::dto::command::{Rounding, RoundingMode, RoundingScope};
+use crate::tracker::interval::{self, Interval};
+
+/// Raw and rounded seconds spent on a task
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Durations {
+    pub raw: i64,
+    pub rounded: i64,
+}
+
+/// Check the rounding before using it, the increment can't be empty
+pub fn validate(rounding: Option<&Rounding>) -> Result<(), String> {
+    match rounding {
+        Some(rounding) if rounding.increment_minutes == 0 => Err(String::from(
+            "Rounding increment must be at least one minute",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Round the seconds to a multiple of the increment
+pub fn round(seconds: i64, increment: i64, mode: RoundingMode) -> i64 {
+    if increment <= 0 {
+        return seconds;
+    }
+    let steps = match mode {
+        RoundingMode::Up => (seconds + increment - 1).div_euclid(increment),
+        RoundingMode::Nearest => (seconds + increment / 2).div_euclid(increment),
+        RoundingMode::Down => seconds.div_euclid(increment),
+    };
+    steps * increment
+}
+
+/// Round the seconds when the rounding applies to the given scope, keep them as they are otherwise
+pub fn round_in_scope(seconds: i64, rounding: Option<&Rounding>, scope: RoundingScope) -> i64 {
+    match rounding {
+        Some(rounding) if rounding.scope == scope => round(
+            seconds,
+            i64::from(rounding.increment_minutes) * 60,
+            rounding.mode,
+        ),
+        _ => seconds,
+    }
+}
+
+/// Raw and rounded time spent per day per task, durations are kept exact without rounding
+///
+/// The intervals are split at local midnight; with the interval scope each interval is rounded as a whole
+/// before the split, filling its days in order so that the rounding difference goes to the last ones.
+pub fn day_durations<Tz: TimeZone>(
+    intervals: &[Interval],
+    tz: &Tz,
+    rounding: Option<&Rounding>,
+) -> BTreeMap<(NaiveDate, String), Durations> {
+    let mut durations: BTreeMap<(NaiveDate, String), Durations> = BTreeMap::new();
+    for interval in intervals {
+        let parts = interval::split_by_day(std::slice::from_ref(interval), tz);
+        let seconds = |part: &Interval| part.end.map_or(0, |end| end - part.start);
+        let raw = parts.iter().map(|(_, part)| seconds(part)).sum();
+        let mut remaining = round_in_scope(raw, rounding, RoundingScope::Interval);
+        let last = parts.len().saturating_sub(1);
+        for (idx, (day, part)) in parts.into_iter().enumerate() {
+            let raw = seconds(&part);
+            let rounded = if idx == last {
+                remaining
+            } else {
+                raw.min(remaining)
+            };
+            remaining -= rounded;
+            let total = durations.entry((day, part.task)).or_default();
+            total.raw += raw;
+            total.rounded += rounded;
+        }
+    }
+    for total in durations.values_mut() {
+        total.rounded = round_in_scope(total.rounded, rounding, RoundingScope::Day);
+    }
+    durations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    const MINUTE: i64 = 60;
+
+    fn rounding(increment_minutes: u32, mode: RoundingMode, scope: RoundingScope) -> Rounding {
+        Rounding {
+            increment_minutes,
+            mode,
+            scope,
+        }
+    }
+
+    /// Two intervals of 7 and 16 minutes on the same task and day, one of 1 minute the next day
+    fn rounded(rounding: Option<Rounding>) -> Vec<(i64, i64)> {
+        let interval = |start: i64, minutes: i64| Interval {
+            task: String::from("dev"),
+            start,
+            end: Some(start + minutes * MINUTE),
+        };
+        let intervals = vec![interval(0, 7), interval(3600, 16), interval(86400, 1)];
+        day_durations(&intervals, &Utc, rounding.as_ref())
+            .values()
+            .map(|d| (d.raw / MINUTE, d.rounded / MINUTE))
+            .collect()
+    }
+
+    #[test]
+    fn test_round() {
+        assert_eq!(round(0, 15 * MINUTE, RoundingMode::Up), 0);
+        assert_eq!(round(1, 15 * MINUTE, RoundingMode::Up), 15 * MINUTE);
+        assert_eq!(
+            round(15 * MINUTE, 15 * MINUTE, RoundingMode::Up),
+            15 * MINUTE
+        );
+        assert_eq!(round(3 * MINUTE - 1, 6 * MINUTE, RoundingMode::Nearest), 0);
+        assert_eq!(
+            round(3 * MINUTE, 6 * MINUTE, RoundingMode::Nearest),
+            6 * MINUTE
+        );
+        assert_eq!(
+            round(59 * MINUTE, 30 * MINUTE, RoundingMode::Down),
+            30 * MINUTE
+        );
+        assert_eq!(round(59, 0, RoundingMode::Down), 59);
+    }
+
+    #[test]
+    fn test_without_rounding() {
+        assert_eq!(rounded(None), vec![(23, 23), (1, 1)]);
+    }
+
+    #[test]
+    fn test_round_up() {
+        use RoundingMode::Up;
+        assert_eq!(
+            rounded(Some(rounding(15, Up, RoundingScope::Interval))),
+            vec![(23, 45), (1, 15)]
+        );
+        assert_eq!(
+            rounded(Some(rounding(15, Up, RoundingScope::Day))),
+            vec![(23, 30), (1, 15)]
+        );
+    }
+
+    #[test]
+    fn test_round_nearest() {
+        use RoundingMode::Nearest;
+        assert_eq!(
+            rounded(Some(rounding(6, Nearest, RoundingScope::Interval))),
+            vec![(23, 24), (1, 0)]
+        );
+        assert_eq!(
+            rounded(Some(rounding(6, Nearest, RoundingScope::Day))),
+            vec![(23, 24), (1, 0)]
+        );
+        assert_eq!(
+            rounded(Some(rounding(30, Nearest, RoundingScope::Interval))),
+            vec![(23, 30), (1, 0)]
+        );
+    }
+
+    #[test]
+    fn test_round_down() {
+        use RoundingMode::Down;
+        assert_eq!(
+            rounded(Some(rounding(15, Down, RoundingScope::Interval))),
+            vec![(23, 15), (1, 0)]
+        );
+        assert_eq!(
+            rounded(Some(rounding(15, Down, RoundingScope::Day))),
+            vec![(23, 15), (1, 0)]
+        );
+        assert_eq!(
+            rounded(Some(rounding(6, Down, RoundingScope::Day))),
+            vec![(23, 18), (1, 0)]
+        );
+    }
+
+    #[test]
+    fn test_round_across_midnight() {
+        use RoundingMode::{Down, Up};
+        let across = |from: i64, minutes: i64, rounding: Rounding| {
+            let interval = Interval {
+                task: String::from("dev"),
+                start: 86400 + from * MINUTE,
+                end: Some(86400 + (from + minutes) * MINUTE),
+            };
+            day_durations(&[interval], &Utc, Some(&rounding))
+                .values()
+                .map(|d| (d.raw / MINUTE, d.rounded / MINUTE))
+                .collect::<Vec<_>>()
+        };
+
+        // The interval is rounded once, not each of its days
+        assert_eq!(
+            across(-10, 15, rounding(15, Up, RoundingScope::Interval)),
+            vec![(10, 10), (5, 5)]
+        );
+        assert_eq!(
+            across(-5, 7, rounding(15, Up, RoundingScope::Interval)),
+            vec![(5, 5), (2, 10)]
+        );
+        assert_eq!(
+            across(-65, 75, rounding(60, Down, RoundingScope::Interval)),
+            vec![(65, 60), (10, 0)]
+        );
+        assert_eq!(
+            across(-10, 15, rounding(15, Up, RoundingScope::Day)),
+            vec![(10, 15), (5, 15)]
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        use RoundingMode::Up;
+        assert!(validate(Some(&rounding(0, Up, RoundingScope::Day))).is_err());
+        assert!(validate(Some(&rounding(6, Up, RoundingScope::Day))).is_ok());
+        assert!(validate(None).is_ok());
+    }
+}
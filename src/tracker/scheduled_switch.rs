@@ -0,0 +1,95 @@
+use chrono::{Datelike, NaiveTime, TimeZone, Timelike};
+use oclock_sqlite::models::ScheduledSwitch;
+
+use crate::dto::command::Weekday;
+
+/// Maximum time span evaluated when catching up with missed switches
+const MAX_CATCH_UP_SECONDS: i64 = 24 * 3600;
+
+/// Bitmask with bit 0 for monday up to bit 6 for sunday, no weekdays means every day
+pub fn weekdays_mask(weekdays: &[Weekday]) -> i32 {
+    if weekdays.is_empty() {
+        return 0b111_1111;
+    }
+
+    weekdays
+        .iter()
+        .map(|weekday| match weekday {
+            Weekday::Monday => 1,
+            Weekday::Tuesday => 1 << 1,
+            Weekday::Wednesday => 1 << 2,
+            Weekday::Thursday => 1 << 3,
+            Weekday::Friday => 1 << 4,
+            Weekday::Saturday => 1 << 5,
+            Weekday::Sunday => 1 << 6,
+        })
+        .fold(0, |mask, day| mask | day)
+}
+
+/// Parse a local time in the `HH:MM` format returning the minutes since midnight
+pub fn parse_minute_of_day(at: &str) -> Result<i32, String> {
+    NaiveTime::parse_from_str(at, "%H:%M")
+        .map(|time| (time.hour() * 60 + time.minute()) as i32)
+        .map_err(|err| format!("Invalid time '{}', expected HH:MM - {}", at, err))
+}
+
+/// Timestamp of the latest minute in `(from, to]` at which the switch is due
+pub fn due_at<Tz: TimeZone>(switch: &ScheduledSwitch, from: i64, to: i64, tz: &Tz) -> Option<i64> {
+    let from = from.max(to - MAX_CATCH_UP_SECONDS);
+
+    (from / 60 + 1..=to / 60)
+        .rev()
+        .map(|minute| minute * 60)
+        .find(|ts| match tz.timestamp_opt(*ts, 0).earliest() {
+            Some(local) => {
+                let minute_of_day = (local.hour() * 60 + local.minute()) as i32;
+                let weekday_bit = 1 << local.weekday().num_days_from_monday();
+                minute_of_day == switch.minute_of_day && switch.weekdays & weekday_bit != 0
+            }
+            None => false,
+        })
+}
+
+#[test]
+fn test_scheduled_switch_due() {
+    use chrono::Utc;
+
+    let standup = ScheduledSwitch {
+        id: 1,
+        task_id: Some(1),
+        minute_of_day: parse_minute_of_day("09:30").unwrap(),
+        weekdays: weekdays_mask(&[
+            Weekday::Monday,
+            Weekday::Tuesday,
+            Weekday::Wednesday,
+            Weekday::Thursday,
+            Weekday::Friday,
+        ]),
+        duration: Some(900),
+    };
+
+    // Thursday 2024-02-15 09:30:00 UTC
+    let thursday = Utc
+        .with_ymd_and_hms(2024, 2, 15, 9, 30, 0)
+        .unwrap()
+        .timestamp();
+    assert_eq!(
+        due_at(&standup, thursday - 60, thursday, &Utc),
+        Some(thursday)
+    );
+    assert_eq!(
+        due_at(&standup, thursday - 60, thursday + 59, &Utc),
+        Some(thursday)
+    );
+    assert_eq!(due_at(&standup, thursday, thursday + 60, &Utc), None);
+    assert_eq!(
+        due_at(&standup, thursday - 3600, thursday + 3600, &Utc),
+        Some(thursday)
+    );
+
+    // Saturday 2024-02-17 09:30:00 UTC
+    let saturday = thursday + 2 * 24 * 3600;
+    assert_eq!(due_at(&standup, saturday - 60, saturday, &Utc), None);
+
+    assert!(parse_minute_of_day("25:00").is_err());
+}
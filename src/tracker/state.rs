@@ -0,0 +1,1489 @@
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Duration, NaiveDate, TimeZone, Timelike};
+use itertools::Itertools;
+use log::debug;
+use oclock_sqlite::backup;
+use oclock_sqlite::connection::{self, Storage};
+use oclock_sqlite::constants::{BudgetPeriod, ContextKind, SystemEventType};
+use oclock_sqlite::dump::{self, Dump, ImportSummary};
+use oclock_sqlite::mappers;
+use oclock_sqlite::mappers::events::EventsFingerprint;
+use oclock_sqlite::models::{
+    ContextRule, ContextSwitchEntry, Event, HistoryEntry, NewContextRule, NewEvent, NewHoliday,
+    NewPomodoroCycle, NewScheduledSwitch, NewTask, NewTaskBudget, NewTaskRate, NewWorkTarget,
+    NewWorkingHours, ScheduledReturn, ScheduledSwitch, Task, TimesheetEntry, WorkingHours,
+};
+use serde::Serialize;
+
+use crate::dto::command::{CalendarRule, ImportMode, Rounding, RoundingScope, Weekday};
+use crate::tracker::backup::{daily_backup_name, expired_backups, KEPT_DAILY_BACKUPS};
+use crate::tracker::billing::{self, BillableEntry, BillableTotal, RateEntry};
+use crate::tracker::budget::{self, BudgetLevel, BudgetStatus};
+use crate::tracker::clock::{Clock, SystemClock};
+use crate::tracker::context_rule;
+use crate::tracker::fsck::{self, FsckReport};
+use crate::tracker::gaps::{self, UntrackedGap};
+use crate::tracker::ical::{self, CalendarImportSummary, IcsEvent};
+use crate::tracker::interval::{self, Interval, IntervalEvent, IntervalImportSummary};
+use crate::tracker::notification::Notification;
+use crate::tracker::overtime::{self, OvertimeReport, OvertimeStatus, WorkSchedule, WorkTargets};
+use crate::tracker::pomodoro::{
+    PomodoroSettings, PomodoroStatus, PomodoroTimer, PomodoroTransition,
+};
+use crate::tracker::rounding;
+use crate::tracker::scheduled_switch;
+
+pub struct State {
+    database: Box<dyn Storage>,
+    clock: Box<dyn Clock>,
+    pomodoro: RefCell<Option<PomodoroTimer>>,
+    /// Last alert level notified for each budget, along with the period it refers to
+    budget_alerts: RefCell<HashMap<i32, (i64, BudgetLevel)>>,
+    schedule_checked_at: Cell<Option<i64>>,
+    /// Seconds tracked on the days before today, reused while the events of those days don't change
+    closed_days: RefCell<Option<ClosedDays>>,
+}
+
+struct ClosedDays {
+    today: NaiveDate,
+    fingerprint: EventsFingerprint,
+    tracked: BTreeMap<NaiveDate, i64>,
+}
+
+#[derive(Serialize)]
+pub struct ExportedState {
+    current_task: Option<Task>,
+    all_tasks: Vec<Task>,
+    pomodoro: Option<PomodoroStatus>,
+    budgets: Vec<BudgetStatus>,
+    overtime: Option<OvertimeStatus>,
+}
+
+/// Part of the exported state changing every second while a pomodoro runs
+#[derive(Serialize)]
+pub struct PomodoroCountdown {
+    pomodoro: Option<PomodoroStatus>,
+}
+
+#[derive(Serialize)]
+pub struct TimesheetPivotRecord {
+    pub day: String,
+    pub entries: Vec<i32>,
+}
+
+fn initialize(database: &dyn Storage) {
+    let mut connection = match database.establish_connection() {
+        Ok(connection) => connection,
+        Err(err) => {
+            log::error!("Error recovering daemon session - {err}");
+            return;
+        }
+    };
+
+    let out = connection::transaction(&mut connection, |conn| {
+        // Events pushed in-process after the daemon shut down don't need any recovery
+        let needs_recovery = match mappers::events::get_last_system_event(conn)? {
+            Some(Event {
+                system_event_name: Some(ref sys_evt),
+                ..
+            }) if sys_evt == &SystemEventType::Shutdown.to_string() => {
+                debug!("Already in correct state");
+                false
+            }
+            Some(_) => {
+                debug!("found non shutdown event");
+                true
+            }
+            None => {
+                debug!("No daemon session found");
+                false
+            }
+        };
+
+        if needs_recovery {
+            let last_event = mappers::events::get_last_event(conn)?;
+            let event = NewEvent {
+                event_timestamp: last_event.event_timestamp,
+                task_id: None,
+                system_event_name: Some(SystemEventType::Shutdown.to_string()),
+                note: None,
+            };
+            mappers::events::push_event(conn, &event)?;
+        }
+
+        mappers::events::remove_all_system_events(conn, SystemEventType::Ping.to_string());
+        Ok(())
+    });
+    if let Err(err) = out {
+        log::error!("Error pushing shut-down event - {err}");
+    }
+}
+
+impl State {
+    /// Open the database in the given directory recovering from an unclean daemon shutdown
+    pub fn new(cfg_path: String) -> Result<State, String> {
+        let state = State::open(format!("{}/oclock.db", cfg_path))?;
+        state.recover();
+        Ok(state)
+    }
+
+    pub fn open(database_path: String) -> Result<State, String> {
+        State::open_with_clock(database_path, Box::new(SystemClock))
+    }
+
+    pub fn open_with_clock(database_path: String, clock: Box<dyn Clock>) -> Result<State, String> {
+        Ok(State::with_storage(
+            connection::open(&database_path)?,
+            clock,
+        ))
+    }
+
+    pub fn with_storage(database: Box<dyn Storage>, clock: Box<dyn Clock>) -> State {
+        State {
+            database,
+            clock,
+            pomodoro: RefCell::new(None),
+            budget_alerts: RefCell::new(HashMap::new()),
+            schedule_checked_at: Cell::new(None),
+            closed_days: RefCell::new(None),
+        }
+    }
+
+    /// Close the session left open by a daemon that didn't shut down cleanly
+    pub fn recover(&self) {
+        initialize(self.database.as_ref());
+    }
+
+    pub fn new_task(&self, name: String) -> Result<serde_json::Value, String> {
+        let new_task = NewTask { name };
+
+        let mut connection = self.database.establish_connection()?;
+
+        match mappers::tasks::create_task(&mut connection, &new_task) {
+            Ok(task_id) => Result::Ok(serde_json::Value::String(format!(
+                "New task id '{}'",
+                task_id
+            ))),
+            Err(err) => Result::Err(format!("Error during task insert '{}'", err)),
+        }
+    }
+
+    pub fn switch_task(&self, id: u64) -> Result<serde_json::Value, String> {
+        self.switch_task_with_note(id, None)
+    }
+
+    /// Switch to the task with the given id, describing what is being done on it with the note
+    pub fn switch_task_with_note(
+        &self,
+        id: u64,
+        note: Option<String>,
+    ) -> Result<serde_json::Value, String> {
+        let unix_now = self.clock.unix_now();
+
+        let mut connection = self.database.establish_connection()?;
+
+        let event = NewEvent {
+            event_timestamp: unix_now as i32,
+            task_id: Some(id as i32),
+            system_event_name: None,
+            note: note.filter(|note| !note.trim().is_empty()),
+        };
+
+        match mappers::events::push_event(&mut connection, &event) {
+            Ok(evt_id) => Result::Ok(serde_json::Value::String(format!(
+                "New event id '{}'",
+                evt_id
+            ))),
+            Err(err) => Result::Err(format!("Error during task switch '{}'", err)),
+        }
+    }
+
+    pub fn system_event(&self, evt: SystemEventType) -> Result<String, String> {
+        let unix_now = self.clock.unix_now();
+
+        let mut connection = self.database.establish_connection()?;
+
+        let event = NewEvent {
+            event_timestamp: unix_now as i32,
+            task_id: None,
+            system_event_name: Some(evt.to_string()),
+            note: None,
+        };
+
+        match mappers::events::push_event(&mut connection, &event) {
+            Ok(evt_id) => Result::Ok(format!("New event id '{}'", evt_id)),
+            Err(err) => Result::Err(format!("Error inserting system event '{}'", err)),
+        }
+    }
+
+    pub fn ping(&self) {
+        let unix_now = self.clock.unix_now();
+        let mut connection = match self.database.establish_connection() {
+            Ok(connection) => connection,
+            Err(err) => {
+                log::error!("Error pinging - {err}");
+                return;
+            }
+        };
+
+        mappers::events::move_system_event(
+            &mut connection,
+            unix_now as i32,
+            SystemEventType::Ping.to_string(),
+        )
+    }
+
+    pub fn list_tasks(&self) -> Result<Vec<Task>, String> {
+        let mut connection = self.database.establish_connection()?;
+        match mappers::tasks::list_tasks(&mut connection) {
+            Ok(v) => Ok(v),
+            Err(e) => Err(format!("Error retrieving tasks list: '{}'", e)),
+        }
+    }
+
+    /// Time spent per day on each task, eventually rounding the durations
+    pub fn full_timesheet(
+        &self,
+        rounding: Option<&Rounding>,
+    ) -> Result<(Vec<String>, Vec<TimesheetPivotRecord>), String> {
+        rounding::validate(rounding)?;
+        let mut connection = self.database.establish_connection()?;
+        let timesheet = match rounding {
+            // The timesheet view sums the intervals of a day, each of them has to be rounded first
+            Some(rounding) if rounding.scope == RoundingScope::Interval => {
+                mappers::history::full_history(&mut connection)
+                    .map(|history| self.rounded_timesheet_entries(history, rounding))
+            }
+            _ => mappers::timesheet::full_timesheet(&mut connection),
+        };
+        match timesheet {
+            Ok(v) => {
+                let mut timesheet_tasks: Vec<Option<i32>> = v.iter().map(|vi| vi.task_id).collect();
+
+                timesheet_tasks.sort();
+                timesheet_tasks.dedup();
+
+                let res = v
+                    .iter()
+                    .chunk_by(|vi| vi.day.clone())
+                    .into_iter()
+                    .map(|(day, records)| {
+                        let day_tasks: Vec<&TimesheetEntry> = records.collect();
+
+                        TimesheetPivotRecord {
+                            day,
+                            entries: timesheet_tasks
+                                .iter()
+                                .map(|task_id| {
+                                    // Untracked time comes in a row per system event
+                                    day_tasks
+                                        .iter()
+                                        .filter(|r| r.task_id == *task_id)
+                                        .map(|r| r.amount)
+                                        .sum::<i32>()
+                                })
+                                .map(|amount| {
+                                    rounding::round_in_scope(
+                                        i64::from(amount),
+                                        rounding,
+                                        RoundingScope::Day,
+                                    ) as i32
+                                })
+                                .collect(),
+                        }
+                    })
+                    .collect();
+
+                let task_names: Vec<String> = timesheet_tasks
+                    .iter()
+                    .map(
+                        |ref task_name| match v.iter().find(|&r| &&r.task_id == task_name) {
+                            Some(&TimesheetEntry {
+                                task_name: Some(ref task_name),
+                                ..
+                            }) => String::from(task_name),
+                            _ => "NONE".to_string(),
+                        },
+                    )
+                    .collect();
+
+                Ok((task_names, res))
+            }
+            Err(e) => Err(format!("Error generating timesheet: '{}'", e)),
+        }
+    }
+
+    /// Timesheet entry of each interval, like the ones of the timesheet view, with the rounded duration
+    fn rounded_timesheet_entries(
+        &self,
+        history: Vec<HistoryEntry>,
+        rounding: &Rounding,
+    ) -> Vec<TimesheetEntry> {
+        let tz = self.clock.now().timezone();
+        let counted_events = [
+            SystemEventType::Startup.to_string(),
+            SystemEventType::Pause.to_string(),
+        ];
+        history
+            .into_iter()
+            .filter(|h| {
+                h.system_event
+                    .as_ref()
+                    .is_none_or(|event| counted_events.contains(event))
+            })
+            .filter_map(|h| {
+                let seconds = i64::from(h.ts_end? - h.ts_start);
+                let day = tz
+                    .timestamp_opt(i64::from(h.ts_start), 0)
+                    .earliest()?
+                    .format("%Y-%m-%d")
+                    .to_string();
+                Some(TimesheetEntry {
+                    id: h.id,
+                    day,
+                    task_name: h.task_name,
+                    task_id: h.task_id,
+                    system_event: h.system_event,
+                    amount: rounding::round_in_scope(
+                        seconds,
+                        Some(rounding),
+                        RoundingScope::Interval,
+                    ) as i32,
+                })
+            })
+            .collect()
+    }
+
+    pub fn change_task_enabled_flag(
+        &self,
+        id: u64,
+        enabled: bool,
+    ) -> Result<serde_json::Value, String> {
+        let mut connection = self.database.establish_connection()?;
+        match mappers::tasks::change_enabled(&mut connection, id as i32, enabled) {
+            Ok(_) => Ok(serde_json::Value::String(format!(
+                "Task {} enabled: {}",
+                id, enabled
+            ))),
+            Err(e) => Err(format!("Error switching task enabled flag: '{}'", e)),
+        }
+    }
+
+    pub fn get_current_task(&self) -> Result<Option<Task>, String> {
+        let mut connection = self.database.establish_connection()?;
+        match mappers::events::current_task(&mut connection) {
+            Ok(t) => Ok(t),
+            Err(e) => Err(format!("Error while fetching last task switch '{}'", e)),
+        }
+    }
+
+    pub fn get_state(&self) -> Result<ExportedState, String> {
+        let unix_now = self.clock.unix_now();
+
+        Ok(ExportedState {
+            current_task: self.get_current_task()?,
+            all_tasks: self.list_tasks()?,
+            pomodoro: self
+                .pomodoro
+                .borrow()
+                .as_ref()
+                .map(|timer| timer.status(unix_now as u64)),
+            budgets: self.budget_statuses()?,
+            overtime: self.overtime_status()?,
+        })
+    }
+
+    pub fn set_task_budget(
+        &self,
+        task_id: u64,
+        period: BudgetPeriod,
+        amount: u32,
+        warning_percent: u32,
+    ) -> Result<(), String> {
+        let mut connection = self.database.establish_connection()?;
+
+        let budget = NewTaskBudget {
+            task_id: task_id as i32,
+            period: period.to_string(),
+            amount: amount as i32,
+            warning_percent: warning_percent as i32,
+        };
+
+        match mappers::budgets::set_budget(&mut connection, &budget) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Error setting task budget '{}'", err)),
+        }
+    }
+
+    pub fn remove_task_budget(&self, task_id: u64, period: BudgetPeriod) -> Result<(), String> {
+        let mut connection = self.database.establish_connection()?;
+
+        match mappers::budgets::remove_budget(&mut connection, task_id as i32, period.to_string()) {
+            Ok(0) => Err(format!("No {} budget found for task {}", period, task_id)),
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Error removing task budget '{}'", err)),
+        }
+    }
+
+    pub fn budget_statuses(&self) -> Result<Vec<BudgetStatus>, String> {
+        let now = self.clock.now();
+        let tasks = self.list_tasks()?;
+        let mut connection = self.database.establish_connection()?;
+
+        let budgets = match mappers::budgets::list_budgets(&mut connection) {
+            Ok(budgets) => budgets,
+            Err(e) => return Err(format!("Error retrieving budgets list: '{}'", e)),
+        };
+
+        budgets
+            .into_iter()
+            .map(|b| {
+                let period: BudgetPeriod = b.period.parse()?;
+                let period_start = budget::period_start(period, &now);
+                let history = mappers::history::task_history_since(
+                    &mut connection,
+                    b.task_id,
+                    period_start as i32,
+                )
+                .map_err(|e| format!("Error retrieving task history: '{}'", e))?;
+                let spent = budget::spent_since(&history, period_start);
+
+                Ok(BudgetStatus {
+                    budget_id: b.id,
+                    task_id: b.task_id,
+                    task_name: tasks
+                        .iter()
+                        .find(|t| t.id == b.task_id)
+                        .map(|t| t.name.clone())
+                        .unwrap_or_default(),
+                    period,
+                    period_start,
+                    amount: b.amount,
+                    spent,
+                    remaining: b.amount - spent,
+                    level: budget::budget_level(b.amount, spent, b.warning_percent),
+                })
+            })
+            .collect()
+    }
+
+    pub fn set_task_rate(
+        &self,
+        task_id: u64,
+        hourly_rate: &str,
+        currency: &str,
+        effective_from: Option<&str>,
+    ) -> Result<(), String> {
+        let currency = currency.trim().to_uppercase();
+        if currency.is_empty() {
+            return Err(String::from("Missing rate currency"));
+        }
+        let effective_from =
+            interval::parse_day(effective_from.unwrap_or(billing::ALWAYS_EFFECTIVE))?;
+
+        let rate = NewTaskRate {
+            task_id: task_id as i32,
+            hourly_rate: billing::parse_amount(hourly_rate)?,
+            currency,
+            effective_from: effective_from.format("%Y-%m-%d").to_string(),
+        };
+
+        let mut connection = self.database.establish_connection()?;
+        match mappers::rates::set_rate(&mut connection, &rate) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Error setting task rate '{}'", err)),
+        }
+    }
+
+    pub fn remove_task_rate(
+        &self,
+        task_id: u64,
+        effective_from: Option<&str>,
+    ) -> Result<(), String> {
+        let effective_from =
+            interval::parse_day(effective_from.unwrap_or(billing::ALWAYS_EFFECTIVE))?
+                .format("%Y-%m-%d")
+                .to_string();
+        let mut connection = self.database.establish_connection()?;
+
+        match mappers::rates::remove_rate(&mut connection, task_id as i32, effective_from.clone()) {
+            Ok(0) => Err(format!(
+                "No rate effective from {} found for task {}",
+                effective_from, task_id
+            )),
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Error removing task rate '{}'", err)),
+        }
+    }
+
+    pub fn list_task_rates(&self) -> Result<Vec<RateEntry>, String> {
+        let tasks = self.list_tasks()?;
+        let mut connection = self.database.establish_connection()?;
+        let rates = mappers::rates::list_rates(&mut connection)
+            .map_err(|e| format!("Error retrieving rates list: '{}'", e))?;
+
+        Ok(rates
+            .into_iter()
+            .map(|r| RateEntry {
+                rate_id: r.id,
+                task_id: r.task_id,
+                task_name: tasks
+                    .iter()
+                    .find(|t| t.id == r.task_id)
+                    .map(|t| t.name.clone())
+                    .unwrap_or_default(),
+                hourly_rate: billing::format_amount(i64::from(r.hourly_rate)),
+                currency: r.currency,
+                effective_from: r.effective_from,
+            })
+            .collect())
+    }
+
+    /// Time spent per day per task between the given days (inclusive), valued with the task rates
+    pub fn billable_timesheet(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        rounding: Option<&Rounding>,
+    ) -> Result<(Vec<BillableEntry>, Vec<BillableTotal>), String> {
+        rounding::validate(rounding)?;
+        let tz = self.clock.now().timezone();
+        let intervals = self.tracked_intervals(from, to)?;
+        let task_ids: BTreeMap<String, i32> = self
+            .list_tasks()?
+            .into_iter()
+            .map(|t| (t.name, t.id))
+            .collect();
+
+        let mut connection = self.database.establish_connection()?;
+        let rates = mappers::rates::list_rates(&mut connection)
+            .map_err(|e| format!("Error retrieving rates list: '{}'", e))?;
+
+        let durations = rounding::day_durations(&intervals, &tz, rounding);
+        let entries = billing::billable_entries(durations, &task_ids, &rates);
+        let totals = billing::billable_totals(&entries);
+        Ok((entries, totals))
+    }
+
+    pub fn set_work_target(
+        &self,
+        daily_minutes: u32,
+        weekdays: &[Weekday],
+        effective_from: &str,
+    ) -> Result<(), String> {
+        let target = NewWorkTarget {
+            effective_from: interval::parse_day(effective_from)?
+                .format("%Y-%m-%d")
+                .to_string(),
+            weekdays: if weekdays.is_empty() {
+                overtime::WORKING_DAYS
+            } else {
+                scheduled_switch::weekdays_mask(weekdays)
+            },
+            daily_minutes: daily_minutes as i32,
+        };
+
+        let mut connection = self.database.establish_connection()?;
+        match mappers::work_targets::set_work_target(&mut connection, &target) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Error setting work target '{}'", err)),
+        }
+    }
+
+    pub fn remove_work_target(&self, effective_from: &str) -> Result<(), String> {
+        let effective_from = interval::parse_day(effective_from)?
+            .format("%Y-%m-%d")
+            .to_string();
+        let mut connection = self.database.establish_connection()?;
+
+        match mappers::work_targets::remove_work_target(&mut connection, effective_from.clone()) {
+            Ok(0) => Err(format!(
+                "No work target effective from {} found",
+                effective_from
+            )),
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Error removing work target '{}'", err)),
+        }
+    }
+
+    pub fn add_holiday(&self, day: &str, description: Option<&str>) -> Result<(), String> {
+        let holiday = NewHoliday {
+            day: interval::parse_day(day)?.format("%Y-%m-%d").to_string(),
+            description: String::from(description.unwrap_or_default()),
+        };
+
+        let mut connection = self.database.establish_connection()?;
+        match mappers::work_targets::set_holiday(&mut connection, &holiday) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Error adding holiday '{}'", err)),
+        }
+    }
+
+    pub fn remove_holiday(&self, day: &str) -> Result<(), String> {
+        let day = interval::parse_day(day)?.format("%Y-%m-%d").to_string();
+        let mut connection = self.database.establish_connection()?;
+
+        match mappers::work_targets::remove_holiday(&mut connection, day.clone()) {
+            Ok(0) => Err(format!("No holiday found on {}", day)),
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Error removing holiday '{}'", err)),
+        }
+    }
+
+    pub fn list_work_targets(&self) -> Result<WorkTargets, String> {
+        let mut connection = self.database.establish_connection()?;
+        let targets = mappers::work_targets::list_work_targets(&mut connection)
+            .map_err(|e| format!("Error retrieving work targets list: '{}'", e))?;
+        let holidays = mappers::work_targets::list_holidays(&mut connection)
+            .map_err(|e| format!("Error retrieving holidays list: '{}'", e))?;
+        Ok(WorkTargets { targets, holidays })
+    }
+
+    /// Work schedule along with the seconds tracked each day from its first target to today
+    ///
+    /// The days before today are computed again only when their events change, today is always computed.
+    fn work_balance(&self) -> Result<(WorkSchedule, BTreeMap<NaiveDate, i64>), String> {
+        let WorkTargets { targets, holidays } = self.list_work_targets()?;
+        let schedule = WorkSchedule::new(targets, &holidays);
+        let Some(first_day) = schedule.first_day() else {
+            return Ok((schedule, BTreeMap::new()));
+        };
+
+        let now = self.clock.now();
+        let tz = now.timezone();
+        let today = now.date_naive();
+        let fingerprint = {
+            let mut connection = self.database.establish_connection()?;
+            mappers::events::events_fingerprint(
+                &mut connection,
+                interval::day_start(today, &tz) as i32,
+            )
+            .map_err(|e| format!("Error retrieving events summary: '{}'", e))?
+        };
+
+        let cached = self
+            .closed_days
+            .borrow()
+            .as_ref()
+            .filter(|closed| closed.today == today && closed.fingerprint == fingerprint)
+            .map(|closed| closed.tracked.clone());
+        let mut tracked = match cached {
+            Some(tracked) => tracked,
+            None => {
+                let tracked = match today.pred_opt() {
+                    Some(yesterday) if first_day <= yesterday => {
+                        self.tracked_per_day(first_day, yesterday)?
+                    }
+                    _ => BTreeMap::new(),
+                };
+                self.closed_days.replace(Some(ClosedDays {
+                    today,
+                    fingerprint,
+                    tracked: tracked.clone(),
+                }));
+                tracked
+            }
+        };
+        tracked.extend(self.tracked_per_day(today, today)?);
+        Ok((schedule, tracked))
+    }
+
+    /// Seconds tracked each day between the given days (inclusive)
+    fn tracked_per_day(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<BTreeMap<NaiveDate, i64>, String> {
+        let tz = self.clock.now().timezone();
+        let mut tracked: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+        for (day, part) in interval::split_by_day(&self.tracked_intervals(from, to)?, &tz) {
+            *tracked.entry(day).or_default() += part.end.map_or(0, |end| end - part.start);
+        }
+        Ok(tracked)
+    }
+
+    /// Over and under time of the days between `from` and `to` (inclusive), by default from the first
+    /// target to today
+    pub fn overtime_report(
+        &self,
+        from: Option<NaiveDate>,
+        to: Option<NaiveDate>,
+    ) -> Result<OvertimeReport, String> {
+        let (schedule, tracked) = self.work_balance()?;
+        let first_day = schedule
+            .first_day()
+            .ok_or_else(|| String::from("No work target found"))?;
+        let to = to.unwrap_or_else(|| self.clock.now().date_naive());
+        Ok(schedule.report(&tracked, from.unwrap_or(first_day), to))
+    }
+
+    pub fn overtime_status(&self) -> Result<Option<OvertimeStatus>, String> {
+        let (schedule, tracked) = self.work_balance()?;
+        Ok(schedule.status(&tracked, self.clock.now().date_naive()))
+    }
+
+    pub fn add_working_hours(
+        &self,
+        weekdays: &[Weekday],
+        start: &str,
+        end: &str,
+    ) -> Result<(), String> {
+        let hours = NewWorkingHours {
+            weekdays: if weekdays.is_empty() {
+                overtime::WORKING_DAYS
+            } else {
+                scheduled_switch::weekdays_mask(weekdays)
+            },
+            start_minute: scheduled_switch::parse_minute_of_day(start)?,
+            end_minute: scheduled_switch::parse_minute_of_day(end)?,
+        };
+        if hours.end_minute <= hours.start_minute {
+            return Err(format!(
+                "Working hours must end after they start, got {}-{}",
+                start, end
+            ));
+        }
+
+        let mut connection = self.database.establish_connection()?;
+        match mappers::working_hours::create_working_hours(&mut connection, &hours) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Error adding working hours '{}'", err)),
+        }
+    }
+
+    pub fn remove_working_hours(&self, id: u64) -> Result<(), String> {
+        let mut connection = self.database.establish_connection()?;
+
+        match mappers::working_hours::remove_working_hours(&mut connection, id as i32) {
+            Ok(0) => Err(format!("No working hours found with id {}", id)),
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Error removing working hours '{}'", err)),
+        }
+    }
+
+    pub fn list_working_hours(&self) -> Result<Vec<WorkingHours>, String> {
+        let mut connection = self.database.establish_connection()?;
+        match mappers::working_hours::list_working_hours(&mut connection) {
+            Ok(v) => Ok(v),
+            Err(e) => Err(format!("Error retrieving working hours list: '{}'", e)),
+        }
+    }
+
+    /// Parts of the working hours between the given days (inclusive) without any active task, up to now
+    pub fn untracked_gaps(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+        min_minutes: u32,
+    ) -> Result<Vec<UntrackedGap>, String> {
+        let hours = self.list_working_hours()?;
+        if hours.is_empty() {
+            return Err(String::from("No working hours found"));
+        }
+        let tz = self.clock.now().timezone();
+        let holidays = self.list_work_targets()?.holidays;
+        let periods = gaps::working_periods(&hours, &holidays, from, to, &tz);
+
+        let since = interval::day_start(from, &tz);
+        let until = interval::day_start(to + Duration::days(1), &tz);
+        let mut connection = self.database.establish_connection()?;
+        let history =
+            mappers::history::history_between(&mut connection, since as i32, until as i32)
+                .map_err(|e| format!("Error retrieving history: '{}'", e))?;
+
+        Ok(gaps::untracked_gaps(
+            &periods,
+            &history,
+            self.clock.unix_now(),
+            i64::from(min_minutes) * 60,
+        ))
+    }
+
+    /// Notifications for the budgets whose level increased since the last check
+    pub fn check_budgets(&self) -> Result<Vec<Notification>, String> {
+        let mut budget_alerts = self.budget_alerts.borrow_mut();
+
+        Ok(self
+            .budget_statuses()?
+            .into_iter()
+            .filter(|status| {
+                let notified_level = match budget_alerts.get(&status.budget_id) {
+                    Some((period_start, level)) if *period_start == status.period_start => *level,
+                    _ => BudgetLevel::Ok,
+                };
+                budget_alerts.insert(status.budget_id, (status.period_start, status.level));
+                status.level > notified_level
+            })
+            .map(Notification::BudgetAlert)
+            .collect())
+    }
+
+    pub fn start_pomodoro(&self, settings: PomodoroSettings) -> Result<(), String> {
+        if settings.cycles == 0 || settings.work_minutes == 0 {
+            return Err(String::from(
+                "Pomodoro requires at least one cycle of one minute",
+            ));
+        }
+
+        self.switch_task(settings.task_id)?;
+
+        let unix_now = self.clock.unix_now();
+
+        *self.pomodoro.borrow_mut() = Some(PomodoroTimer::start(settings, unix_now as u64));
+        Ok(())
+    }
+
+    pub fn stop_pomodoro(&self) -> Result<(), String> {
+        match self.pomodoro.borrow_mut().take() {
+            Some(_) => Ok(()),
+            None => Err(String::from("No pomodoro is running")),
+        }
+    }
+
+    pub fn has_pomodoro(&self) -> bool {
+        self.pomodoro.borrow().is_some()
+    }
+
+    /// Remaining time of the running pomodoro, without the rest of the state
+    pub fn pomodoro_countdown(&self) -> PomodoroCountdown {
+        let unix_now = self.clock.unix_now();
+        PomodoroCountdown {
+            pomodoro: self
+                .pomodoro
+                .borrow()
+                .as_ref()
+                .map(|timer| timer.status(unix_now as u64)),
+        }
+    }
+
+    /// Advance the running pomodoro, `true` when a phase is over and the task was switched
+    pub fn pomodoro_tick(&self) -> Result<bool, String> {
+        let unix_now = self.clock.unix_now();
+
+        let mut pomodoro = self.pomodoro.borrow_mut();
+        let (settings, transition) = match pomodoro.as_mut() {
+            Some(timer) => match timer.advance(unix_now as u64) {
+                Some(transition) => (timer.settings().clone(), transition),
+                None => return Ok(false),
+            },
+            None => return Ok(false),
+        };
+
+        let switched = match transition {
+            PomodoroTransition::BreakStarted {
+                started_at,
+                completed_at,
+            } => {
+                self.push_pomodoro_cycle(settings.task_id, started_at, completed_at)?;
+                match settings.break_task_id {
+                    Some(break_task_id) => self.switch_task(break_task_id).map(|_| ()),
+                    None => self.system_event(SystemEventType::Pause).map(|_| ()),
+                }
+            }
+            PomodoroTransition::WorkStarted => self.switch_task(settings.task_id).map(|_| ()),
+            PomodoroTransition::Finished {
+                started_at,
+                completed_at,
+            } => {
+                *pomodoro = None;
+                self.push_pomodoro_cycle(settings.task_id, started_at, completed_at)
+            }
+        };
+        switched.map(|_| true)
+    }
+
+    fn push_pomodoro_cycle(
+        &self,
+        task_id: u64,
+        started_at: u64,
+        completed_at: u64,
+    ) -> Result<(), String> {
+        let mut connection = self.database.establish_connection()?;
+
+        let cycle = NewPomodoroCycle {
+            task_id: task_id as i32,
+            started_at: started_at as i32,
+            completed_at: completed_at as i32,
+        };
+
+        match mappers::pomodoro::push_cycle(&mut connection, &cycle) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Error recording pomodoro cycle '{}'", err)),
+        }
+    }
+
+    pub fn retro_switch_task(
+        &self,
+        task_id: i32,
+        timestamp: i32,
+        keep_prev_task: bool,
+    ) -> Result<String, String> {
+        let unix_now = self.clock.unix_now();
+
+        let mut connection = self.database.establish_connection()?;
+
+        let out = connection::transaction(&mut connection, |conn| {
+            let opt_prev_task = match keep_prev_task {
+                true => mappers::events::current_task(conn)?,
+                false => None,
+            };
+
+            let event = NewEvent {
+                event_timestamp: timestamp,
+                task_id: Some(task_id),
+                system_event_name: None,
+                note: None,
+            };
+            mappers::events::push_event(conn, &event)?;
+
+            match opt_prev_task {
+                Some(prev_task) => {
+                    let redo_prev_task_evt = NewEvent {
+                        event_timestamp: unix_now as i32,
+                        task_id: Some(prev_task.id),
+                        system_event_name: None,
+                        note: None,
+                    };
+
+                    mappers::events::push_event(conn, &redo_prev_task_evt)
+                        .map(|evt_id| format!("New event id '{}'", evt_id))
+                }
+                None => Ok("OK".to_string()),
+            }
+        });
+
+        out.map_err(|err| format!("Error during task switch '{}'", err))
+    }
+
+    pub fn add_scheduled_switch(
+        &self,
+        task_id: Option<u64>,
+        at: &str,
+        weekdays: &[Weekday],
+        duration: Option<u32>,
+    ) -> Result<(), String> {
+        let switch = NewScheduledSwitch {
+            task_id: task_id.map(|id| id as i32),
+            minute_of_day: scheduled_switch::parse_minute_of_day(at)?,
+            weekdays: scheduled_switch::weekdays_mask(weekdays),
+            duration: duration.map(|d| d as i32),
+        };
+
+        let mut connection = self.database.establish_connection()?;
+
+        match mappers::scheduled_switches::create_scheduled_switch(&mut connection, &switch) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Error creating scheduled switch '{}'", err)),
+        }
+    }
+
+    pub fn remove_scheduled_switch(&self, id: u64) -> Result<(), String> {
+        let mut connection = self.database.establish_connection()?;
+
+        match mappers::scheduled_switches::remove_scheduled_switch(&mut connection, id as i32) {
+            Ok(0) => Err(format!("No scheduled switch found with id {}", id)),
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Error removing scheduled switch '{}'", err)),
+        }
+    }
+
+    pub fn list_scheduled_switches(&self) -> Result<Vec<ScheduledSwitch>, String> {
+        let mut connection = self.database.establish_connection()?;
+        match mappers::scheduled_switches::list_scheduled_switches(&mut connection) {
+            Ok(v) => Ok(v),
+            Err(e) => Err(format!("Error retrieving scheduled switches list: '{}'", e)),
+        }
+    }
+
+    /// Execute the scheduled switches due since the last check, returning whether the current task changed
+    pub fn run_scheduled_switches(&self) -> Result<bool, String> {
+        let now = self.clock.now();
+        let unix_now = now.timestamp();
+        let checked_at = self
+            .schedule_checked_at
+            .replace(Some(unix_now))
+            .unwrap_or(unix_now - 60);
+
+        let mut changed = false;
+
+        // The pending return is stored in the database, a restarted daemon still goes back to the previous task
+        let scheduled_return = {
+            let mut connection = self.database.establish_connection()?;
+            mappers::scheduled_switches::get_scheduled_return(&mut connection)
+                .map_err(|e| format!("Error retrieving scheduled return: '{}'", e))?
+        };
+        if let Some(scheduled_return) =
+            scheduled_return.filter(|r| i64::from(r.return_at) <= unix_now)
+        {
+            let mut connection = self.database.establish_connection()?;
+            mappers::scheduled_switches::clear_scheduled_return(&mut connection)
+                .map_err(|e| format!("Error clearing scheduled return: '{}'", e))?;
+            drop(connection);
+
+            // Revert only when the user didn't switch to another task in the meantime
+            let current_task_id = self.get_current_task()?.map(|t| t.id);
+            if current_task_id == scheduled_return.task_id {
+                self.push_switch_event(
+                    i64::from(scheduled_return.return_at),
+                    scheduled_return.previous_task_id,
+                )?;
+                changed = true;
+            }
+        }
+
+        for switch in self.list_scheduled_switches()? {
+            if let Some(due) =
+                scheduled_switch::due_at(&switch, checked_at, unix_now, &now.timezone())
+            {
+                let previous_task_id = self.get_current_task()?.map(|t| t.id);
+                self.push_switch_event(due, switch.task_id)?;
+                if let Some(duration) = switch.duration {
+                    let scheduled_return = ScheduledReturn {
+                        switch_id: switch.id,
+                        task_id: switch.task_id,
+                        return_at: (due + i64::from(duration)) as i32,
+                        previous_task_id,
+                    };
+                    let mut connection = self.database.establish_connection()?;
+                    mappers::scheduled_switches::set_scheduled_return(
+                        &mut connection,
+                        &scheduled_return,
+                    )
+                    .map_err(|e| format!("Error storing scheduled return: '{}'", e))?;
+                }
+                changed = true;
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// Switch to the given task at the given time, no task means tracking is paused
+    fn push_switch_event(&self, timestamp: i64, task_id: Option<i32>) -> Result<(), String> {
+        let mut connection = self.database.establish_connection()?;
+
+        let event = NewEvent {
+            event_timestamp: timestamp as i32,
+            task_id,
+            system_event_name: match task_id {
+                Some(_) => None,
+                None => Some(SystemEventType::Pause.to_string()),
+            },
+            note: None,
+        };
+
+        match mappers::events::push_event(&mut connection, &event) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Error during task switch '{}'", err)),
+        }
+    }
+
+    pub fn add_context_rule(
+        &self,
+        kind: ContextKind,
+        pattern: String,
+        task_id: u64,
+    ) -> Result<(), String> {
+        context_rule::validate_pattern(&pattern)?;
+
+        let rule = NewContextRule {
+            context_kind: kind.to_string(),
+            pattern,
+            task_id: task_id as i32,
+        };
+
+        let mut connection = self.database.establish_connection()?;
+
+        match mappers::context::create_rule(&mut connection, &rule) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Error creating context rule '{}'", err)),
+        }
+    }
+
+    pub fn remove_context_rule(&self, id: u64) -> Result<(), String> {
+        let mut connection = self.database.establish_connection()?;
+
+        match mappers::context::remove_rule(&mut connection, id as i32) {
+            Ok(0) => Err(format!("No context rule found with id {}", id)),
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Error removing context rule '{}'", err)),
+        }
+    }
+
+    pub fn list_context_rules(&self) -> Result<Vec<ContextRule>, String> {
+        let mut connection = self.database.establish_connection()?;
+        match mappers::context::list_rules(&mut connection) {
+            Ok(v) => Ok(v),
+            Err(e) => Err(format!("Error retrieving context rules list: '{}'", e)),
+        }
+    }
+
+    pub fn list_context_switches(&self) -> Result<Vec<ContextSwitchEntry>, String> {
+        let mut connection = self.database.establish_connection()?;
+        match mappers::context::list_context_switches(&mut connection) {
+            Ok(v) => Ok(v),
+            Err(e) => Err(format!("Error retrieving context switches list: '{}'", e)),
+        }
+    }
+
+    /// Switch to the task of the first rule matching the context, returning whether the current task changed
+    pub fn context_event(&self, kind: ContextKind, value: &str) -> Result<bool, String> {
+        let rules = self.list_context_rules()?;
+        let rule = match context_rule::find_matching_rule(&rules, kind, value) {
+            Some(rule) => rule,
+            None => return Ok(false),
+        };
+
+        if self.get_current_task()?.map(|t| t.id) == Some(rule.task_id) {
+            return Ok(false);
+        }
+
+        let unix_now = self.clock.unix_now();
+
+        let mut connection = self.database.establish_connection()?;
+
+        let event = NewEvent {
+            event_timestamp: unix_now as i32,
+            task_id: Some(rule.task_id),
+            system_event_name: None,
+            note: None,
+        };
+
+        match mappers::context::push_context_switch(&mut connection, &event, rule, value) {
+            Ok(_) => Ok(true),
+            Err(err) => Err(format!("Error during context task switch '{}'", err)),
+        }
+    }
+
+    pub fn backup(&self, path: &Path) -> Result<(), String> {
+        backup::backup_into(self.database.database_url(), path)
+    }
+
+    pub fn restore(&self, path: &Path) -> Result<(), String> {
+        let mut connection = self.database.establish_connection()?;
+        backup::restore_from(&mut connection, path)
+    }
+
+    /// Write today's backup in the given directory unless already present, deleting the oldest ones
+    pub fn daily_backup(&self, backups_dir: &Path) -> Result<Option<PathBuf>, String> {
+        let path = backups_dir.join(daily_backup_name(self.clock.now().date_naive()));
+        if path.exists() {
+            return Ok(None);
+        }
+
+        fs::create_dir_all(backups_dir)
+            .map_err(|e| format!("Error creating {}: '{}'", backups_dir.display(), e))?;
+        self.backup(&path)?;
+
+        let file_names: Vec<String> = fs::read_dir(backups_dir)
+            .map_err(|e| format!("Error listing {}: '{}'", backups_dir.display(), e))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        for expired in expired_backups(&file_names, KEPT_DAILY_BACKUPS) {
+            if let Err(err) = fs::remove_file(backups_dir.join(&expired)) {
+                log::warn!("Error removing backup {expired} - {err}");
+            }
+        }
+
+        Ok(Some(path))
+    }
+
+    pub fn export_data(&self) -> Result<Dump, String> {
+        let mut connection = self.database.establish_connection()?;
+        dump::read_dump(&mut connection).map_err(|e| format!("Error exporting data: '{}'", e))
+    }
+
+    pub fn import_data(&self, data: &Dump, mode: ImportMode) -> Result<ImportSummary, String> {
+        let mut connection = self.database.establish_connection()?;
+        match mode {
+            ImportMode::Replace => dump::replace_all(&mut connection, data),
+            ImportMode::Merge => dump::merge(&mut connection, data),
+        }
+    }
+
+    /// Look for inconsistencies in the event log, eventually fixing them
+    pub fn fsck(&self, fix: bool) -> Result<FsckReport, String> {
+        let tz = self.clock.now().timezone();
+        let mut connection = self.database.establish_connection()?;
+
+        connection::transaction(&mut connection, |conn| {
+            let task_ids = mappers::tasks::list_tasks(conn)?
+                .into_iter()
+                .map(|t| t.id)
+                .collect();
+            let events = mappers::events::list_events(conn)?;
+            let findings = fsck::check(&events, &task_ids, &tz);
+
+            if fix {
+                let repairs = fsck::repairs(&findings);
+                mappers::events::delete_events(conn, &repairs.delete_event_ids)?;
+                mappers::events::convert_to_pause(conn, &repairs.pause_event_ids)?;
+                for timestamp in repairs.shutdown_timestamps {
+                    let event = NewEvent {
+                        event_timestamp: timestamp,
+                        task_id: None,
+                        system_event_name: Some(SystemEventType::Shutdown.to_string()),
+                        note: None,
+                    };
+                    mappers::events::push_event(conn, &event)?;
+                }
+            }
+
+            Ok(FsckReport {
+                findings,
+                fixed: fix,
+            })
+        })
+        .map_err(|e| format!("Error checking events: '{}'", e))
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Tracked intervals of the local days in `[from, to]`, the running one ends now
+    pub fn tracked_intervals(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Interval>, String> {
+        let tz = self.clock.now().timezone();
+        let since = interval::day_start(from, &tz);
+        let until = interval::day_start(to + Duration::days(1), &tz);
+
+        let mut connection = self.database.establish_connection()?;
+        let history =
+            mappers::history::history_between(&mut connection, since as i32, until as i32)
+                .map_err(|e| format!("Error retrieving history: '{}'", e))?;
+        Ok(interval::tracked_between(
+            &history,
+            since,
+            until,
+            self.clock.unix_now(),
+        ))
+    }
+
+    /// Tracked intervals between the given days (inclusive), along with the note of the switch starting each
+    /// of them
+    pub fn noted_intervals(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<(Interval, Option<String>)>, String> {
+        let tz = self.clock.now().timezone();
+        let since = interval::day_start(from, &tz);
+        let until = interval::day_start(to + Duration::days(1), &tz);
+
+        let mut connection = self.database.establish_connection()?;
+        let history =
+            mappers::history::history_between(&mut connection, since as i32, until as i32)
+                .map_err(|e| format!("Error retrieving history: '{}'", e))?;
+        let first_start = history
+            .iter()
+            .map(|h| h.ts_start)
+            .min()
+            .unwrap_or(since as i32);
+        let notes: HashMap<i32, String> =
+            mappers::events::notes_between(&mut connection, first_start, until as i32)
+                .map_err(|e| format!("Error retrieving notes: '{}'", e))?
+                .into_iter()
+                .collect();
+        Ok(
+            interval::tracked_events_between(&history, since, until, self.clock.unix_now())
+                .into_iter()
+                .map(|(event_id, interval)| (interval, notes.get(&event_id).cloned()))
+                .collect(),
+        )
+    }
+
+    /// Tracked and untracked intervals, sorted by start time
+    pub fn history(&self) -> Result<Vec<HistoryEntry>, String> {
+        let mut connection = self.database.establish_connection()?;
+        mappers::history::full_history(&mut connection)
+            .map_err(|e| format!("Error retrieving history: '{}'", e))
+    }
+
+    /// Add the intervals not overlapping the tracked history, creating the missing tasks
+    ///
+    /// With `dry_run` the same summary is computed without writing anything.
+    pub fn import_intervals(
+        &self,
+        intervals: Vec<Interval>,
+        dry_run: bool,
+    ) -> Result<IntervalImportSummary, String> {
+        self.import_with_switches(intervals, Vec::new(), false, dry_run)
+            .map(|(summary, _)| summary)
+    }
+
+    /// Add the intervals as [State::import_intervals] does along with the scheduled switches not
+    /// present yet, in a single transaction, returning the number of switches added
+    ///
+    /// With `split_tracked` the intervals within a tracked one split it, see [interval::plan_import].
+    fn import_with_switches(
+        &self,
+        intervals: Vec<Interval>,
+        switches: Vec<NewScheduledSwitch>,
+        split_tracked: bool,
+        dry_run: bool,
+    ) -> Result<(IntervalImportSummary, usize), String> {
+        let mut connection = self.database.establish_connection()?;
+
+        connection::transaction(&mut connection, |conn| {
+            let mut existing_switches: HashSet<_> =
+                mappers::scheduled_switches::list_scheduled_switches(conn)?
+                    .into_iter()
+                    .map(|s| (s.task_id, s.minute_of_day, s.weekdays, s.duration))
+                    .collect();
+            let mut added_switches = 0;
+            for switch in switches {
+                if existing_switches.insert((
+                    switch.task_id,
+                    switch.minute_of_day,
+                    switch.weekdays,
+                    switch.duration,
+                )) {
+                    if !dry_run {
+                        mappers::scheduled_switches::create_scheduled_switch(conn, &switch)?;
+                    }
+                    added_switches += 1;
+                }
+            }
+
+            let history = mappers::history::full_history(conn)?;
+            let plan = interval::plan_import(&history, intervals, split_tracked);
+
+            // Tasks are matched ignoring case, as done when creating them
+            let existing_tasks: HashSet<String> = mappers::tasks::list_tasks(conn)?
+                .into_iter()
+                .map(|t| t.name.to_ascii_lowercase())
+                .collect();
+            let new_tasks: Vec<String> = plan
+                .accepted
+                .iter()
+                .map(|i| i.task.clone())
+                .filter(|name| !existing_tasks.contains(&name.to_ascii_lowercase()))
+                .unique_by(|name| name.to_ascii_lowercase())
+                .collect();
+
+            if !dry_run {
+                for (timestamp, event) in interval::interval_events(&history, &plan.accepted) {
+                    let (task_id, system_event_name) = match event {
+                        IntervalEvent::Task(name) => {
+                            (Some(mappers::tasks::find_or_create_task(conn, name)?), None)
+                        }
+                        IntervalEvent::System(name) => (None, Some(name)),
+                    };
+                    let event = NewEvent {
+                        event_timestamp: timestamp as i32,
+                        task_id,
+                        system_event_name,
+                        note: None,
+                    };
+                    mappers::events::push_event(conn, &event)?;
+                }
+            }
+
+            Ok((
+                IntervalImportSummary {
+                    dry_run,
+                    new_tasks,
+                    intervals: plan.accepted,
+                    conflicts: plan.conflicts,
+                },
+                added_switches,
+            ))
+        })
+        .map_err(|e| format!("Error importing intervals: '{}'", e))
+    }
+
+    /// Map the calendar events to tasks, importing the past events and occurrences as intervals and
+    /// scheduling the endless daily and weekly recurrences, scheduled switches already present are
+    /// not duplicated
+    ///
+    /// With `split_tracked` the events within a tracked interval split it, going back to its task
+    /// once they are over, instead of being skipped as conflicts.
+    pub fn import_calendar(
+        &self,
+        events: &[IcsEvent],
+        rules: &[CalendarRule],
+        split_tracked: bool,
+    ) -> Result<CalendarImportSummary, String> {
+        ical::validate_rules(rules)?;
+        let tasks = self.list_tasks()?;
+        let task_name = |task_id: u64| {
+            tasks
+                .iter()
+                .find(|t| t.id as u64 == task_id)
+                .map(|t| t.name.clone())
+                .ok_or(format!("Unknown task {}", task_id))
+        };
+        for rule in rules {
+            task_name(rule.task_id)?;
+        }
+
+        let tz = self.clock.now().timezone();
+        let unix_now = self.clock.unix_now();
+        let mut summary = CalendarImportSummary::default();
+        let mut intervals = Vec::new();
+        let mut switches = Vec::new();
+
+        for event in events {
+            let Some(rule) = ical::find_matching_rule(rules, event) else {
+                summary.unmatched += 1;
+                continue;
+            };
+            let task = task_name(rule.task_id)?;
+
+            if event.rrule.is_none() {
+                if event.end <= unix_now {
+                    intervals.push(Interval {
+                        task,
+                        start: event.start,
+                        end: Some(event.end),
+                    });
+                } else {
+                    summary.upcoming += 1;
+                }
+                continue;
+            }
+
+            let Some((occurrences, open)) = ical::past_occurrences(event, unix_now, &tz) else {
+                summary.skipped += 1;
+                continue;
+            };
+            intervals.extend(occurrences.into_iter().map(|(start, end)| Interval {
+                task: task.clone(),
+                start,
+                end: Some(end),
+            }));
+            if !open {
+                continue;
+            }
+
+            let (Some(weekdays), Some(start)) = (
+                ical::recurring_weekdays(event, &tz),
+                tz.timestamp_opt(event.start, 0).earliest(),
+            ) else {
+                summary.skipped += 1;
+                continue;
+            };
+            switches.push(NewScheduledSwitch {
+                task_id: Some(rule.task_id as i32),
+                minute_of_day: (start.hour() * 60 + start.minute()) as i32,
+                weekdays: scheduled_switch::weekdays_mask(&weekdays),
+                duration: Some((event.end - event.start) as i32).filter(|d| *d > 0),
+            });
+        }
+
+        let (intervals, scheduled_switches) =
+            self.import_with_switches(intervals, switches, split_tracked, false)?;
+        summary.intervals = intervals;
+        summary.scheduled_switches = scheduled_switches;
+        Ok(summary)
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate, TimeZone, Timelike, Weekday};
+use serde::Serialize;
+
+use crate::tracker::interval::{self, Interval};
+
+/// Longest time spent on a task without switching to anything else
+#[derive(Serialize, Debug, PartialEq)]
+pub struct FocusStreak {
+    pub task: String,
+    pub start: i64,
+    pub duration: i64,
+}
+
+/// Activity of a day with tracked time, timestamps are unix seconds
+#[derive(Serialize, Debug, PartialEq)]
+pub struct DayStats {
+    pub day: String,
+    pub tracked: i64,
+    pub context_switches: usize,
+    pub first_activity: i64,
+    pub last_activity: i64,
+}
+
+/// Statistics of the tracked time in a range of days, durations are in seconds
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Stats {
+    pub total: i64,
+    /// Days from Monday to Friday in the range
+    pub working_days: usize,
+    pub average_per_working_day: i64,
+    pub longest_streaks: Vec<FocusStreak>,
+    pub average_context_switches: f64,
+    /// Seconds tracked in each hour of the day, from 00:00 to 23:00 local time
+    pub hour_distribution: Vec<i64>,
+    pub first_activity: Option<i64>,
+    pub last_activity: Option<i64>,
+    pub days: Vec<DayStats>,
+}
+
+/// Join the intervals of the same task following each other without a gap
+fn merge_adjacent(intervals: &[Interval]) -> Vec<Interval> {
+    let mut merged: Vec<Interval> = Vec::new();
+    for interval in intervals.iter().filter(|i| i.end.is_some()) {
+        match merged.last_mut() {
+            Some(last) if last.task == interval.task && last.end == Some(interval.start) => {
+                last.end = interval.end
+            }
+            _ => merged.push(interval.clone()),
+        }
+    }
+    merged
+}
+
+fn seconds(interval: &Interval) -> i64 {
+    interval.end.map_or(0, |end| end - interval.start)
+}
+
+/// Seconds of the interval spent in each local hour of the day
+fn add_hours<Tz: TimeZone>(distribution: &mut [i64], interval: &Interval, tz: &Tz) {
+    let end = interval.end.unwrap_or(interval.start);
+    let mut start = interval.start;
+    while start < end {
+        let Some(local) = tz.timestamp_opt(start, 0).earliest() else {
+            break;
+        };
+        let hour_end = start - i64::from(local.minute() * 60 + local.second()) + 3600;
+        let part_end = end.min(hour_end);
+        distribution[local.hour() as usize] += part_end - start;
+        start = part_end;
+    }
+}
+
+/// Statistics of the closed intervals tracked between `from` and `to` (inclusive), sorted by start time
+pub fn compute<Tz: TimeZone>(
+    intervals: &[Interval],
+    from: NaiveDate,
+    to: NaiveDate,
+    tz: &Tz,
+) -> Stats {
+    let merged = merge_adjacent(intervals);
+
+    let mut streaks: BTreeMap<&str, FocusStreak> = BTreeMap::new();
+    for interval in &merged {
+        let duration = seconds(interval);
+        let streak = streaks
+            .entry(interval.task.as_str())
+            .or_insert(FocusStreak {
+                task: interval.task.clone(),
+                start: interval.start,
+                duration: 0,
+            });
+        if duration > streak.duration {
+            streak.start = interval.start;
+            streak.duration = duration;
+        }
+    }
+
+    let mut hour_distribution = vec![0; 24];
+    for interval in &merged {
+        add_hours(&mut hour_distribution, interval, tz);
+    }
+
+    let mut days: BTreeMap<NaiveDate, DayStats> = BTreeMap::new();
+    let mut previous: Option<(NaiveDate, String)> = None;
+    for (day, part) in interval::split_by_day(&merged, tz) {
+        let end = part.end.unwrap_or(part.start);
+        let stats = days.entry(day).or_insert(DayStats {
+            day: day.format("%Y-%m-%d").to_string(),
+            tracked: 0,
+            context_switches: 0,
+            first_activity: part.start,
+            last_activity: end,
+        });
+        stats.tracked += seconds(&part);
+        stats.last_activity = stats.last_activity.max(end);
+        if previous.is_some_and(|(previous_day, previous_task)| {
+            previous_day == day && previous_task != part.task
+        }) {
+            stats.context_switches += 1;
+        }
+        previous = Some((day, part.task));
+    }
+
+    let total: i64 = days.values().map(|d| d.tracked).sum();
+    let working_days = from
+        .iter_days()
+        .take_while(|day| *day <= to)
+        .filter(|day| !matches!(day.weekday(), Weekday::Sat | Weekday::Sun))
+        .count();
+    let average_context_switches = if days.is_empty() {
+        0.0
+    } else {
+        days.values().map(|d| d.context_switches).sum::<usize>() as f64 / days.len() as f64
+    };
+
+    Stats {
+        total,
+        working_days,
+        average_per_working_day: total / working_days.max(1) as i64,
+        longest_streaks: streaks.into_values().collect(),
+        average_context_switches,
+        hour_distribution,
+        first_activity: days.values().map(|d| d.first_activity).min(),
+        last_activity: days.values().map(|d| d.last_activity).max(),
+        days: days.into_values().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracker::interval::{test_day, test_interval};
+    use chrono::Utc;
+
+    const HOUR: i64 = 3600;
+
+    #[test]
+    fn test_compute() {
+        // Monday 2024-03-04 and Tuesday 2024-03-05
+        let monday = interval::day_start(test_day("2024-03-04"), &Utc);
+        let tuesday = monday + 24 * HOUR;
+        let intervals = vec![
+            test_interval("dev", monday + 9 * HOUR, Some(monday + 10 * HOUR)),
+            test_interval("dev", monday + 10 * HOUR, Some(monday + 11 * HOUR + 1800)),
+            test_interval(
+                "meeting",
+                monday + 11 * HOUR + 1800,
+                Some(monday + 12 * HOUR),
+            ),
+            test_interval("dev", monday + 13 * HOUR, Some(monday + 14 * HOUR)),
+            test_interval("dev", monday + 23 * HOUR, Some(tuesday + HOUR)),
+            test_interval(
+                "meeting",
+                tuesday + 9 * HOUR,
+                Some(tuesday + 9 * HOUR + 900),
+            ),
+        ];
+
+        let stats = compute(
+            &intervals,
+            test_day("2024-03-04"),
+            test_day("2024-03-10"),
+            &Utc,
+        );
+        assert_eq!(stats.total, 6 * HOUR + 900);
+        assert_eq!(stats.working_days, 5);
+        assert_eq!(stats.average_per_working_day, (6 * HOUR + 900) / 5);
+        assert_eq!(
+            stats.longest_streaks,
+            vec![
+                FocusStreak {
+                    task: String::from("dev"),
+                    start: monday + 9 * HOUR,
+                    duration: 2 * HOUR + 1800,
+                },
+                FocusStreak {
+                    task: String::from("meeting"),
+                    start: monday + 11 * HOUR + 1800,
+                    duration: 1800,
+                },
+            ]
+        );
+
+        assert_eq!(stats.hour_distribution[0], HOUR);
+        assert_eq!(stats.hour_distribution[9], HOUR + 900);
+        assert_eq!(stats.hour_distribution[11], HOUR);
+        assert_eq!(stats.hour_distribution[12], 0);
+        assert_eq!(stats.hour_distribution.iter().sum::<i64>(), stats.total);
+
+        assert_eq!(
+            stats.days,
+            vec![
+                DayStats {
+                    day: String::from("2024-03-04"),
+                    tracked: 5 * HOUR,
+                    context_switches: 2,
+                    first_activity: monday + 9 * HOUR,
+                    last_activity: tuesday,
+                },
+                DayStats {
+                    day: String::from("2024-03-05"),
+                    tracked: HOUR + 900,
+                    context_switches: 1,
+                    first_activity: tuesday,
+                    last_activity: tuesday + 9 * HOUR + 900,
+                },
+            ]
+        );
+        assert_eq!(stats.average_context_switches, 1.5);
+        assert_eq!(stats.first_activity, Some(monday + 9 * HOUR));
+        assert_eq!(stats.last_activity, Some(tuesday + 9 * HOUR + 900));
+    }
+
+    #[test]
+    fn test_empty_range() {
+        let day = NaiveDate::from_ymd_opt(2024, 3, 9).unwrap();
+        let stats = compute(&[], day, day, &Utc);
+        assert_eq!(stats.working_days, 0);
+        assert_eq!(stats.average_per_working_day, 0);
+        assert_eq!(stats.average_context_switches, 0.0);
+        assert_eq!(stats.first_activity, None);
+    }
+}
@@ -0,0 +1,426 @@
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+
+use chrono::{Datelike, Days, NaiveDate, TimeZone};
+
+use crate::tracker::handler::format_time_interval;
+use crate::tracker::html_report::escape;
+use crate::tracker::interval::{self, Interval};
+
+/// Colors assigned to the tasks in name order, repeated when there are more tasks
+const PALETTE: [&str; 10] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+    "#9c755f", "#bab0ac",
+];
+
+const PLOT_WIDTH: f64 = 720.0;
+const PLOT_HEIGHT: f64 = 320.0;
+const MARGIN: f64 = 50.0;
+const LEGEND_WIDTH: f64 = 260.0;
+const LEGEND_ROW: f64 = 20.0;
+const TIMELINE_ROW: f64 = 24.0;
+const TIMELINE_LABEL: f64 = 90.0;
+
+/// Periods grouped in a bar of the stacked bar chart
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BarPeriod {
+    Day,
+    /// Weeks start on Monday
+    Week,
+}
+
+/// Index of each task, giving its color
+fn task_colors(parts: &[(NaiveDate, Interval)]) -> BTreeMap<&str, &'static str> {
+    let mut tasks: Vec<&str> = parts.iter().map(|(_, i)| i.task.as_str()).collect();
+    tasks.sort();
+    tasks.dedup();
+    tasks
+        .into_iter()
+        .enumerate()
+        .map(|(idx, task)| (task, PALETTE[idx % PALETTE.len()]))
+        .collect()
+}
+
+fn seconds(interval: &Interval) -> i64 {
+    interval.end.map_or(0, |end| end - interval.start)
+}
+
+fn svg_document(width: f64, height: f64, content: &str) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" \
+         font-family=\"sans-serif\" font-size=\"12\">\n\
+         <rect width=\"{w}\" height=\"{h}\" fill=\"white\"/>\n{content}</svg>\n",
+        w = width,
+        h = height,
+        content = content
+    )
+}
+
+/// Colored squares with the task names and the given labels, from the given top left corner
+fn legend(entries: &[(&str, &str, String)], x: f64, y: f64) -> String {
+    let mut legend = String::new();
+    for (idx, (task, color, label)) in entries.iter().enumerate() {
+        let row = y + idx as f64 * LEGEND_ROW;
+        legend.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"12\" height=\"12\" fill=\"{}\"/>\
+             <text x=\"{}\" y=\"{}\">{} {}</text>\n",
+            x,
+            row,
+            color,
+            x + 18.0,
+            row + 10.0,
+            escape(task),
+            label
+        ));
+    }
+    legend
+}
+
+fn period_start(day: NaiveDate, period: BarPeriod) -> NaiveDate {
+    match period {
+        BarPeriod::Day => day,
+        BarPeriod::Week => day - Days::new(u64::from(day.weekday().num_days_from_monday())),
+    }
+}
+
+/// Bars of the time spent per day or week between `from` and `to`, stacking the tasks
+pub fn stacked_bars(
+    parts: &[(NaiveDate, Interval)],
+    period: BarPeriod,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> String {
+    let colors = task_colors(parts);
+    let step = match period {
+        BarPeriod::Day => Days::new(1),
+        BarPeriod::Week => Days::new(7),
+    };
+
+    let mut bars: BTreeMap<NaiveDate, BTreeMap<&str, i64>> = BTreeMap::new();
+    let mut bar = period_start(from, period);
+    while bar <= to {
+        bars.insert(bar, BTreeMap::new());
+        bar = match bar.checked_add_days(step) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    for (day, part) in parts {
+        *bars
+            .entry(period_start(*day, period))
+            .or_default()
+            .entry(part.task.as_str())
+            .or_default() += seconds(part);
+    }
+
+    let max_hours = bars
+        .values()
+        .map(|tasks| tasks.values().sum::<i64>())
+        .max()
+        .unwrap_or(0)
+        .div_euclid(3600)
+        + 1;
+    let hour_step = (max_hours + 7) / 8;
+    let scale = PLOT_HEIGHT / (max_hours * 3600) as f64;
+    let slot = PLOT_WIDTH / bars.len().max(1) as f64;
+    let bottom = MARGIN + PLOT_HEIGHT;
+
+    let mut content = String::new();
+    for hour in (0..=max_hours).step_by(hour_step.max(1) as usize) {
+        let y = bottom - (hour * 3600) as f64 * scale;
+        content.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{y:.1}\" x2=\"{}\" y2=\"{y:.1}\" stroke=\"#ddd\"/>\
+             <text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{}h</text>\n",
+            MARGIN,
+            MARGIN + PLOT_WIDTH,
+            MARGIN - 6.0,
+            y + 4.0,
+            hour,
+            y = y
+        ));
+    }
+    for (idx, (bar, tasks)) in bars.iter().enumerate() {
+        let x = MARGIN + idx as f64 * slot + slot * 0.15;
+        let mut y = bottom;
+        for (task, seconds) in tasks {
+            let height = *seconds as f64 * scale;
+            y -= height;
+            content.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\">\
+                 <title>{} {}</title></rect>\n",
+                x,
+                y,
+                slot * 0.7,
+                height,
+                colors[task],
+                escape(task),
+                format_time_interval(&(*seconds as i32))
+            ));
+        }
+        content.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{}\" text-anchor=\"middle\">{}</text>\n",
+            x + slot * 0.35,
+            bottom + 16.0,
+            bar.format("%m-%d")
+        ));
+    }
+
+    let mut totals: BTreeMap<&str, i64> = BTreeMap::new();
+    for (_, part) in parts {
+        *totals.entry(part.task.as_str()).or_default() += seconds(part);
+    }
+    let entries: Vec<(&str, &str, String)> = totals
+        .iter()
+        .map(|(task, seconds)| {
+            (
+                *task,
+                colors[task],
+                format_time_interval(&(*seconds as i32)),
+            )
+        })
+        .collect();
+    content.push_str(&legend(&entries, 2.0 * MARGIN + PLOT_WIDTH, MARGIN));
+
+    svg_document(
+        3.0 * MARGIN + PLOT_WIDTH + LEGEND_WIDTH,
+        (2.0 * MARGIN + PLOT_HEIGHT).max(2.0 * MARGIN + entries.len() as f64 * LEGEND_ROW),
+        &content,
+    )
+}
+
+fn point(cx: f64, cy: f64, radius: f64, angle: f64) -> (f64, f64) {
+    (cx + radius * angle.sin(), cy - radius * angle.cos())
+}
+
+/// Donut of the share of each task in the total time
+pub fn donut(parts: &[(NaiveDate, Interval)]) -> String {
+    let colors = task_colors(parts);
+    let mut totals: BTreeMap<&str, i64> = BTreeMap::new();
+    for (_, part) in parts {
+        *totals.entry(part.task.as_str()).or_default() += seconds(part);
+    }
+    let total: i64 = totals.values().sum();
+
+    let radius = PLOT_HEIGHT / 2.0;
+    let inner = radius * 0.6;
+    let (cx, cy) = (MARGIN + radius, MARGIN + radius);
+
+    let mut content = String::new();
+    let mut angle = 0.0;
+    for (task, seconds) in &totals {
+        let share = *seconds as f64 / total as f64;
+        let title = format!(
+            "<title>{} {}</title>",
+            escape(task),
+            format_time_interval(&(*seconds as i32))
+        );
+        if share >= 1.0 {
+            // A single arc can't draw a full circle
+            content.push_str(&format!(
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\">{}</circle>\n",
+                cx,
+                cy,
+                (radius + inner) / 2.0,
+                colors[task],
+                radius - inner,
+                title
+            ));
+            continue;
+        }
+        let end = angle + share * 2.0 * PI;
+        let large_arc = u8::from(end - angle > PI);
+        let (x1, y1) = point(cx, cy, radius, angle);
+        let (x2, y2) = point(cx, cy, radius, end);
+        let (x3, y3) = point(cx, cy, inner, end);
+        let (x4, y4) = point(cx, cy, inner, angle);
+        content.push_str(&format!(
+            "<path d=\"M{x1:.2} {y1:.2} A{r} {r} 0 {l} 1 {x2:.2} {y2:.2} L{x3:.2} {y3:.2} \
+             A{ri} {ri} 0 {l} 0 {x4:.2} {y4:.2} Z\" fill=\"{color}\">{title}</path>\n",
+            r = radius,
+            ri = inner,
+            l = large_arc,
+            color = colors[task],
+        ));
+        angle = end;
+    }
+    content.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"16\">{}</text>\n",
+        cx,
+        cy + 6.0,
+        format_time_interval(&(total as i32))
+    ));
+
+    let entries: Vec<(&str, &str, String)> = totals
+        .iter()
+        .map(|(task, seconds)| {
+            (
+                *task,
+                colors[task],
+                format!(
+                    "{} ({:.0}%)",
+                    format_time_interval(&(*seconds as i32)),
+                    *seconds as f64 * 100.0 / total as f64
+                ),
+            )
+        })
+        .collect();
+    content.push_str(&legend(&entries, 2.0 * MARGIN + 2.0 * radius, MARGIN));
+
+    svg_document(
+        3.0 * MARGIN + 2.0 * radius + LEGEND_WIDTH,
+        (2.0 * MARGIN + 2.0 * radius).max(2.0 * MARGIN + entries.len() as f64 * LEGEND_ROW),
+        &content,
+    )
+}
+
+/// Strip per day between `from` and `to` showing when each task was tracked
+pub fn timeline<Tz: TimeZone>(
+    parts: &[(NaiveDate, Interval)],
+    from: NaiveDate,
+    to: NaiveDate,
+    tz: &Tz,
+) -> String {
+    let colors = task_colors(parts);
+    let days: Vec<NaiveDate> = from.iter_days().take_while(|day| *day <= to).collect();
+    let left = MARGIN + TIMELINE_LABEL;
+
+    let mut content = String::new();
+    for hour in (0..=24).step_by(3) {
+        let x = left + PLOT_WIDTH * f64::from(hour) / 24.0;
+        content.push_str(&format!(
+            "<line x1=\"{x:.1}\" y1=\"{}\" x2=\"{x:.1}\" y2=\"{}\" stroke=\"#ddd\"/>\
+             <text x=\"{x:.1}\" y=\"{}\" text-anchor=\"middle\">{:02}:00</text>\n",
+            MARGIN,
+            MARGIN + days.len() as f64 * TIMELINE_ROW,
+            MARGIN - 6.0,
+            hour,
+            x = x
+        ));
+    }
+    for (row, day) in days.iter().enumerate() {
+        let y = MARGIN + row as f64 * TIMELINE_ROW;
+        let start = interval::day_start(*day, tz);
+        let length = day
+            .succ_opt()
+            .map_or(86400, |next| interval::day_start(next, tz) - start)
+            .max(1) as f64;
+        content.push_str(&format!(
+            "<text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>\n",
+            left - 6.0,
+            y + TIMELINE_ROW * 0.65,
+            day.format("%a %Y-%m-%d")
+        ));
+        for (_, part) in parts.iter().filter(|(d, _)| d == day) {
+            let x = left + PLOT_WIDTH * (part.start - start) as f64 / length;
+            let width = PLOT_WIDTH * seconds(part) as f64 / length;
+            content.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\">\
+                 <title>{} {}</title></rect>\n",
+                x,
+                y + 2.0,
+                width,
+                TIMELINE_ROW - 4.0,
+                colors[part.task.as_str()],
+                escape(&part.task),
+                format_time_interval(&(seconds(part) as i32))
+            ));
+        }
+    }
+
+    let entries: Vec<(&str, &str, String)> = colors
+        .iter()
+        .map(|(task, color)| (*task, *color, String::new()))
+        .collect();
+    let legend_top = 1.5 * MARGIN + days.len() as f64 * TIMELINE_ROW;
+    content.push_str(&legend(&entries, left, legend_top));
+
+    svg_document(
+        2.0 * MARGIN + TIMELINE_LABEL + PLOT_WIDTH,
+        legend_top + entries.len() as f64 * LEGEND_ROW + MARGIN / 2.0,
+        &content,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracker::interval::test_day;
+    use chrono::Utc;
+
+    /// Parts of 2024-03-04 (Monday) to 2024-03-11 (Monday of the following week)
+    fn parts() -> Vec<(NaiveDate, Interval)> {
+        let part = |d: &str, task: &str, hour: i64, hours: i64| {
+            let start = interval::day_start(test_day(d), &Utc) + hour * 3600;
+            (
+                test_day(d),
+                Interval {
+                    task: String::from(task),
+                    start,
+                    end: Some(start + hours * 3600),
+                },
+            )
+        };
+        vec![
+            part("2024-03-04", "dev", 9, 3),
+            part("2024-03-04", "meeting", 13, 1),
+            part("2024-03-06", "dev", 6, 6),
+            part("2024-03-11", "dev & ops", 12, 2),
+        ]
+    }
+
+    #[test]
+    fn test_stacked_bars() {
+        let daily = stacked_bars(
+            &parts(),
+            BarPeriod::Day,
+            test_day("2024-03-04"),
+            test_day("2024-03-11"),
+        );
+        assert!(daily.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert_eq!(daily.matches("text-anchor=\"middle\">03-").count(), 8);
+        assert_eq!(daily.matches("<title>").count(), 4);
+        assert!(daily.contains("fill=\"#e15759\"><title>meeting 01:00:00</title>"));
+        assert!(daily.contains("<text x=\"838\" y=\"60\">dev 09:00:00</text>"));
+        assert!(daily.contains("dev &amp; ops 02:00:00"));
+
+        let weekly = stacked_bars(
+            &parts(),
+            BarPeriod::Week,
+            test_day("2024-03-06"),
+            test_day("2024-03-11"),
+        );
+        assert!(weekly.contains(">03-04</text>"));
+        assert!(weekly.contains(">03-11</text>"));
+        assert_eq!(weekly.matches("text-anchor=\"middle\">03-").count(), 2);
+        assert!(weekly.contains("<title>dev 09:00:00</title>"));
+    }
+
+    #[test]
+    fn test_donut() {
+        let chart = donut(&parts());
+        assert_eq!(chart.matches("<path").count(), 3);
+        assert!(chart.contains("dev 09:00:00 (75%)"));
+        assert!(chart.contains(">12:00:00</text>"));
+
+        let single = donut(&parts()[..1]);
+        assert_eq!(single.matches("<circle").count(), 1);
+        assert!(!single.contains("<path"));
+    }
+
+    #[test]
+    fn test_timeline() {
+        let chart = timeline(
+            &parts(),
+            test_day("2024-03-04"),
+            test_day("2024-03-05"),
+            &Utc,
+        );
+        assert!(chart.contains(">Mon 2024-03-04</text>"));
+        assert!(chart.contains(">Tue 2024-03-05</text>"));
+        // 09:00 to 12:00 of the first row
+        assert!(chart.contains(
+            "<rect x=\"410.0\" y=\"52.0\" width=\"90.0\" height=\"20.0\" fill=\"#4e79a7\">"
+        ));
+        assert_eq!(chart.matches("<rect x=\"").count(), 2 + 3);
+    }
+}
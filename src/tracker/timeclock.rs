@@ -0,0 +1,137 @@
+use chrono::{NaiveDateTime, TimeZone};
+
+use crate::tracker::interval::Interval;
+
+const TIMECLOCK_FORMAT: &str = "%Y/%m/%d %H:%M:%S";
+
+fn format_timestamp<Tz: TimeZone>(timestamp: i64, tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    tz.timestamp_opt(timestamp, 0)
+        .earliest()
+        .map(|dt| dt.format(TIMECLOCK_FORMAT).to_string())
+        .unwrap_or_default()
+}
+
+/// Timeclock entries of ledger and hledger, tasks are used as account names
+///
+/// The running interval is left clocked in.
+pub fn write_intervals<Tz: TimeZone>(intervals: &[Interval], tz: &Tz) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let mut entries = String::new();
+    for interval in intervals {
+        entries.push_str(&format!(
+            "i {} {}\n",
+            format_timestamp(interval.start, tz),
+            interval.task
+        ));
+        if let Some(end) = interval.end {
+            entries.push_str(&format!("o {}\n", format_timestamp(end, tz)));
+        }
+    }
+    entries
+}
+
+fn parse_timestamp<Tz: TimeZone>(date: &str, time: &str, tz: &Tz) -> Result<i64, String> {
+    let value = format!("{} {}", date.replace('-', "/"), time);
+    let local = NaiveDateTime::parse_from_str(&value, TIMECLOCK_FORMAT)
+        .or_else(|_| NaiveDateTime::parse_from_str(&value, "%Y/%m/%d %H:%M"))
+        .map_err(|e| format!("Invalid date-time '{}': '{}'", value, e))?;
+    tz.from_local_datetime(&local)
+        .earliest()
+        .map(|dt| dt.timestamp())
+        .ok_or(format!("Nonexistent local time '{}'", value))
+}
+
+/// Intervals of the clock-in and clock-out entries, the account of the clock-in names the task
+///
+/// The account ends at two spaces or a tab, what follows is the description and it is ignored.
+pub fn read_intervals<Tz: TimeZone>(content: &str, tz: &Tz) -> Result<Vec<Interval>, String> {
+    let mut intervals: Vec<Interval> = Vec::new();
+    for (idx, line) in content.lines().enumerate() {
+        let line_error = |e: &str| format!("Line {}: {}", idx + 1, e);
+        let mut fields = line.splitn(4, ' ');
+        let (Some(code), Some(date), Some(time)) = (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let clocked_in = intervals.last().is_some_and(|i| i.end.is_none());
+
+        match code {
+            "i" | "I" => {
+                if clocked_in {
+                    return Err(line_error("Clock-in while already clocked in"));
+                }
+                let account = fields
+                    .next()
+                    .map(|rest| rest.split("  ").next().unwrap_or_default())
+                    .and_then(|rest| rest.split('\t').next())
+                    .map(str::trim)
+                    .filter(|account| !account.is_empty())
+                    .ok_or(line_error("Missing account"))?;
+                intervals.push(Interval {
+                    task: account.to_string(),
+                    start: parse_timestamp(date, time, tz).map_err(|e| line_error(&e))?,
+                    end: None,
+                });
+            }
+            "o" | "O" => match intervals.last_mut() {
+                Some(interval) if clocked_in => {
+                    interval.end =
+                        Some(parse_timestamp(date, time, tz).map_err(|e| line_error(&e))?)
+                }
+                _ => return Err(line_error("Clock-out without clock-in")),
+            },
+            _ => (),
+        }
+    }
+    Ok(intervals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tracker::interval::test_interval;
+    use chrono::Utc;
+
+    #[test]
+    fn test_round_trip() {
+        let intervals = vec![
+            test_interval("client:website", 1709629200, Some(1709634600)),
+            test_interval("meeting", 1709636400, None),
+        ];
+        let entries = write_intervals(&intervals, &Utc);
+
+        assert_eq!(
+            entries,
+            "i 2024/03/05 09:00:00 client:website\n\
+             o 2024/03/05 10:30:00\n\
+             i 2024/03/05 11:00:00 meeting\n"
+        );
+        assert_eq!(read_intervals(&entries, &Utc).unwrap(), intervals);
+    }
+
+    #[test]
+    fn test_read_intervals() {
+        let content = "; hledger timeclock\n\
+                       i 2024-03-05 09:00 client:website  fixing the footer\n\
+                       o 2024-03-05 09:45\n\
+                       \n";
+
+        assert_eq!(
+            read_intervals(content, &Utc).unwrap(),
+            vec![test_interval(
+                "client:website",
+                1709629200,
+                Some(1709631900)
+            )]
+        );
+        assert_eq!(
+            read_intervals(&format!("{}o 2024/03/05 10:00:00\n", content), &Utc),
+            Err(String::from("Line 5: Clock-out without clock-in"))
+        );
+    }
+}
@@ -0,0 +1,257 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDateTime};
+use oclock_sqlite::models::HistoryEntry;
+use serde::{Deserialize, Serialize};
+
+use crate::tracker::interval::Interval;
+
+const TIMEW_DATETIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Interval in the format of `timew export`, tags are joined with a space to name the task
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct TimewInterval {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<usize>,
+    pub start: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<String>,
+}
+
+fn parse_datetime(value: &str) -> Result<i64, String> {
+    NaiveDateTime::parse_from_str(value, TIMEW_DATETIME_FORMAT)
+        .map(|dt| dt.and_utc().timestamp())
+        .map_err(|e| format!("Invalid timewarrior date '{}': '{}'", value, e))
+}
+
+fn format_datetime(timestamp: i32) -> String {
+    DateTime::from_timestamp(i64::from(timestamp), 0)
+        .unwrap_or_default()
+        .format(TIMEW_DATETIME_FORMAT)
+        .to_string()
+}
+
+impl TimewInterval {
+    /// Interval of the task named after the tags, untagged intervals have no task
+    pub fn to_interval(&self) -> Result<Option<Interval>, String> {
+        if self.tags.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Interval {
+            task: self.tags.join(" "),
+            start: parse_datetime(&self.start)?,
+            end: self.end.as_deref().map(parse_datetime).transpose()?,
+        }))
+    }
+}
+
+/// Split the tags of a data file line, quoted tags may contain spaces and escaped quotes
+///
+/// Tokenizing stops at a lone `#`, introducing the annotation.
+fn split_tags(text: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut chars = text.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(first) = chars.next() else {
+            break;
+        };
+
+        let mut tag = String::new();
+        if first == '"' {
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => tag.extend(chars.next()),
+                    '"' => break,
+                    c => tag.push(c),
+                }
+            }
+        } else {
+            tag.push(first);
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                tag.push(c);
+            }
+            if tag == "#" {
+                break;
+            }
+        }
+        tags.push(tag);
+    }
+    tags
+}
+
+/// Parse a line of a timewarrior data file, like `inc 20240305T090000Z - 20240305T100000Z # dev`
+fn parse_data_line(line: &str) -> Result<Option<TimewInterval>, String> {
+    let line = line.trim();
+    let Some(rest) = line.strip_prefix("inc ") else {
+        return Ok(None);
+    };
+    let (range, tags) = match rest.split_once(" # ") {
+        Some((range, tags)) => (range, split_tags(tags)),
+        None => (rest.trim_end_matches(" #"), vec![]),
+    };
+
+    let mut bounds = range.split(" - ");
+    match (bounds.next(), bounds.next(), bounds.next()) {
+        (Some(start), end, None) => Ok(Some(TimewInterval {
+            id: None,
+            start: start.trim().to_string(),
+            end: end.map(|e| e.trim().to_string()),
+            tags,
+            annotation: None,
+        })),
+        _ => Err(format!("Invalid timewarrior data line '{}'", line)),
+    }
+}
+
+fn parse_data_file(content: &str) -> Result<Vec<TimewInterval>, String> {
+    let mut intervals = Vec::new();
+    for line in content.lines() {
+        intervals.extend(parse_data_line(line)?);
+    }
+    Ok(intervals)
+}
+
+/// Read `timew export` json, a timewarrior data file or a whole data directory
+pub fn read_intervals(path: &Path) -> Result<Vec<TimewInterval>, String> {
+    let read = |path: &Path| {
+        fs::read_to_string(path).map_err(|e| format!("Error reading {}: '{}'", path.display(), e))
+    };
+
+    if path.is_dir() {
+        let entries =
+            fs::read_dir(path).map_err(|e| format!("Error reading {}: '{}'", path.display(), e))?;
+        let mut files: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "data"))
+            .collect();
+        files.sort();
+
+        let mut intervals = Vec::new();
+        for file in files {
+            intervals.extend(parse_data_file(&read(&file)?)?);
+        }
+        return Ok(intervals);
+    }
+
+    let content = read(path)?;
+    if content.trim_start().starts_with('[') {
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid timewarrior export {}: '{}'", path.display(), e))
+    } else {
+        parse_data_file(&content)
+    }
+}
+
+/// Tracked history in the format of `timew export`, the most recent interval has id 1
+pub fn export_intervals(history: &[HistoryEntry]) -> Vec<TimewInterval> {
+    let tracked: Vec<&HistoryEntry> = history.iter().filter(|h| h.task_id.is_some()).collect();
+    let count = tracked.len();
+
+    tracked
+        .into_iter()
+        .enumerate()
+        .map(|(idx, entry)| TimewInterval {
+            id: Some(count - idx),
+            start: format_datetime(entry.ts_start),
+            end: entry.ts_end.map(format_datetime),
+            tags: entry.task_name.iter().cloned().collect(),
+            annotation: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_data_file() {
+        let content = "inc 20240305T090000Z - 20240305T100000Z # dev \"code review\"\n\
+                       inc 20240305T100000Z - 20240305T103000Z\n\
+                       inc 20240305T110000Z # \"say \\\"hi\\\"\" planning # notes\n";
+
+        assert_eq!(
+            parse_data_file(content).unwrap(),
+            vec![
+                TimewInterval {
+                    id: None,
+                    start: String::from("20240305T090000Z"),
+                    end: Some(String::from("20240305T100000Z")),
+                    tags: vec![String::from("dev"), String::from("code review")],
+                    annotation: None,
+                },
+                TimewInterval {
+                    id: None,
+                    start: String::from("20240305T100000Z"),
+                    end: Some(String::from("20240305T103000Z")),
+                    tags: vec![],
+                    annotation: None,
+                },
+                TimewInterval {
+                    id: None,
+                    start: String::from("20240305T110000Z"),
+                    end: None,
+                    tags: vec![String::from("say \"hi\""), String::from("planning")],
+                    annotation: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let json = r#"[
+            {"id":2,"start":"20240305T090000Z","end":"20240305T100000Z","tags":["dev","review"],"annotation":"PR"},
+            {"id":1,"start":"20240305T100000Z"}
+        ]"#;
+        let intervals: Vec<TimewInterval> = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            intervals[0].to_interval().unwrap(),
+            Some(Interval {
+                task: String::from("dev review"),
+                start: 1709629200,
+                end: Some(1709632800),
+            })
+        );
+        assert_eq!(intervals[1].to_interval().unwrap(), None);
+
+        let history = vec![
+            HistoryEntry {
+                id: 1,
+                ts_start: 1709629200,
+                ts_end: Some(1709632800),
+                system_event: None,
+                task_name: Some(String::from("dev review")),
+                task_id: Some(1),
+            },
+            HistoryEntry {
+                id: 2,
+                ts_start: 1709632800,
+                ts_end: Some(1709636400),
+                system_event: Some(String::from("Pause")),
+                task_name: None,
+                task_id: None,
+            },
+            HistoryEntry {
+                id: 3,
+                ts_start: 1709636400,
+                ts_end: None,
+                system_event: None,
+                task_name: Some(String::from("meeting")),
+                task_id: Some(2),
+            },
+        ];
+        assert_eq!(
+            serde_json::to_string(&export_intervals(&history)).unwrap(),
+            "[{\"id\":2,\"start\":\"20240305T090000Z\",\"end\":\"20240305T100000Z\",\"tags\":[\"dev review\"]},\
+             {\"id\":1,\"start\":\"20240305T110000Z\",\"tags\":[\"meeting\"]}]"
+        );
+    }
+}
@@ -0,0 +1,401 @@
+use std::rc::Rc;
+
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use oclock::dto::command::{
+    CalendarField, CalendarRule, OClockClientCommand, Rounding, RoundingMode, RoundingScope,
+    Weekday,
+};
+use oclock::tracker::clock::FakeClock;
+use oclock::tracker::handler::dispatch;
+use oclock::tracker::ical::{EventZone, IcsEvent};
+use oclock::tracker::pomodoro::PomodoroSettings;
+use oclock::tracker::state::State;
+use oclock_sqlite::connection::MemoryDB;
+use oclock_sqlite::constants::SystemEventType;
+
+const DEV: u64 = 1;
+const MEETING: u64 = 2;
+
+/// Unix timestamp of the given local time, formatted as `YYYY-MM-DD HH:MM`
+fn at(datetime: &str) -> i64 {
+    NaiveDateTime::parse_from_str(datetime, "%Y-%m-%d %H:%M")
+        .unwrap()
+        .and_local_timezone(Local)
+        .single()
+        .unwrap()
+        .timestamp()
+}
+
+/// In-memory database driven by a fake clock, emulating daemon sessions
+struct Scenario {
+    database: Rc<MemoryDB>,
+    clock: FakeClock,
+}
+
+impl Scenario {
+    fn new() -> Scenario {
+        let scenario = Scenario {
+            database: Rc::new(MemoryDB::new().unwrap()),
+            clock: FakeClock::new(0),
+        };
+
+        let state = scenario.open();
+        state.new_task(String::from("dev")).unwrap();
+        state.new_task(String::from("meeting")).unwrap();
+        scenario
+    }
+
+    fn open(&self) -> State {
+        State::with_storage(
+            Box::new(self.database.clone()),
+            Box::new(self.clock.clone()),
+        )
+    }
+
+    /// Same steps the daemon performs at startup
+    fn start_daemon(&self, datetime: &str) -> State {
+        self.clock.set(at(datetime));
+        let state = self.open();
+        state.recover();
+        state.system_event(SystemEventType::Startup).unwrap();
+        state.system_event(SystemEventType::Ping).unwrap();
+        state
+    }
+
+    /// Same steps the daemon performs every minute
+    fn tick(&self, state: &State, datetime: &str) {
+        self.clock.set(at(datetime));
+        state.ping();
+        state.run_scheduled_switches().unwrap();
+    }
+
+    fn stop_daemon(&self, state: State, datetime: &str) {
+        self.tick(&state, datetime);
+        state.system_event(SystemEventType::Shutdown).unwrap();
+    }
+
+    fn switch_task(&self, state: &State, datetime: &str, task_id: u64) {
+        self.clock.set(at(datetime));
+        state.switch_task(task_id).unwrap();
+    }
+
+    fn timesheet(&self) -> String {
+        self.rounded_timesheet(None)
+    }
+
+    fn rounded_timesheet(&self, rounding: Option<Rounding>) -> String {
+        match dispatch(
+            OClockClientCommand::Timesheet { rounding },
+            &self.open(),
+            &mut |_| (),
+        )
+        .unwrap()
+        {
+            serde_json::Value::String(csv) => csv,
+            other => panic!("Unexpected timesheet {other}"),
+        }
+    }
+}
+
+#[test]
+fn test_regular_days() {
+    let scenario = Scenario::new();
+
+    let state = scenario.start_daemon("2024-03-04 08:50");
+    scenario.switch_task(&state, "2024-03-04 09:00", DEV);
+    scenario.switch_task(&state, "2024-03-04 12:30", MEETING);
+    scenario.switch_task(&state, "2024-03-04 13:00", DEV);
+    scenario.stop_daemon(state, "2024-03-04 17:30");
+
+    let state = scenario.start_daemon("2024-03-05 08:40");
+    scenario.switch_task(&state, "2024-03-05 08:45", MEETING);
+    scenario.switch_task(&state, "2024-03-05 09:15", DEV);
+    scenario.stop_daemon(state, "2024-03-05 18:00");
+
+    assert_eq!(
+        scenario.timesheet(),
+        "day,NONE,dev,meeting\n\
+         2024-03-04,00:10:00,08:00:00,00:30:00\n\
+         2024-03-05,00:05:00,08:45:00,00:30:00\n"
+    );
+}
+
+#[test]
+fn test_rounded_days() {
+    let scenario = Scenario::new();
+
+    let state = scenario.start_daemon("2024-03-04 08:50");
+    scenario.switch_task(&state, "2024-03-04 09:00", DEV);
+    scenario.tick(&state, "2024-03-04 10:00");
+    scenario.switch_task(&state, "2024-03-04 12:37", MEETING);
+    scenario.switch_task(&state, "2024-03-04 12:52", DEV);
+    scenario.stop_daemon(state, "2024-03-04 17:30");
+
+    let rounding = |scope| Rounding {
+        increment_minutes: 15,
+        mode: RoundingMode::Up,
+        scope,
+    };
+    assert_eq!(
+        scenario.rounded_timesheet(Some(rounding(RoundingScope::Interval))),
+        "day,NONE,dev,meeting\n\
+         2024-03-04,00:15:00,08:30:00,00:15:00\n"
+    );
+    assert_eq!(
+        scenario.rounded_timesheet(Some(rounding(RoundingScope::Day))),
+        "day,NONE,dev,meeting\n\
+         2024-03-04,00:15:00,08:15:00,00:15:00\n"
+    );
+    assert_eq!(
+        scenario.timesheet(),
+        "day,NONE,dev,meeting\n\
+         2024-03-04,00:10:00,08:15:00,00:15:00\n"
+    );
+}
+
+#[test]
+fn test_crash_recovery_and_retro_switch() {
+    let scenario = Scenario::new();
+
+    let state = scenario.start_daemon("2024-03-05 08:55");
+    scenario.switch_task(&state, "2024-03-05 09:00", DEV);
+    scenario.tick(&state, "2024-03-05 10:59");
+    scenario.tick(&state, "2024-03-05 11:00");
+    // The daemon is killed without writing the shutdown event
+    drop(state);
+
+    let state = scenario.start_daemon("2024-03-05 14:00");
+    scenario.switch_task(&state, "2024-03-05 14:05", MEETING);
+    scenario.clock.set(at("2024-03-05 16:00"));
+    state
+        .retro_switch_task(DEV as i32, at("2024-03-05 15:00") as i32, true)
+        .unwrap();
+    scenario.stop_daemon(state, "2024-03-05 17:00");
+
+    assert_eq!(
+        scenario.timesheet(),
+        "day,NONE,dev,meeting\n\
+         2024-03-05,00:10:00,03:00:00,01:55:00\n"
+    );
+}
+
+#[test]
+fn test_scheduled_stop_while_daemon_running() {
+    let scenario = Scenario::new();
+    scenario
+        .open()
+        .add_scheduled_switch(
+            None,
+            "19:00",
+            &[
+                Weekday::Monday,
+                Weekday::Tuesday,
+                Weekday::Wednesday,
+                Weekday::Thursday,
+                Weekday::Friday,
+            ],
+            None,
+        )
+        .unwrap();
+
+    // Wednesday, the user forgets to stop tracking
+    let state = scenario.start_daemon("2024-03-06 08:00");
+    scenario.switch_task(&state, "2024-03-06 08:30", DEV);
+    scenario.tick(&state, "2024-03-06 18:58");
+    scenario.tick(&state, "2024-03-06 19:00");
+    // The stop leaves the daemon running untracked until shutdown
+    scenario.stop_daemon(state, "2024-03-06 22:00");
+
+    // Saturday, the rule doesn't apply
+    let state = scenario.start_daemon("2024-03-09 17:55");
+    scenario.switch_task(&state, "2024-03-09 18:00", MEETING);
+    scenario.tick(&state, "2024-03-09 18:59");
+    scenario.tick(&state, "2024-03-09 19:00");
+    scenario.stop_daemon(state, "2024-03-09 19:30");
+
+    assert_eq!(
+        scenario.timesheet(),
+        "day,NONE,dev,meeting\n\
+         2024-03-06,03:30:00,10:30:00,00:00:00\n\
+         2024-03-09,00:05:00,00:00:00,01:30:00\n"
+    );
+}
+
+#[test]
+fn test_scheduled_return_after_restart() {
+    let scenario = Scenario::new();
+    scenario
+        .open()
+        .add_scheduled_switch(Some(MEETING), "09:30", &[], Some(900))
+        .unwrap();
+
+    let state = scenario.start_daemon("2024-03-06 08:55");
+    scenario.switch_task(&state, "2024-03-06 09:00", DEV);
+    scenario.tick(&state, "2024-03-06 09:29");
+    scenario.tick(&state, "2024-03-06 09:30");
+    // The daemon is killed during the standup and restarted, the user goes back to it
+    drop(state);
+    let state = scenario.start_daemon("2024-03-06 09:36");
+    scenario.switch_task(&state, "2024-03-06 09:37", MEETING);
+    scenario.tick(&state, "2024-03-06 09:44");
+    scenario.tick(&state, "2024-03-06 09:45");
+    scenario.stop_daemon(state, "2024-03-06 10:00");
+
+    assert_eq!(
+        scenario.timesheet(),
+        "day,NONE,dev,meeting\n\
+         2024-03-06,00:06:00,00:45:00,00:08:00\n"
+    );
+}
+
+#[test]
+fn test_pomodoro_day() {
+    let scenario = Scenario::new();
+
+    let state = scenario.start_daemon("2024-03-07 08:55");
+    scenario.clock.set(at("2024-03-07 09:00"));
+    state
+        .start_pomodoro(PomodoroSettings {
+            task_id: DEV,
+            work_minutes: 25,
+            break_minutes: 5,
+            cycles: 2,
+            break_task_id: None,
+        })
+        .unwrap();
+    // Only the ticks ending a phase report a switch
+    for (datetime, switched) in [
+        ("2024-03-07 09:10", false),
+        ("2024-03-07 09:25", true),
+        ("2024-03-07 09:30", true),
+        ("2024-03-07 09:55", true),
+    ] {
+        scenario.clock.set(at(datetime));
+        assert_eq!(state.pomodoro_tick().unwrap(), switched);
+    }
+    assert!(!state.has_pomodoro());
+    scenario.stop_daemon(state, "2024-03-07 10:00");
+
+    assert_eq!(
+        scenario.timesheet(),
+        "day,NONE,dev\n\
+         2024-03-07,00:10:00,00:55:00\n"
+    );
+}
+
+#[test]
+fn test_switch_in_same_second_as_startup() {
+    let scenario = Scenario::new();
+
+    let state = scenario.start_daemon("2024-03-08 09:00");
+    scenario.switch_task(&state, "2024-03-08 09:00", DEV);
+    scenario.switch_task(&state, "2024-03-08 12:00", MEETING);
+    scenario.switch_task(&state, "2024-03-08 12:00", DEV);
+    scenario.stop_daemon(state, "2024-03-08 17:00");
+
+    assert_eq!(
+        scenario.timesheet(),
+        "day,NONE,dev,meeting\n\
+         2024-03-08,00:00:00,08:00:00,00:00:00\n"
+    );
+}
+
+#[test]
+fn test_overtime_status_follows_past_changes() {
+    let scenario = Scenario::new();
+
+    let state = scenario.start_daemon("2024-03-04 08:00");
+    state.set_work_target(60, &[], "2024-03-04").unwrap();
+    scenario.switch_task(&state, "2024-03-04 09:00", DEV);
+    scenario.stop_daemon(state, "2024-03-04 11:00");
+
+    let state = scenario.start_daemon("2024-03-05 09:00");
+    scenario.switch_task(&state, "2024-03-05 09:00", DEV);
+    scenario.tick(&state, "2024-03-05 09:30");
+    let status = state.overtime_status().unwrap().unwrap();
+    assert_eq!((status.day_tracked, status.balance), (1800, 3600));
+
+    // Today keeps being computed while the previous days are reused
+    scenario.tick(&state, "2024-03-05 10:00");
+    let status = state.overtime_status().unwrap().unwrap();
+    assert_eq!((status.day_tracked, status.balance), (3600, 3600));
+
+    // A change to a previous day made by another process is picked up
+    scenario
+        .open()
+        .retro_switch_task(DEV as i32, at("2024-03-04 08:30") as i32, false)
+        .unwrap();
+    let status = state.overtime_status().unwrap().unwrap();
+    assert_eq!((status.day_tracked, status.balance), (3600, 5400));
+}
+
+#[test]
+fn test_switch_notes() {
+    let scenario = Scenario::new();
+
+    let state = scenario.start_daemon("2024-03-04 08:50");
+    scenario.clock.set(at("2024-03-04 09:00"));
+    state
+        .switch_task_with_note(DEV, Some(String::from("OCLOCK-1 export")))
+        .unwrap();
+    scenario.clock.set(at("2024-03-04 10:00"));
+    state
+        .switch_task_with_note(MEETING, Some(String::from(" ")))
+        .unwrap();
+    scenario.stop_daemon(state, "2024-03-04 10:30");
+
+    let day = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+    let notes: Vec<(String, Option<String>)> = scenario
+        .open()
+        .noted_intervals(day, day)
+        .unwrap()
+        .into_iter()
+        .map(|(interval, note)| (interval.task, note))
+        .collect();
+    assert_eq!(
+        notes,
+        vec![
+            (String::from("dev"), Some(String::from("OCLOCK-1 export"))),
+            (String::from("meeting"), None),
+        ]
+    );
+}
+
+#[test]
+fn test_calendar_meeting_splits_tracked_time() {
+    let scenario = Scenario::new();
+
+    let state = scenario.start_daemon("2024-03-04 08:50");
+    scenario.switch_task(&state, "2024-03-04 09:00", DEV);
+    scenario.stop_daemon(state, "2024-03-04 12:00");
+
+    let events = [IcsEvent {
+        summary: String::from("Sprint review"),
+        categories: vec![],
+        start: at("2024-03-04 10:00"),
+        end: at("2024-03-04 10:45"),
+        zone: EventZone::Floating,
+        rrule: None,
+        exdates: vec![],
+    }];
+    let rules = [CalendarRule {
+        field: CalendarField::Summary,
+        pattern: String::from("review"),
+        task_id: MEETING,
+    }];
+
+    let state = scenario.start_daemon("2024-03-05 08:50");
+    let summary = state.import_calendar(&events, &rules, false).unwrap();
+    assert_eq!(summary.intervals.conflicts.len(), 1);
+
+    let summary = state.import_calendar(&events, &rules, true).unwrap();
+    assert_eq!(summary.intervals.intervals.len(), 1);
+    scenario.stop_daemon(state, "2024-03-05 08:55");
+
+    assert_eq!(
+        scenario.timesheet(),
+        "day,NONE,dev,meeting\n\
+         2024-03-04,00:10:00,02:15:00,00:45:00\n\
+         2024-03-05,00:05:00,00:00:00,00:00:00\n"
+    );
+}